@@ -0,0 +1 @@
+DROP TABLE quarantined_tasks;
\ No newline at end of file
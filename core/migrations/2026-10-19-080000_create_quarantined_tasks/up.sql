@@ -0,0 +1,8 @@
+CREATE TABLE quarantined_tasks
+(
+    id_task          TEXT                                NOT NULL
+        CONSTRAINT quarantined_tasks_pk PRIMARY KEY,
+    payload          TEXT                                NOT NULL,
+    reason           TEXT                                NOT NULL,
+    quarantined_date TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,34 @@
+DROP TRIGGER remove_sub_tasks_on_task_delete;
+
+ALTER TABLE tasks ADD COLUMN sub_tasks TEXT DEFAULT "[]" NOT NULL;
+
+UPDATE tasks
+SET sub_tasks = (SELECT json_group_array(json(item))
+                 FROM (SELECT json_object(
+                                  'id', id_sub_task,
+                                  'parent', parent_task,
+                                  'title', title,
+                                  'favorite', json('false'),
+                                  'today', json('false'),
+                                  'status', CASE status WHEN 1 THEN 'Completed' ELSE 'NotStarted' END,
+                                  'priority', 'Low',
+                                  'sub_tasks', json('[]'),
+                                  'tags', json('[]'),
+                                  'notes', notes,
+                                  'completion_date', NULL,
+                                  'deletion_date', NULL,
+                                  'due_date', strftime('%Y-%m-%dT%H:%M:%SZ', due_date),
+                                  'reminder_date', NULL,
+                                  'recurrence', json_object(
+                                      'monday', json('false'), 'tuesday', json('false'),
+                                      'wednesday', json('false'), 'thursday', json('false'),
+                                      'friday', json('false'), 'saturday', json('false'),
+                                      'sunday', json('false')),
+                                  'created_date_time', strftime('%Y-%m-%dT%H:%M:%SZ', created_date_time),
+                                  'last_modified_date_time', strftime('%Y-%m-%dT%H:%M:%SZ', last_modified_date_time)
+                              ) AS item
+                       FROM sub_tasks
+                       WHERE sub_tasks.parent_task = tasks.id_task
+                       ORDER BY position));
+
+DROP TABLE sub_tasks;
@@ -0,0 +1,108 @@
+CREATE TABLE sub_tasks
+(
+    id_sub_task             TEXT                                NOT NULL
+        CONSTRAINT sub_tasks_pk PRIMARY KEY,
+    parent_task             TEXT                                NOT NULL,
+    root_task               TEXT                                NOT NULL,
+    position                INTEGER   DEFAULT 0                 NOT NULL,
+    title                   TEXT                                NOT NULL,
+    status                  INTEGER   DEFAULT 0                 NOT NULL,
+    notes                   TEXT      DEFAULT ''                NOT NULL,
+    due_date                TIMESTAMP,
+    created_date_time       TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_date_time TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX sub_tasks_root_task_index ON sub_tasks (root_task);
+
+-- Sub-tasks could have their own sub-tasks, walk the whole tree. Ids are
+-- picked once per node so children can point at their parent.
+INSERT OR IGNORE INTO sub_tasks
+WITH RECURSIVE tree(id, parent_task, root_task, position, value) AS (
+    SELECT COALESCE(NULLIF(json_extract(item.value, '$.id'), ''), lower(hex(randomblob(16)))),
+           tasks.id_task,
+           tasks.id_task,
+           item.key,
+           item.value
+    FROM tasks,
+         json_each(CASE
+                       WHEN json_valid(tasks.sub_tasks) AND json_type(tasks.sub_tasks) = 'array'
+                           THEN tasks.sub_tasks
+                       ELSE '[]' END) AS item
+    WHERE item.type = 'object'
+    UNION ALL
+    SELECT COALESCE(NULLIF(json_extract(child.value, '$.id'), ''), lower(hex(randomblob(16)))),
+           tree.id,
+           tree.root_task,
+           child.key,
+           child.value
+    FROM tree,
+         json_each(CASE json_type(tree.value, '$.sub_tasks')
+                       WHEN 'array' THEN json_extract(tree.value, '$.sub_tasks')
+                       ELSE '[]' END) AS child
+    WHERE child.type = 'object'
+)
+SELECT id,
+       parent_task,
+       root_task,
+       position,
+       COALESCE(json_extract(value, '$.title'), ''),
+       CASE json_extract(value, '$.status') WHEN 'Completed' THEN 1 ELSE 0 END,
+       COALESCE(json_extract(value, '$.notes'), ''),
+       datetime(json_extract(value, '$.due_date')),
+       COALESCE(datetime(json_extract(value, '$.created_date_time')), CURRENT_TIMESTAMP),
+       COALESCE(datetime(json_extract(value, '$.last_modified_date_time')), CURRENT_TIMESTAMP)
+FROM tree;
+
+CREATE TABLE temp_tasks AS
+SELECT *
+FROM tasks;
+
+DROP TABLE tasks;
+
+CREATE TABLE tasks
+(
+    id_task                 TEXT                                NOT NULL
+        CONSTRAINT tasks_pk PRIMARY KEY,
+    parent                  TEXT                                NOT NULL,
+    title                   TEXT                                NOT NULL,
+    notes                   TEXT                                NOT NULL,
+    priority                INTEGER   DEFAULT 1                 NOT NULL,
+    favorite                BOOLEAN   DEFAULT false             NOT NULL,
+    status                  INTEGER   DEFAULT 1                 NOT NULL,
+    completion_date         TIMESTAMP,
+    due_date                TIMESTAMP,
+    reminder_date           TIMESTAMP,
+    created_date_time       TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_date_time TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    tags                    TEXT      DEFAULT "[]"              NOT NULL,
+    today                   BOOLEAN   DEFAULT false             NOT NULL,
+    deletion_date           TIMESTAMP,
+    recurrence              TEXT
+);
+
+INSERT INTO tasks
+SELECT id_task,
+       parent,
+       title,
+       notes,
+       priority,
+       favorite,
+       status,
+       completion_date,
+       due_date,
+       reminder_date,
+       created_date_time,
+       last_modified_date_time,
+       tags,
+       today,
+       deletion_date,
+       recurrence
+FROM temp_tasks;
+
+DROP TABLE temp_tasks;
+
+CREATE TRIGGER remove_sub_tasks_on_task_delete BEFORE DELETE ON tasks BEGIN
+DELETE FROM sub_tasks
+WHERE sub_tasks.root_task = old.id_task;
+END;
@@ -0,0 +1,14 @@
+CREATE TABLE task_dependencies
+(
+    id_task          TEXT                  NOT NULL,
+    depends_on       TEXT                  NOT NULL,
+    depends_on_title TEXT      DEFAULT ''  NOT NULL,
+    resolved         BOOLEAN   DEFAULT false NOT NULL,
+    CONSTRAINT task_dependencies_pk PRIMARY KEY (id_task, depends_on)
+);
+
+CREATE TRIGGER remove_dependencies_on_task_delete BEFORE DELETE ON tasks BEGIN
+DELETE FROM task_dependencies
+WHERE task_dependencies.id_task = old.id_task
+   OR task_dependencies.depends_on = old.id_task;
+END;
@@ -0,0 +1,3 @@
+DROP TRIGGER remove_time_entries_on_task_delete;
+DROP INDEX time_entries_task_index;
+DROP TABLE time_entries;
\ No newline at end of file
@@ -0,0 +1,14 @@
+CREATE TABLE time_entries
+(
+    id_time_entry TEXT PRIMARY KEY NOT NULL,
+    id_task       TEXT             NOT NULL,
+    start_time    TIMESTAMP        NOT NULL,
+    end_time      TIMESTAMP,
+    note          TEXT DEFAULT ''  NOT NULL
+);
+
+CREATE INDEX time_entries_task_index ON time_entries (id_task);
+
+CREATE TRIGGER remove_time_entries_on_task_delete BEFORE DELETE ON tasks BEGIN
+DELETE FROM time_entries WHERE time_entries.id_task = old.id_task;
+END;
@@ -0,0 +1,2 @@
+DROP TABLE task_overlays;
+ALTER TABLE tasks DROP COLUMN estimate;
\ No newline at end of file
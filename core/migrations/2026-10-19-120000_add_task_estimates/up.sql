@@ -0,0 +1,8 @@
+ALTER TABLE tasks ADD COLUMN estimate INTEGER;
+
+-- Values for tasks that live in remote services which can't store them.
+CREATE TABLE task_overlays
+(
+    id_task  TEXT PRIMARY KEY NOT NULL,
+    estimate INTEGER
+);
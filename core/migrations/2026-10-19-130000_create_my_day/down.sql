@@ -0,0 +1 @@
+DROP TABLE my_day;
@@ -0,0 +1,10 @@
+CREATE TABLE my_day
+(
+    id_task    TEXT PRIMARY KEY NOT NULL,
+    planned_on DATE             NOT NULL
+);
+
+INSERT INTO my_day (id_task, planned_on)
+SELECT id_task, date('now', 'localtime')
+FROM tasks
+WHERE today = 1;
@@ -0,0 +1,2 @@
+-- Nothing to undo, midnight UTC is still a valid timestamp.
+SELECT 1;
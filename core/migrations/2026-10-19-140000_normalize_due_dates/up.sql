@@ -0,0 +1,14 @@
+-- Due dates are calendar days, stored as midnight UTC of that day.
+UPDATE tasks
+SET due_date = date(due_date) || ' 00:00:00'
+WHERE due_date IS NOT NULL;
+
+UPDATE sub_tasks
+SET due_date = date(due_date) || ' 00:00:00'
+WHERE due_date IS NOT NULL;
+
+-- The other timestamps (completion_date, deletion_date, reminder_date,
+-- created_date_time and last_modified_date_time of tasks and sub_tasks) stay
+-- as they are: they were written with `naive_local()` of a `DateTime<Utc>`,
+-- which is already UTC, the same value `naive_utc()` gives now. Running them
+-- through datetime(..., 'utc') would shift them by the local offset.
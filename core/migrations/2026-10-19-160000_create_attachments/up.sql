@@ -0,0 +1,11 @@
+CREATE TABLE attachments
+(
+    id_attachment     TEXT PRIMARY KEY NOT NULL,
+    id_task           TEXT             NOT NULL,
+    name              TEXT             NOT NULL,
+    content_type      TEXT             NOT NULL,
+    size              BIGINT           NOT NULL,
+    created_date_time TIMESTAMP        NOT NULL
+);
+
+CREATE INDEX attachments_task_index ON attachments (id_task);
@@ -0,0 +1 @@
+ALTER TABLE tasks ADD COLUMN linked_resources TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,8 @@
+CREATE TABLE accounts
+(
+    id_account        TEXT PRIMARY KEY NOT NULL,
+    provider          TEXT             NOT NULL,
+    name              TEXT             NOT NULL,
+    remote_id         TEXT             NOT NULL,
+    created_date_time TIMESTAMP        NOT NULL
+);
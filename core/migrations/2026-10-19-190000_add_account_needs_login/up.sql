@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN needs_login BOOLEAN NOT NULL DEFAULT 0;
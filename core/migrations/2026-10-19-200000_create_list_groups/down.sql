@@ -0,0 +1,2 @@
+ALTER TABLE lists DROP COLUMN id_group;
+DROP TABLE list_groups;
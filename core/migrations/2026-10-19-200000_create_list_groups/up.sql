@@ -0,0 +1,8 @@
+CREATE TABLE list_groups
+(
+    id_group TEXT PRIMARY KEY NOT NULL,
+    name     TEXT             NOT NULL,
+    position INTEGER          NOT NULL DEFAULT 0,
+    expanded BOOLEAN          NOT NULL DEFAULT 1
+);
+ALTER TABLE lists ADD COLUMN id_group TEXT;
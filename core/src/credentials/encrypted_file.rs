@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::num::NonZeroU32;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use ring::aead::{
+	Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN,
+};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use super::{entry_name, CredentialStore};
+
+/// Environment variable with a passphrase to derive the encryption key from,
+/// instead of the generated key file.
+pub const PASSPHRASE_VARIABLE: &str = "DONE_CREDENTIAL_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const ITERATIONS: u32 = 100_000;
+
+/// Stores secrets in a ChaCha20-Poly1305 encrypted file, for setups without a
+/// keyring.
+///
+/// The key is derived from [`PASSPHRASE_VARIABLE`] when it is set. Otherwise
+/// it comes from a random key file stored next to the credentials, which is
+/// obfuscation only: anyone who can read that directory can decrypt the
+/// secrets. It only helps when the credentials file is copied on its own.
+#[derive(Debug)]
+pub struct EncryptedFileStore {
+	path: PathBuf,
+	key_path: PathBuf,
+	/// The derived key along with its salt, derived on first use. Also
+	/// serializes access to the file.
+	key: Mutex<Option<DerivedKey>>,
+}
+
+/// A key derived from the secret, with the salt stored in the file header.
+struct DerivedKey {
+	salt: [u8; SALT_LEN],
+	key: LessSafeKey,
+}
+
+impl std::fmt::Debug for DerivedKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("DerivedKey").finish_non_exhaustive()
+	}
+}
+
+impl EncryptedFileStore {
+	pub fn new(application_id: &str) -> Result<Self> {
+		let directory = dirs::data_dir()
+			.context("No directory to store credentials in")?
+			.join(application_id);
+		Self::in_directory(directory)
+	}
+
+	/// A store keeping its files in `directory`.
+	pub fn in_directory(directory: PathBuf) -> Result<Self> {
+		fs::create_dir_all(&directory)?;
+		Ok(Self {
+			path: directory.join("credentials"),
+			key_path: directory.join("credentials.key"),
+			key: Mutex::new(None),
+		})
+	}
+
+	fn secret(&self) -> Result<Vec<u8>> {
+		if let Ok(passphrase) = std::env::var(PASSPHRASE_VARIABLE) {
+			return Ok(passphrase.into_bytes());
+		}
+		if self.key_path.exists() {
+			return fs::read(&self.key_path).context("Failed to read the key file");
+		}
+		let mut key = [0; KEY_LEN];
+		SystemRandom::new()
+			.fill(&mut key)
+			.map_err(|_| anyhow::anyhow!("Failed to generate a key"))?;
+		write_private(&self.key_path, &key)?;
+		Ok(key.to_vec())
+	}
+
+	fn derive(&self, salt: [u8; SALT_LEN]) -> Result<DerivedKey> {
+		let mut key = [0; KEY_LEN];
+		pbkdf2::derive(
+			pbkdf2::PBKDF2_HMAC_SHA256,
+			NonZeroU32::new(ITERATIONS).unwrap(),
+			&salt,
+			&self.secret()?,
+			&mut key,
+		);
+		let key = UnboundKey::new(&CHACHA20_POLY1305, &key)
+			.map_err(|_| anyhow::anyhow!("Invalid key"))?;
+		Ok(DerivedKey {
+			salt,
+			key: LessSafeKey::new(key),
+		})
+	}
+
+	/// The key for `salt`, derived again only when the salt changed, like
+	/// when another process rewrote the file.
+	fn key<'a>(
+		&self,
+		cached: &'a mut Option<DerivedKey>,
+		salt: [u8; SALT_LEN],
+	) -> Result<&'a LessSafeKey> {
+		if cached.as_ref().is_none_or(|cached| cached.salt != salt) {
+			*cached = Some(self.derive(salt)?);
+		}
+		Ok(&cached.as_ref().unwrap().key)
+	}
+
+	fn load(
+		&self,
+		cached: &mut Option<DerivedKey>,
+	) -> Result<HashMap<String, String>> {
+		if !self.path.exists() {
+			return Ok(HashMap::new());
+		}
+		let mut data = fs::read(&self.path)?;
+		if data.len() < SALT_LEN + NONCE_LEN {
+			bail!("The credentials file is truncated")
+		}
+		let mut sealed = data.split_off(SALT_LEN + NONCE_LEN);
+		let (salt, nonce) = data.split_at(SALT_LEN);
+		let nonce = Nonce::try_assume_unique_for_key(nonce)
+			.map_err(|_| anyhow::anyhow!("Invalid nonce"))?;
+		let plain = self
+			.key(cached, salt.try_into()?)?
+			.open_in_place(nonce, Aad::empty(), &mut sealed)
+			.map_err(|_| {
+				anyhow::anyhow!("The credentials file can't be decrypted")
+			})?;
+		Ok(serde_json::from_slice(plain)?)
+	}
+
+	fn save(
+		&self,
+		cached: &mut Option<DerivedKey>,
+		secrets: &HashMap<String, String>,
+	) -> Result<()> {
+		let random = SystemRandom::new();
+		let salt = match cached {
+			Some(cached) => cached.salt,
+			None => {
+				let mut salt = [0; SALT_LEN];
+				random
+					.fill(&mut salt)
+					.map_err(|_| anyhow::anyhow!("Failed to generate a salt"))?;
+				salt
+			},
+		};
+		let mut nonce = [0; NONCE_LEN];
+		random
+			.fill(&mut nonce)
+			.map_err(|_| anyhow::anyhow!("Failed to generate a nonce"))?;
+
+		let mut sealed = serde_json::to_vec(secrets)?;
+		self
+			.key(cached, salt)?
+			.seal_in_place_append_tag(
+				Nonce::assume_unique_for_key(nonce),
+				Aad::empty(),
+				&mut sealed,
+			)
+			.map_err(|_| anyhow::anyhow!("Failed to encrypt the credentials"))?;
+
+		let mut data = salt.to_vec();
+		data.extend_from_slice(&nonce);
+		data.extend(sealed);
+		write_private(&self.path, &data)
+	}
+}
+
+impl CredentialStore for EncryptedFileStore {
+	fn get(&self, account: &str, key: &str) -> Result<Option<String>> {
+		let mut cached = self.key.lock().unwrap();
+		Ok(self.load(&mut cached)?.remove(&entry_name(account, key)))
+	}
+
+	fn set(&self, account: &str, key: &str, secret: &str) -> Result<()> {
+		let mut cached = self.key.lock().unwrap();
+		let mut secrets = self.load(&mut cached)?;
+		secrets.insert(entry_name(account, key), secret.to_string());
+		self.save(&mut cached, &secrets)
+	}
+
+	fn delete(&self, account: &str, key: &str) -> Result<()> {
+		let mut cached = self.key.lock().unwrap();
+		let mut secrets = self.load(&mut cached)?;
+		if secrets.remove(&entry_name(account, key)).is_some() {
+			self.save(&mut cached, &secrets)?;
+		}
+		Ok(())
+	}
+}
+
+/// Writes a file only the current user can read.
+///
+/// The data goes to a temporary file next to `path` first, which then
+/// replaces it, so a crash halfway never leaves a truncated file behind and
+/// the permissions apply even when `path` already existed.
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+	let name = path
+		.file_name()
+		.with_context(|| format!("{} is not a file", path.display()))?;
+	let temporary = path.with_file_name(format!(
+		".{}.{}.tmp",
+		name.to_string_lossy(),
+		uuid::Uuid::new_v4()
+	));
+	let written = write_new(&temporary, data)
+		.and_then(|_| fs::rename(&temporary, path).map_err(Into::into));
+	if written.is_err() {
+		let _ = fs::remove_file(&temporary);
+	}
+	written.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn write_new(path: &Path, data: &[u8]) -> Result<()> {
+	let mut options = OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	options.mode(0o600);
+	let mut file = options.open(path)?;
+	file.write_all(data)?;
+	file.sync_all()?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn store() -> (EncryptedFileStore, PathBuf) {
+		let directory =
+			std::env::temp_dir().join(format!("done-{}", uuid::Uuid::new_v4()));
+		(EncryptedFileStore::in_directory(directory.clone()).unwrap(), directory)
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn writes_files_only_the_user_can_read() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let (store, directory) = store();
+		let path = directory.join("credentials");
+		store.set("account", "token", "secret").unwrap();
+		// Rewriting an existing file keeps it private too.
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+		store.set("account", "token", "other").unwrap();
+
+		for name in ["credentials", "credentials.key"] {
+			let mode = fs::metadata(directory.join(name)).unwrap().permissions().mode();
+			assert_eq!(mode & 0o777, 0o600, "{name}");
+		}
+		assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+		fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn keeps_a_corrupt_file_untouched() {
+		let (store, directory) = store();
+		store.set("account", "token", "secret").unwrap();
+		let path = directory.join("credentials");
+		let mut data = fs::read(&path).unwrap();
+		*data.last_mut().unwrap() ^= 1;
+		fs::write(&path, &data).unwrap();
+
+		assert!(store.get("account", "token").is_err());
+		assert!(store.set("account", "token", "other").is_err());
+		assert_eq!(fs::read(&path).unwrap(), data);
+		fs::remove_dir_all(directory).unwrap();
+	}
+
+	#[test]
+	fn reads_secrets_written_by_another_store() {
+		let (store, directory) = store();
+		store.set("account", "token", "secret").unwrap();
+
+		let reopened = EncryptedFileStore::in_directory(directory.clone()).unwrap();
+		assert_eq!(
+			reopened.get("account", "token").unwrap().as_deref(),
+			Some("secret")
+		);
+		let data = fs::read(directory.join("credentials")).unwrap();
+		assert!(!String::from_utf8_lossy(&data).contains("secret"));
+		fs::remove_dir_all(directory).unwrap();
+	}
+}
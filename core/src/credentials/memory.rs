@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyhow::Result;
+
+use super::{entry_name, CredentialStore};
+
+/// Keeps secrets for the lifetime of the process, meant for tests.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+	secrets: Mutex<HashMap<String, String>>,
+}
+
+impl CredentialStore for MemoryStore {
+	fn get(&self, account: &str, key: &str) -> Result<Option<String>> {
+		let secrets = self.secrets.lock().unwrap();
+		Ok(secrets.get(&entry_name(account, key)).cloned())
+	}
+
+	fn set(&self, account: &str, key: &str, secret: &str) -> Result<()> {
+		let mut secrets = self.secrets.lock().unwrap();
+		secrets.insert(entry_name(account, key), secret.to_string());
+		Ok(())
+	}
+
+	fn delete(&self, account: &str, key: &str) -> Result<()> {
+		let mut secrets = self.secrets.lock().unwrap();
+		secrets.remove(&entry_name(account, key));
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trips_secrets() {
+		let store = MemoryStore::default();
+		store.set("account", "token", "secret").unwrap();
+		store.set("other", "token", "another").unwrap();
+
+		assert_eq!(
+			store.get("account", "token").unwrap().as_deref(),
+			Some("secret")
+		);
+		assert_eq!(
+			store.get("other", "token").unwrap().as_deref(),
+			Some("another")
+		);
+
+		store.delete("account", "token").unwrap();
+		assert_eq!(store.get("account", "token").unwrap(), None);
+	}
+}
@@ -0,0 +1,78 @@
+//! Storage for the secrets providers need, like OAuth tokens.
+//!
+//! Secrets are namespaced per account, so several accounts of the same
+//! provider can be signed in at once without overwriting each other.
+
+use std::fmt::Debug;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{bail, Result};
+
+pub mod encrypted_file;
+pub mod memory;
+pub mod secret_service;
+
+pub use encrypted_file::EncryptedFileStore;
+pub use memory::MemoryStore;
+pub use secret_service::SecretServiceStore;
+
+/// Environment variable to force a store: `secret-service`, `file` or
+/// `memory`.
+pub const STORE_VARIABLE: &str = "DONE_CREDENTIAL_STORE";
+
+static STORE: OnceLock<Arc<dyn CredentialStore>> = OnceLock::new();
+
+pub trait CredentialStore: Debug + Send + Sync {
+	/// Reads the secret stored under `key` for `account`.
+	fn get(&self, account: &str, key: &str) -> Result<Option<String>>;
+
+	/// Stores `secret` under `key` for `account`, replacing any previous one.
+	fn set(&self, account: &str, key: &str, secret: &str) -> Result<()>;
+
+	/// Removes the secret stored under `key` for `account`, if any.
+	fn delete(&self, account: &str, key: &str) -> Result<()>;
+}
+
+/// The store shared by every provider, picked on first use.
+///
+/// The Secret Service is preferred, the encrypted file is used when it can't
+/// be reached, like on headless or sandboxed setups without a keyring.
+pub fn credential_store(application_id: &str) -> Arc<dyn CredentialStore> {
+	STORE
+		.get_or_init(|| match open_store(application_id) {
+			Ok(store) => store,
+			Err(err) => {
+				tracing::error!("Secrets will not be persisted: {err:#}");
+				Arc::new(MemoryStore::default())
+			},
+		})
+		.clone()
+}
+
+fn open_store(application_id: &str) -> Result<Arc<dyn CredentialStore>> {
+	let requested = std::env::var(STORE_VARIABLE).ok();
+	match requested.as_deref() {
+		Some("secret-service") => {
+			Ok(Arc::new(SecretServiceStore::new(application_id)))
+		},
+		Some("file") => Ok(Arc::new(EncryptedFileStore::new(application_id)?)),
+		Some("memory") => Ok(Arc::new(MemoryStore::default())),
+		Some(other) => bail!("Unknown {STORE_VARIABLE} value: {other}"),
+		None => {
+			let secret_service = SecretServiceStore::new(application_id);
+			if secret_service.reachable() {
+				Ok(Arc::new(secret_service))
+			} else {
+				tracing::warn!(
+					"The Secret Service is unavailable, using an encrypted file"
+				);
+				Ok(Arc::new(EncryptedFileStore::new(application_id)?))
+			}
+		},
+	}
+}
+
+/// The name a secret is stored under in flat key-value stores.
+pub(crate) fn entry_name(account: &str, key: &str) -> String {
+	format!("{account}/{key}")
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+
+use super::{entry_name, CredentialStore};
+
+/// Stores secrets in the desktop keyring through libsecret.
+///
+/// Secrets are cached after the first read, so checking whether an account
+/// is signed in doesn't go through D-Bus every time.
+#[derive(Debug, Clone)]
+pub struct SecretServiceStore {
+	application_id: String,
+	cache: Arc<Mutex<HashMap<String, Option<String>>>>,
+}
+
+impl SecretServiceStore {
+	pub fn new(application_id: &str) -> Self {
+		Self {
+			application_id: application_id.to_string(),
+			cache: Default::default(),
+		}
+	}
+
+	/// Whether a keyring answers on the session bus.
+	pub fn reachable(&self) -> bool {
+		keytar::get_password(&self.application_id, "probe").is_ok()
+	}
+}
+
+impl CredentialStore for SecretServiceStore {
+	fn get(&self, account: &str, key: &str) -> Result<Option<String>> {
+		let name = entry_name(account, key);
+		let mut cache = self.cache.lock().unwrap();
+		if let Some(secret) = cache.get(&name) {
+			return Ok(secret.clone());
+		}
+		let password = keytar::get_password(&self.application_id, &name)
+			.context("Failed to read from the keyring")?;
+		let secret = (password.success && !password.password.is_empty())
+			.then_some(password.password);
+		cache.insert(name, secret.clone());
+		Ok(secret)
+	}
+
+	fn set(&self, account: &str, key: &str, secret: &str) -> Result<()> {
+		let name = entry_name(account, key);
+		keytar::set_password(&self.application_id, &name, secret)
+			.context("Failed to write to the keyring")?;
+		self
+			.cache
+			.lock()
+			.unwrap()
+			.insert(name, Some(secret.to_string()));
+		Ok(())
+	}
+
+	fn delete(&self, account: &str, key: &str) -> Result<()> {
+		let name = entry_name(account, key);
+		keytar::delete_password(&self.application_id, &name)
+			.context("Failed to delete from the keyring")?;
+		self.cache.lock().unwrap().insert(name, None);
+		Ok(())
+	}
+}
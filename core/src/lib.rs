@@ -1,3 +1,4 @@
+pub mod credentials;
 pub mod models;
 pub(crate) mod schema;
 pub mod service;
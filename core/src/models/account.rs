@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An account signed in to a remote provider, several accounts of the same
+/// provider can be used at once.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Account {
+	pub id: Uuid,
+	/// Which provider the account belongs to, like `microsoft`.
+	pub provider: String,
+	/// What the user recognizes the account by, usually an email address.
+	pub name: String,
+	/// The provider's id for the user, used to recognize an account that
+	/// signs in again.
+	pub remote_id: String,
+	pub created_date_time: DateTime<Utc>,
+	/// The provider stopped accepting the account's sign-in, the user has to
+	/// sign in again before it syncs.
+	pub needs_login: bool,
+}
+
+impl Account {
+	pub fn new(id: Uuid, provider: &str, name: &str, remote_id: &str) -> Self {
+		Self {
+			id,
+			provider: provider.to_string(),
+			name: name.to_string(),
+			remote_id: remote_id.to_string(),
+			created_date_time: Utc::now(),
+			needs_login: false,
+		}
+	}
+}
@@ -0,0 +1,80 @@
+use chrono::NaiveDate;
+
+use crate::service::Service;
+
+use super::{dates::to_local, task::Task};
+
+/// Why a task shows up on a given day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AgendaEntryKind {
+	Due,
+	Reminder,
+	/// A projected future occurrence of a recurring task.
+	Occurrence,
+}
+
+impl AgendaEntryKind {
+	pub fn as_str_name(&self) -> &'static str {
+		match self {
+			AgendaEntryKind::Due => "DUE",
+			AgendaEntryKind::Reminder => "REMINDER",
+			AgendaEntryKind::Occurrence => "OCCURRENCE",
+		}
+	}
+
+	pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+		match value {
+			"DUE" => Some(Self::Due),
+			"REMINDER" => Some(Self::Reminder),
+			"OCCURRENCE" => Some(Self::Occurrence),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgendaEntry {
+	pub service: Service,
+	pub task: Task,
+	pub date: NaiveDate,
+	pub kind: AgendaEntryKind,
+}
+
+/// Places every task on the days between `from` and `to` (inclusive) where it
+/// is due, has a reminder or is projected to repeat.
+pub fn agenda_entries(
+	tasks: &[(Service, Task)],
+	from: NaiveDate,
+	to: NaiveDate,
+) -> Vec<AgendaEntry> {
+	let mut entries = vec![];
+	let in_range = |date: &NaiveDate| *date >= from && *date <= to;
+	for (service, task) in tasks {
+		let entry = |date, kind| AgendaEntry {
+			service: *service,
+			task: task.clone(),
+			date,
+			kind,
+		};
+		let due_date = task.due_date.map(|date| date.date_naive());
+		if let Some(date) = due_date.filter(in_range) {
+			entries.push(entry(date, AgendaEntryKind::Due));
+		}
+		if let Some(date) = task
+			.reminder_date
+			.map(|date| to_local(date).date_naive())
+			.filter(in_range)
+		{
+			entries.push(entry(date, AgendaEntryKind::Reminder));
+		}
+		if !task.status.is_finished() {
+			let after = due_date
+				.unwrap_or(to_local(task.created_date_time).date_naive());
+			for date in task.recurrence.occurrences(after, from, to) {
+				entries.push(entry(date, AgendaEntryKind::Occurrence));
+			}
+		}
+	}
+	entries.sort_by(|a, b| (a.date, a.kind).cmp(&(b.date, b.kind)));
+	entries
+}
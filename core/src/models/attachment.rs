@@ -0,0 +1,172 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A file attached to a task.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Attachment {
+	pub id: String,
+	pub task_id: String,
+	pub name: String,
+	pub content_type: String,
+	/// Size in bytes.
+	pub size: u64,
+	pub created_date_time: DateTime<Utc>,
+}
+
+impl Attachment {
+	/// Describes `path` as a new attachment of a task, without copying it.
+	pub fn from_path(task_id: String, path: &Path) -> Result<Self> {
+		let name = path
+			.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.with_context(|| format!("{} is not a file", path.display()))?;
+		let size = std::fs::metadata(path)
+			.with_context(|| format!("Failed to read {}", path.display()))?
+			.len();
+		Ok(Self {
+			id: Uuid::new_v4().to_string(),
+			task_id,
+			content_type: content_type(path).to_string(),
+			name,
+			size,
+			created_date_time: Utc::now(),
+		})
+	}
+
+	/// Where the file of this attachment lives under `directory`, each
+	/// attachment gets its own folder so the file keeps its name.
+	///
+	/// Ids and names can come from a remote service, so only their last
+	/// component is used and the path never leaves `directory`.
+	pub fn path_in(&self, directory: &Path) -> Result<PathBuf> {
+		Ok(
+			directory
+				.join(path_component(&self.id)?)
+				.join(path_component(&self.name)?),
+		)
+	}
+}
+
+/// Reduces `value` to a single file name, fails if nothing usable is left.
+fn path_component(value: &str) -> Result<&OsStr> {
+	Path::new(value)
+		.file_name()
+		.filter(|name| !matches!(name.to_str(), Some("" | "." | "..")))
+		.with_context(|| format!("{value:?} is not a valid file name"))
+}
+
+/// Where attachment files are kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachmentStore {
+	/// Files that only exist on this computer.
+	Data,
+	/// Downloaded copies of files stored by a remote service.
+	Cache,
+}
+
+pub fn attachments_dir(
+	application_id: &str,
+	store: AttachmentStore,
+) -> Result<PathBuf> {
+	let base = match store {
+		AttachmentStore::Data => dirs::data_dir(),
+		AttachmentStore::Cache => dirs::cache_dir(),
+	}
+	.context("No directory to store attachments in")?;
+	Ok(base.join(application_id).join("attachments"))
+}
+
+/// Guesses the MIME type of a file from its extension.
+pub fn content_type(path: &Path) -> &'static str {
+	let extension = path
+		.extension()
+		.map(|extension| extension.to_string_lossy().to_lowercase())
+		.unwrap_or_default();
+	match extension.as_str() {
+		"txt" | "log" => "text/plain",
+		"md" => "text/markdown",
+		"csv" => "text/csv",
+		"html" | "htm" => "text/html",
+		"json" => "application/json",
+		"pdf" => "application/pdf",
+		"zip" => "application/zip",
+		"png" => "image/png",
+		"jpg" | "jpeg" => "image/jpeg",
+		"gif" => "image/gif",
+		"svg" => "image/svg+xml",
+		"webp" => "image/webp",
+		"mp3" => "audio/mpeg",
+		"ogg" => "audio/ogg",
+		"mp4" => "video/mp4",
+		"doc" => "application/msword",
+		"docx" => {
+			"application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+		},
+		"xls" => "application/vnd.ms-excel",
+		"xlsx" => {
+			"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+		},
+		"ppt" => "application/vnd.ms-powerpoint",
+		"pptx" => {
+			"application/vnd.openxmlformats-officedocument.presentationml.presentation"
+		},
+		"odt" => "application/vnd.oasis.opendocument.text",
+		"ods" => "application/vnd.oasis.opendocument.spreadsheet",
+		_ => "application/octet-stream",
+	}
+}
+
+/// Formats a size in bytes for display, e.g. `1.2 MB`.
+pub fn format_size(size: u64) -> String {
+	const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+	let mut value = size as f64;
+	let mut unit = 0;
+	while value >= 1024.0 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
+	}
+	if unit == 0 {
+		format!("{size} {}", UNITS[0])
+	} else {
+		format!("{value:.1} {}", UNITS[unit])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn attachment(id: &str, name: &str) -> Attachment {
+		Attachment {
+			id: id.to_string(),
+			task_id: "task".to_string(),
+			name: name.to_string(),
+			content_type: "text/plain".to_string(),
+			size: 0,
+			created_date_time: Utc::now(),
+		}
+	}
+
+	#[test]
+	fn keeps_paths_inside_the_directory() {
+		let directory = Path::new("/cache/attachments");
+		let path = attachment("../../id", "../../../etc/passwd")
+			.path_in(directory)
+			.unwrap();
+		assert_eq!(path, directory.join("id").join("passwd"));
+	}
+
+	#[test]
+	fn rejects_names_without_a_file_name() {
+		let directory = Path::new("/cache/attachments");
+		assert!(attachment("..", "file.txt").path_in(directory).is_err());
+		assert!(attachment("id", "").path_in(directory).is_err());
+		assert!(attachment("id", "/").path_in(directory).is_err());
+		assert!(attachment("id", "a/..").path_in(directory).is_err());
+	}
+}
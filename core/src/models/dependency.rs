@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+/// States that `task_id` can't be started until `prerequisite_id` is done.
+/// Both tasks may live in different lists or services.
+#[derive(
+	Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct Dependency {
+	pub task_id: String,
+	pub prerequisite_id: String,
+	pub prerequisite_title: String,
+	pub resolved: bool,
+}
+
+impl Dependency {
+	pub fn new(task_id: String, prerequisite_id: String, title: String) -> Self {
+		Self {
+			task_id,
+			prerequisite_id,
+			prerequisite_title: title,
+			resolved: false,
+		}
+	}
+}
+
+/// Why a dependency was refused. The app shows its own translated messages,
+/// [`Display`](std::fmt::Display) is meant for logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyError {
+	/// A task was picked as its own prerequisite.
+	SelfDependency,
+	/// The prerequisite, whose title is kept, already depends on the task.
+	Cycle(String),
+}
+
+impl std::fmt::Display for DependencyError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::SelfDependency => write!(f, "A task can't depend on itself."),
+			Self::Cycle(title) => write!(f, "\"{title}\" already depends on this task."),
+		}
+	}
+}
+
+impl std::error::Error for DependencyError {}
+
+/// Returns `true` if adding an edge from `task_id` to `prerequisite_id` would
+/// make a task (indirectly) depend on itself.
+pub fn creates_cycle(
+	dependencies: &[Dependency],
+	task_id: &str,
+	prerequisite_id: &str,
+) -> bool {
+	let mut prerequisites: HashMap<&str, Vec<&str>> = HashMap::new();
+	for dependency in dependencies {
+		prerequisites
+			.entry(dependency.task_id.as_str())
+			.or_default()
+			.push(dependency.prerequisite_id.as_str());
+	}
+
+	let mut visited = HashSet::new();
+	let mut pending = vec![prerequisite_id];
+	while let Some(current) = pending.pop() {
+		if current == task_id {
+			return true;
+		}
+		if visited.insert(current) {
+			if let Some(next) = prerequisites.get(current) {
+				pending.extend(next);
+			}
+		}
+	}
+	false
+}
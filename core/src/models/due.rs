@@ -0,0 +1,62 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+use super::task::Task;
+
+/// When a task is due relative to a given day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DueBucket {
+	Overdue,
+	Today,
+	Tomorrow,
+	/// Later in the same week, weeks start on Monday.
+	ThisWeek,
+	Later,
+	NoDate,
+}
+
+impl DueBucket {
+	pub const ALL: [DueBucket; 6] = [
+		DueBucket::Overdue,
+		DueBucket::Today,
+		DueBucket::Tomorrow,
+		DueBucket::ThisWeek,
+		DueBucket::Later,
+		DueBucket::NoDate,
+	];
+
+	/// Places `task` by its due date as seen from `today`, in local time.
+	/// Finished tasks are placed the same way as open ones.
+	pub fn of(task: &Task, today: NaiveDate) -> Self {
+		let Some(due_date) = task.due_date else {
+			return DueBucket::NoDate;
+		};
+		let due_date = due_date.date_naive();
+		let end_of_week = today
+			+ Duration::days(6 - today.weekday().num_days_from_monday() as i64);
+		if due_date < today {
+			DueBucket::Overdue
+		} else if due_date == today {
+			DueBucket::Today
+		} else if due_date == today + Duration::days(1) {
+			DueBucket::Tomorrow
+		} else if due_date <= end_of_week {
+			DueBucket::ThisWeek
+		} else {
+			DueBucket::Later
+		}
+	}
+}
+
+impl Task {
+	/// Whether the task is still open after its due date has passed.
+	pub fn is_overdue(&self, today: NaiveDate) -> bool {
+		!self.status.is_finished() && DueBucket::of(self, today) == DueBucket::Overdue
+	}
+
+	/// Whether the task is hidden until a start date after `today`.
+	pub fn is_deferred(&self, today: NaiveDate) -> bool {
+		self
+			.start_date
+			.is_some_and(|start_date| start_date.date_naive() > today)
+	}
+}
@@ -0,0 +1,75 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+/// Name recorded as the source of links captured by this app.
+pub const APPLICATION_NAME: &str = "Done";
+
+/// A link from a task to something outside of it, like a web page.
+#[derive(
+	Clone,
+	Default,
+	Debug,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+	Serialize,
+	Deserialize,
+)]
+pub struct LinkedResource {
+	pub id: String,
+	pub web_url: String,
+	/// The app the link came from.
+	pub application_name: String,
+	pub display_name: String,
+}
+
+impl LinkedResource {
+	pub fn new(web_url: &str) -> Self {
+		Self {
+			id: Uuid::new_v4().to_string(),
+			web_url: web_url.to_string(),
+			application_name: APPLICATION_NAME.to_string(),
+			display_name: display_name(web_url),
+		}
+	}
+
+	/// Opens the link with the default handler.
+	pub fn open(&self) -> anyhow::Result<()> {
+		open::that(&self.web_url)?;
+		Ok(())
+	}
+}
+
+/// The host of a URL without a leading `www.`, or the URL itself if it has
+/// none.
+pub fn display_name(web_url: &str) -> String {
+	Url::parse(web_url)
+		.ok()
+		.and_then(|url| url.host_str().map(str::to_string))
+		.map(|host| host.trim_start_matches("www.").to_string())
+		.unwrap_or_else(|| web_url.to_string())
+}
+
+static URL: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r#"https?://[^\s<>"'()\[\]]+"#).unwrap());
+
+/// Finds the web URLs in `text`, in order and without duplicates.
+pub fn detect_urls(text: &str) -> Vec<String> {
+	let mut urls: Vec<String> = vec![];
+	for found in URL.find_iter(text) {
+		let url = found
+			.as_str()
+			.trim_end_matches(['.', ',', ';', ':', '!', '?'])
+			.to_string();
+		if Url::parse(&url).is_ok() && !urls.contains(&url) {
+			urls.push(url);
+		}
+	}
+	urls
+}
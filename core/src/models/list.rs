@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::service::Service;
-use crate::services::microsoft::models::list::TodoTaskList;
+use crate::services::microsoft::models::list::{
+	TodoTaskList, WellKnownListName,
+};
 
 #[derive(
 	Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
@@ -14,6 +16,25 @@ pub struct List {
 	pub description: String,
 	pub icon: Option<String>,
 	pub service: Service,
+	/// Whether the list is shared with other people.
+	pub shared: bool,
+	/// Whether someone else owns the list and shared it with the user.
+	pub shared_with_me: bool,
+	/// Lists the provider creates on its own, see [`WellKnownList`].
+	pub well_known: Option<WellKnownList>,
+	/// Id of the [`ListGroup`](super::list_group::ListGroup) the list is in.
+	pub group: Option<String>,
+}
+
+/// Lists the provider creates for every account, they can't be deleted.
+#[derive(
+	Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum WellKnownList {
+	/// Where tasks go when no list is chosen, "Tasks" in Microsoft To Do.
+	Default,
+	/// Emails flagged in Outlook, tasks can't be added to it.
+	FlaggedEmails,
 }
 
 impl FromIterator<List> for List {
@@ -34,20 +55,51 @@ impl List {
 			service,
 			description: String::new(),
 			icon: Some("✍️".to_string()),
+			shared: false,
+			shared_with_me: false,
+			well_known: None,
+			group: None,
 		}
 	}
+
+	/// Only the owner can rename a list, and well-known lists keep their name.
+	pub fn can_rename(&self) -> bool {
+		!self.shared_with_me && self.well_known.is_none()
+	}
+
+	pub fn can_delete(&self) -> bool {
+		!self.shared_with_me && self.well_known.is_none()
+	}
+
+	/// Whether tasks can't be added to the list.
+	pub fn read_only(&self) -> bool {
+		self.well_known == Some(WellKnownList::FlaggedEmails)
+	}
 }
 
-impl From<TodoTaskList> for List {
-	fn from(task: TodoTaskList) -> Self {
-		let display_name = remove_emoji(&task.display_name);
-		let icon = extract_emoji(&task.display_name);
+impl List {
+	/// Converts a Graph list belonging to the account of `service`.
+	pub fn from_todo_list(list: TodoTaskList, service: Service) -> Self {
+		let display_name = remove_emoji(&list.display_name);
+		let icon = extract_emoji(&list.display_name);
 		Self {
-			id: task.id,
+			id: list.id,
 			name: display_name,
 			description: String::new(),
 			icon,
-			service: Service::Microsoft,
+			service,
+			shared: list.is_shared,
+			shared_with_me: !list.is_owner,
+			well_known: match list.wellknown_list_name {
+				WellKnownListName::DefaultList => Some(WellKnownList::Default),
+				WellKnownListName::FlaggedEmails => {
+					Some(WellKnownList::FlaggedEmails)
+				},
+				WellKnownListName::None | WellKnownListName::UnknownFutureValue => {
+					None
+				},
+			},
+			group: None,
 		}
 	}
 }
@@ -60,9 +112,13 @@ impl From<List> for TodoTaskList {
 		Self {
 			id: list.id,
 			display_name,
-			is_owner: true,
-			is_shared: false,
-			wellknown_list_name: Default::default(),
+			is_owner: !list.shared_with_me,
+			is_shared: list.shared,
+			wellknown_list_name: match list.well_known {
+				Some(WellKnownList::Default) => WellKnownListName::DefaultList,
+				Some(WellKnownList::FlaggedEmails) => WellKnownListName::FlaggedEmails,
+				None => WellKnownListName::None,
+			},
 		}
 	}
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::service::Service;
+
+/// A folder of lists in the sidebar, see [`List::group`](super::list::List).
+#[derive(
+	Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct ListGroup {
+	pub id: String,
+	pub name: String,
+	/// Where the group goes among the other groups, lowest first.
+	pub position: i32,
+	/// Whether the sidebar shows the lists of the group.
+	pub expanded: bool,
+	pub service: Service,
+}
+
+impl ListGroup {
+	pub fn new(name: &str, position: i32, service: Service) -> Self {
+		Self {
+			id: Uuid::new_v4().to_string(),
+			name: name.to_string(),
+			position,
+			expanded: true,
+			service,
+		}
+	}
+}
@@ -0,0 +1,33 @@
+use std::fmt::Display;
+
+/// Something the user has to do before a sign-in can finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginPrompt {
+	/// Enter `user_code` at `verification_uri` from any browser.
+	DeviceCode {
+		user_code: String,
+		verification_uri: String,
+		message: String,
+	},
+}
+
+impl Display for LoginPrompt {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			LoginPrompt::DeviceCode { message, .. } => write!(f, "{message}"),
+		}
+	}
+}
+
+/// Shows the prompts of a sign-in to the user.
+pub type LoginPrompter = Box<dyn Fn(LoginPrompt) + Send + Sync>;
+
+/// How a sign-in ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Login {
+	/// The account is signed in.
+	Completed,
+	/// The browser will send the code through the `done://` scheme, which
+	/// finishes the sign-in in [`handle_uri_params`](crate::service::Service).
+	Redirect,
+}
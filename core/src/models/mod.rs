@@ -2,8 +2,34 @@ pub mod task;
 
 pub mod list;
 
+pub mod list_group;
+
 pub mod priority;
 
 pub mod status;
 
 pub mod recurrence;
+
+pub mod dependency;
+
+pub mod agenda;
+
+pub mod time_entry;
+
+pub mod workload;
+
+pub mod my_day;
+
+pub mod due;
+
+pub mod dates;
+
+pub mod notes;
+
+pub mod attachment;
+
+pub mod linked_resource;
+
+pub mod account;
+
+pub mod login;
@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+
+use crate::service::Service;
+
+use super::task::Task;
+
+/// Why a task is suggested for today's plan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SuggestionReason {
+	Overdue,
+	DueToday,
+	/// Planned for an earlier day and still not finished.
+	Leftover,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+	pub service: Service,
+	pub task: Task,
+	pub reason: SuggestionReason,
+}
+
+/// Picks the tasks worth adding to today's plan: overdue ones, the ones due
+/// `today` and the unfinished `leftovers` of earlier days. Tasks already
+/// planned, finished, set aside or deferred are skipped.
+pub fn suggestions(
+	tasks: &[(Service, Task)],
+	leftovers: &HashSet<String>,
+	today: NaiveDate,
+) -> Vec<Suggestion> {
+	let mut suggestions: Vec<Suggestion> = tasks
+		.iter()
+		.filter(|(_, task)| {
+			!task.today
+				&& !task.status.is_finished()
+				&& !task.status.is_set_aside()
+				&& !task.is_deferred(today)
+		})
+		.filter_map(|(service, task)| {
+			let due_date = task.due_date.map(|date| date.date_naive());
+			let reason = match due_date {
+				Some(date) if date < today => SuggestionReason::Overdue,
+				Some(date) if date == today => SuggestionReason::DueToday,
+				_ if leftovers.contains(&task.id) => SuggestionReason::Leftover,
+				_ => return None,
+			};
+			Some(Suggestion {
+				service: *service,
+				task: task.clone(),
+				reason,
+			})
+		})
+		.collect();
+	suggestions.sort_by(|a, b| {
+		a.reason
+			.cmp(&b.reason)
+			.then_with(|| a.task.due_date.cmp(&b.task.due_date))
+	});
+	suggestions
+}
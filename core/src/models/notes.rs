@@ -0,0 +1,43 @@
+use std::sync::LazyLock;
+
+use pulldown_cmark::{html, Event, Options, Parser};
+use regex::Regex;
+
+/// Document metadata and styles, which don't belong in notes.
+static METADATA: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"(?is)<head\b.*?</head>|<style\b.*?</style>").unwrap()
+});
+
+/// Markdown extensions notes can use on top of CommonMark.
+pub fn markdown_options() -> Options {
+	Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS
+}
+
+/// Parses notes, line breaks are kept as typed instead of joining lines into
+/// paragraphs since most notes are written as plain text.
+pub fn parse_markdown(markdown: &str) -> impl Iterator<Item = Event<'_>> {
+	Parser::new_ext(markdown, markdown_options()).map(|event| match event {
+		Event::SoftBreak => Event::HardBreak,
+		event => event,
+	})
+}
+
+/// Renders Markdown notes as the HTML body Microsoft Graph expects.
+pub fn markdown_to_html(markdown: &str) -> String {
+	let mut output = String::new();
+	html::push_html(&mut output, parse_markdown(markdown));
+	output
+}
+
+/// Converts an HTML body, like the ones of tasks created in Outlook, to
+/// Markdown. Document metadata and styles are dropped.
+pub fn html_to_markdown(html: &str) -> String {
+	let markdown = html2md::parse_html(&METADATA.replace_all(html, ""));
+	markdown
+		.lines()
+		.map(str::trim_end)
+		.collect::<Vec<&str>>()
+		.join("\n")
+		.trim()
+		.to_string()
+}
@@ -1,6 +1,8 @@
-use crate::services::microsoft::models::importance::TaskImportance;
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
+use crate::services::microsoft::models::importance::TaskImportance;
+
 #[derive(
 	Clone,
 	Copy,
@@ -21,13 +23,15 @@ pub enum Priority {
 	High = 2,
 }
 
-impl From<i32> for Priority {
-	fn from(value: i32) -> Self {
+impl TryFrom<i32> for Priority {
+	type Error = anyhow::Error;
+
+	fn try_from(value: i32) -> Result<Self, Self::Error> {
 		match value {
-			0 => Priority::Low,
-			1 => Priority::Normal,
-			2 => Priority::High,
-			_ => panic!("Invalid value for Priority"),
+			0 => Ok(Priority::Low),
+			1 => Ok(Priority::Normal),
+			2 => Ok(Priority::High),
+			_ => bail!("Invalid value for Priority: {value}"),
 		}
 	}
 }
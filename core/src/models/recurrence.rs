@@ -1,5 +1,6 @@
 use crate::services::microsoft::models::recurrence::DayOfWeek;
 use crate::services::microsoft::models::recurrence::TaskRecurrence;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -64,6 +65,44 @@ pub struct Recurrence {
 }
 
 impl Recurrence {
+	pub fn is_empty(&self) -> bool {
+		*self == Self::default()
+	}
+
+	pub fn includes(&self, weekday: Weekday) -> bool {
+		match weekday {
+			Weekday::Mon => self.monday,
+			Weekday::Tue => self.tuesday,
+			Weekday::Wed => self.wednesday,
+			Weekday::Thu => self.thursday,
+			Weekday::Fri => self.friday,
+			Weekday::Sat => self.saturday,
+			Weekday::Sun => self.sunday,
+		}
+	}
+
+	/// Projects the days between `from` and `to` (inclusive) on which the
+	/// task repeats, skipping anything up to and including `after`.
+	pub fn occurrences(
+		&self,
+		after: NaiveDate,
+		from: NaiveDate,
+		to: NaiveDate,
+	) -> Vec<NaiveDate> {
+		let mut occurrences = vec![];
+		if self.is_empty() {
+			return occurrences;
+		}
+		let mut day = from.max(after + Duration::days(1));
+		while day <= to {
+			if self.includes(day.weekday()) {
+				occurrences.push(day);
+			}
+			day += Duration::days(1);
+		}
+		occurrences
+	}
+
 	pub fn from_string(value: String) -> Self {
 		Self {
 			monday: value.contains(Day::Monday.to_string().as_str()),
@@ -1,6 +1,8 @@
-use crate::services::microsoft::models::status::TaskStatus;
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
+use crate::services::microsoft::models::status::TaskStatus;
+
 #[derive(
 	Clone,
 	Copy,
@@ -18,14 +20,28 @@ pub enum Status {
 	#[default]
 	NotStarted = 0,
 	Completed = 1,
+	InProgress = 2,
+	Waiting = 3,
+	Deferred = 4,
+	Cancelled = 5,
 }
 
-impl From<i32> for Status {
-	fn from(value: i32) -> Self {
+/// Microsoft To Do has no cancelled state, cancelled tasks are stored there as
+/// completed tasks carrying this category.
+pub const CANCELLED_CATEGORY: &str = "Cancelled";
+
+impl TryFrom<i32> for Status {
+	type Error = anyhow::Error;
+
+	fn try_from(value: i32) -> Result<Self, Self::Error> {
 		match value {
-			0 => Self::NotStarted,
-			1 => Status::Completed,
-			_ => panic!("Invalid value for Status"),
+			0 => Ok(Self::NotStarted),
+			1 => Ok(Status::Completed),
+			2 => Ok(Status::InProgress),
+			3 => Ok(Status::Waiting),
+			4 => Ok(Status::Deferred),
+			5 => Ok(Status::Cancelled),
+			_ => bail!("Invalid value for Status: {value}"),
 		}
 	}
 }
@@ -35,15 +51,43 @@ impl From<Status> for i32 {
 		match value {
 			Status::NotStarted => 0,
 			Status::Completed => 1,
+			Status::InProgress => 2,
+			Status::Waiting => 3,
+			Status::Deferred => 4,
+			Status::Cancelled => 5,
 		}
 	}
 }
 
 impl Status {
+	pub const ALL: [Status; 6] = [
+		Status::NotStarted,
+		Status::InProgress,
+		Status::Waiting,
+		Status::Deferred,
+		Status::Completed,
+		Status::Cancelled,
+	];
+
+	/// Whether no more work is expected on the task.
+	pub fn is_finished(&self) -> bool {
+		matches!(self, Status::Completed | Status::Cancelled)
+	}
+
+	/// Whether the task was put aside, for later or for good. Smart lists of
+	/// upcoming work leave these out.
+	pub fn is_set_aside(&self) -> bool {
+		matches!(self, Status::Deferred | Status::Cancelled)
+	}
+
 	pub fn as_str_name(&self) -> &'static str {
 		match self {
 			Status::NotStarted => "NOT_STARTED",
 			Status::Completed => "COMPLETED",
+			Status::InProgress => "IN_PROGRESS",
+			Status::Waiting => "WAITING",
+			Status::Deferred => "DEFERRED",
+			Status::Cancelled => "CANCELLED",
 		}
 	}
 	/// Creates an enum from field names used in the ProtoBuf definition.
@@ -51,6 +95,10 @@ impl Status {
 		match value {
 			"NOT_STARTED" => Some(Self::NotStarted),
 			"COMPLETED" => Some(Self::Completed),
+			"IN_PROGRESS" => Some(Self::InProgress),
+			"WAITING" => Some(Self::Waiting),
+			"DEFERRED" => Some(Self::Deferred),
+			"CANCELLED" => Some(Self::Cancelled),
 			_ => None,
 		}
 	}
@@ -60,10 +108,10 @@ impl From<TaskStatus> for Status {
 	fn from(value: TaskStatus) -> Self {
 		match value {
 			TaskStatus::NotStarted => Self::NotStarted,
-			TaskStatus::Started => Self::NotStarted,
+			TaskStatus::Started => Self::InProgress,
 			TaskStatus::Completed => Self::Completed,
-			TaskStatus::WaitingOnOthers => Self::NotStarted,
-			TaskStatus::Deferred => Self::NotStarted,
+			TaskStatus::WaitingOnOthers => Self::Waiting,
+			TaskStatus::Deferred => Self::Deferred,
 		}
 	}
 }
@@ -73,6 +121,10 @@ impl From<Status> for TaskStatus {
 		match value {
 			Status::NotStarted => TaskStatus::NotStarted,
 			Status::Completed => TaskStatus::Completed,
+			Status::InProgress => TaskStatus::Started,
+			Status::Waiting => TaskStatus::WaitingOnOthers,
+			Status::Deferred => TaskStatus::Deferred,
+			Status::Cancelled => TaskStatus::Completed,
 		}
 	}
 }
@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use anyhow::Context;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -7,10 +8,19 @@ use uuid::Uuid;
 use crate::services::microsoft::models::{
 	body::{BodyType, ItemBody},
 	checklist_item::ChecklistItem,
+	date_time_zone::DateTimeTimeZone,
+	status::TaskStatus,
 	task::TodoTask,
 };
 
-use super::{priority::Priority, recurrence::Recurrence, status::Status};
+use super::{
+	dates::all_day,
+	linked_resource::{detect_urls, LinkedResource},
+	notes::markdown_to_html,
+	priority::Priority,
+	recurrence::Recurrence,
+	status::{Status, CANCELLED_CATEGORY},
+};
 
 #[derive(
 	Clone,
@@ -34,14 +44,24 @@ pub struct Task {
 	pub priority: Priority,
 	pub sub_tasks: Vec<Task>,
 	pub tags: Vec<String>,
+	/// Markdown, see [`super::notes`].
 	pub notes: String,
 	pub completion_date: Option<DateTime<Utc>>,
 	pub deletion_date: Option<DateTime<Utc>>,
+	/// An all-day date, see [`all_day`].
 	pub due_date: Option<DateTime<Utc>>,
+	/// An all-day date before which the task is hidden from most views.
+	pub start_date: Option<DateTime<Utc>>,
 	pub reminder_date: Option<DateTime<Utc>>,
 	pub recurrence: Recurrence,
 	pub created_date_time: DateTime<Utc>,
 	pub last_modified_date_time: DateTime<Utc>,
+	/// Planned effort in minutes.
+	pub estimate: Option<u32>,
+	/// Whether files are attached, see
+	/// [`TodoProvider::read_attachments`](crate::task_service::TodoProvider::read_attachments).
+	pub has_attachments: bool,
+	pub linked_resources: Vec<LinkedResource>,
 }
 
 impl Task {
@@ -61,69 +81,179 @@ impl Task {
 			completion_date: None,
 			deletion_date: None,
 			due_date: None,
+			start_date: None,
 			reminder_date: None,
 			recurrence: Default::default(),
 			created_date_time: now,
 			last_modified_date_time: now,
+			estimate: None,
+			has_attachments: false,
+			linked_resources: vec![],
+		}
+	}
+
+	/// A copy of the task and its sub-tasks with new ids, to be created in the
+	/// list `parent`.
+	pub fn copy_to(&self, parent: &str) -> Task {
+		let id = Uuid::new_v4().to_string();
+		Task {
+			sub_tasks: self
+				.sub_tasks
+				.iter()
+				.map(|sub_task| sub_task.copy_to(&id))
+				.collect(),
+			id,
+			parent: parent.to_string(),
+			has_attachments: false,
+			..self.clone()
 		}
 	}
+
+	/// Changes the status, recording when the task was completed.
+	pub fn set_status(&mut self, status: Status) {
+		if status == Status::Completed {
+			self.completion_date.get_or_insert_with(Utc::now);
+		} else {
+			self.completion_date = None;
+		}
+		self.status = status;
+	}
+
+	/// Links the URLs found in the title and notes that aren't linked yet,
+	/// returns whether any was added.
+	pub fn capture_links(&mut self) -> bool {
+		let mut captured = false;
+		for url in detect_urls(&format!("{}\n{}", self.title, self.notes)) {
+			if !self.linked_resources.iter().any(|link| link.web_url == url) {
+				self.linked_resources.push(LinkedResource::new(&url));
+				captured = true;
+			}
+		}
+		captured
+	}
+
+	/// Returns every sub-task in the tree, depth first, without their children.
+	/// Providers that only support a single level of sub-tasks, like Microsoft
+	/// To Do checklist items, use this so nested items aren't lost.
+	pub fn flattened_sub_tasks(&self) -> Vec<Task> {
+		let mut sub_tasks = vec![];
+		for sub_task in &self.sub_tasks {
+			sub_tasks.push(Task {
+				sub_tasks: vec![],
+				..sub_task.clone()
+			});
+			sub_tasks.extend(sub_task.flattened_sub_tasks());
+		}
+		sub_tasks
+	}
 }
 
-impl From<TodoTask> for Task {
-	fn from(task: TodoTask) -> Self {
-		Self {
+impl TryFrom<TodoTask> for Task {
+	type Error = anyhow::Error;
+
+	fn try_from(task: TodoTask) -> Result<Self, Self::Error> {
+		let reminder_date: Option<DateTime<Utc>> =
+			task.reminder_date_time.map(TryInto::try_into).transpose()?;
+		let cancelled = task.status == TaskStatus::Completed
+			&& task.categories.iter().any(|category| category == CANCELLED_CATEGORY);
+		let mut tags = task.categories;
+		// Outlook categories are the tags of the task, the one marking it as
+		// cancelled is its status.
+		if cancelled {
+			tags.retain(|category| category != CANCELLED_CATEGORY);
+		}
+		Ok(Self {
 			id: task.id,
 			parent: String::new(),
 			title: task.title,
 			favorite: false,
-			today: task.reminder_date_time.is_some()
-				&& Into::<DateTime<Utc>>::into(
-					task.reminder_date_time.clone().unwrap(),
-				) == Utc::now(),
-			status: task.status.into(),
+			// My Day is planned locally, see `Database::apply_my_day`.
+			today: false,
+			status: if cancelled {
+				Status::Cancelled
+			} else {
+				task.status.into()
+			},
 			priority: task.importance.into(),
 			sub_tasks: task
 				.checklist_items
 				.unwrap_or_default()
-				.iter()
-				.map(|item| item.clone().into())
-				.collect(),
-			tags: vec![],
-			notes: task.body.content,
-			completion_date: task.completed_date_time.map(|date| date.into()),
+				.into_iter()
+				.map(TryInto::try_into)
+				.collect::<Result<Vec<Task>, _>>()?,
+			tags,
+			notes: task.body.to_markdown(),
+			completion_date: task
+				.completed_date_time
+				.map(TryInto::try_into)
+				.transpose()?,
 			deletion_date: None,
-			due_date: task.due_date_time.map(|date| date.into()),
-			reminder_date: task.reminder_date_time.map(|date| date.into()),
+			due_date: task
+				.due_date_time
+				.map(|due| due.date().map(all_day))
+				.transpose()?,
+			start_date: task
+				.start_date_time
+				.map(|start| start.date().map(all_day))
+				.transpose()?,
+			reminder_date,
 			recurrence: task.recurrence.unwrap_or_default().into(),
 			created_date_time: DateTime::<Utc>::from_str(&task.created_date_time)
-				.unwrap(),
+				.with_context(|| {
+					format!("Invalid creation date: {}", task.created_date_time)
+				})?,
 			last_modified_date_time: DateTime::<Utc>::from_str(
 				&task.last_modified_date_time,
 			)
-			.unwrap(),
-		}
+			.with_context(|| {
+				format!(
+					"Invalid modification date: {}",
+					task.last_modified_date_time
+				)
+			})?,
+			estimate: None,
+			has_attachments: task.has_attachments,
+			linked_resources: task
+				.linked_resources
+				.unwrap_or_default()
+				.into_iter()
+				.map(Into::into)
+				.collect(),
+		})
 	}
 }
 
 impl From<Task> for TodoTask {
 	fn from(task: Task) -> Self {
 		let checklist_items: Vec<ChecklistItem> =
-			task.sub_tasks.iter().map(|t| t.to_owned().into()).collect();
+			task.flattened_sub_tasks().into_iter().map(Into::into).collect();
+		let mut categories = task.tags;
+		if task.status == Status::Cancelled
+			&& !categories.iter().any(|category| category == CANCELLED_CATEGORY)
+		{
+			categories.push(CANCELLED_CATEGORY.to_string());
+		}
 		Self {
 			id: task.id,
-			body: ItemBody {
-				content: task.notes,
-				content_type: BodyType::Text,
+			body: if task.notes.is_empty() {
+				ItemBody::default()
+			} else {
+				ItemBody {
+					content: markdown_to_html(&task.notes),
+					content_type: BodyType::Html,
+				}
 			},
-			categories: vec![],
+			categories,
 			completed_date_time: task.completion_date.map(|date| date.into()),
-			due_date_time: task.due_date.map(|date| date.into()),
+			due_date_time: task
+				.due_date
+				.map(|date| DateTimeTimeZone::all_day(date.date_naive())),
 			importance: task.priority.into(),
 			is_reminder_on: task.reminder_date.is_some(),
 			recurrence: Default::default(),
 			title: task.title,
 			status: task.status.into(),
-			has_attachments: false,
+			has_attachments: task.has_attachments,
 			checklist_items: Some(checklist_items),
 			created_date_time: task
 				.created_date_time
@@ -134,14 +264,26 @@ impl From<Task> for TodoTask {
 				.format("%Y-%m-%dT%H:%M:%S%.fZ")
 				.to_string(),
 			reminder_date_time: task.reminder_date.map(|date| date.into()),
-			start_date_time: None,
+			start_date_time: task
+				.start_date
+				.map(|date| DateTimeTimeZone::all_day(date.date_naive())),
+			linked_resources: Some(
+				task.linked_resources.into_iter().map(Into::into).collect(),
+			),
 		}
 	}
 }
 
-impl From<ChecklistItem> for Task {
-	fn from(value: ChecklistItem) -> Self {
-		Self {
+impl TryFrom<ChecklistItem> for Task {
+	type Error = anyhow::Error;
+
+	fn try_from(value: ChecklistItem) -> Result<Self, Self::Error> {
+		let created_date_time = match value.created_date_time {
+			Some(date) => DateTime::<Utc>::from_str(&date)
+				.with_context(|| format!("Invalid checklist item date: {date}"))?,
+			None => Utc::now(),
+		};
+		Ok(Self {
 			id: value.id,
 			title: value.display_name,
 			status: if value.is_checked {
@@ -149,12 +291,9 @@ impl From<ChecklistItem> for Task {
 			} else {
 				Status::NotStarted
 			},
-			created_date_time: DateTime::<Utc>::from_str(
-				&value.created_date_time.unwrap_or_default(),
-			)
-			.unwrap(),
+			created_date_time,
 			..Default::default()
-		}
+		})
 	}
 }
 
@@ -164,7 +303,7 @@ impl From<Task> for ChecklistItem {
 			display_name: task.title,
 			created_date_time: None,
 			checked_date_time: None,
-			is_checked: matches!(task.status, Status::Completed),
+			is_checked: task.status.is_finished(),
 			id: task.id,
 		}
 	}
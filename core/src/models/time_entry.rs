@@ -0,0 +1,124 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{list::List, task::Task};
+
+/// A span of time spent working on a task. Entries without an `end` belong to
+/// a timer that is still running.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TimeEntry {
+	pub id: String,
+	pub task_id: String,
+	pub start: DateTime<Utc>,
+	pub end: Option<DateTime<Utc>>,
+	pub note: String,
+}
+
+impl TimeEntry {
+	pub fn start(task_id: String, note: String) -> Self {
+		Self {
+			id: Uuid::new_v4().to_string(),
+			task_id,
+			start: Utc::now(),
+			end: None,
+			note,
+		}
+	}
+
+	pub fn running(&self) -> bool {
+		self.end.is_none()
+	}
+
+	/// Length of the entry, counting up to now for running timers.
+	pub fn duration(&self) -> Duration {
+		self.end.unwrap_or(Utc::now()) - self.start
+	}
+}
+
+/// Formats a duration as hours and minutes, e.g. `1h 05m`.
+pub fn format_duration(duration: Duration) -> String {
+	let minutes = duration.num_minutes().max(0);
+	format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeReportGrouping {
+	#[default]
+	List,
+	Tag,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeReportRow {
+	pub group: String,
+	pub task: String,
+	pub entries: usize,
+	pub duration: Duration,
+}
+
+/// Sums the time tracked on each task, grouped by list or by tag. Tasks with
+/// several tags are reported once per tag, untagged tasks under an empty
+/// group.
+pub fn time_report(
+	entries: &[TimeEntry],
+	tasks: &[Task],
+	lists: &[List],
+	grouping: TimeReportGrouping,
+) -> Vec<TimeReportRow> {
+	let mut rows: Vec<TimeReportRow> = vec![];
+	for task in tasks {
+		let task_entries: Vec<&TimeEntry> = entries
+			.iter()
+			.filter(|entry| entry.task_id == task.id)
+			.collect();
+		if task_entries.is_empty() {
+			continue;
+		}
+		let duration = task_entries
+			.iter()
+			.fold(Duration::zero(), |total, entry| total + entry.duration());
+		let groups = match grouping {
+			TimeReportGrouping::List => vec![lists
+				.iter()
+				.find(|list| list.id == task.parent)
+				.map(|list| list.name.clone())
+				.unwrap_or_default()],
+			TimeReportGrouping::Tag if task.tags.is_empty() => vec![String::new()],
+			TimeReportGrouping::Tag => task.tags.clone(),
+		};
+		for group in groups {
+			rows.push(TimeReportRow {
+				group,
+				task: task.title.clone(),
+				entries: task_entries.len(),
+				duration,
+			});
+		}
+	}
+	rows.sort_by(|a, b| (&a.group, &a.task).cmp(&(&b.group, &b.task)));
+	rows
+}
+
+/// Renders a report as CSV with a header row and durations in decimal hours.
+pub fn time_report_csv(rows: &[TimeReportRow]) -> String {
+	let mut csv = String::from("group,task,entries,hours\n");
+	for row in rows {
+		csv.push_str(&format!(
+			"{},{},{},{:.2}\n",
+			csv_field(&row.group),
+			csv_field(&row.task),
+			row.entries,
+			row.duration.num_seconds() as f64 / 3600.0
+		));
+	}
+	csv
+}
+
+fn csv_field(value: &str) -> String {
+	if value.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", value.replace('"', "\"\""))
+	} else {
+		value.to_string()
+	}
+}
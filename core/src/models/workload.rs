@@ -0,0 +1,72 @@
+use chrono::{Duration, NaiveDate};
+
+use super::task::Task;
+
+/// Planned effort of a set of tasks, in minutes, per day and per list.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Workload {
+	pub days: Vec<(NaiveDate, u32)>,
+	/// List names with the effort planned in them, in order of appearance.
+	pub lists: Vec<(String, u32)>,
+}
+
+impl Workload {
+	/// Sums the estimates of unfinished tasks over the `days` days starting
+	/// at `from`. A task counts on its due date, or on `from` when it's only
+	/// marked for today. Each task is paired with the name of its list.
+	pub fn new(tasks: &[(Task, String)], from: NaiveDate, days: i64) -> Self {
+		let mut workload = Workload {
+			days: (0..days).map(|day| (from + Duration::days(day), 0)).collect(),
+			lists: vec![],
+		};
+		for (task, list) in tasks {
+			let Some(estimate) = task.estimate else {
+				continue;
+			};
+			if task.status.is_finished() {
+				continue;
+			}
+			let date = task
+				.due_date
+				.map(|date| date.date_naive())
+				.filter(|date| *date >= from)
+				.or(task.today.then_some(from));
+			let Some((_, total)) = workload
+				.days
+				.iter_mut()
+				.find(|(day, _)| Some(*day) == date)
+			else {
+				continue;
+			};
+			*total += estimate;
+			match workload.lists.iter_mut().find(|(name, _)| name == list) {
+				Some((_, total)) => *total += estimate,
+				None => workload.lists.push((list.clone(), estimate)),
+			}
+		}
+		workload
+	}
+
+	pub fn total(&self) -> u32 {
+		self.days.iter().map(|(_, minutes)| minutes).sum()
+	}
+
+	/// Days whose planned effort goes over `capacity` minutes.
+	pub fn overloaded(&self, capacity: u32) -> Vec<NaiveDate> {
+		self
+			.days
+			.iter()
+			.filter(|(_, minutes)| *minutes > capacity)
+			.map(|(day, _)| *day)
+			.collect()
+	}
+}
+
+/// Formats an amount of minutes the way estimates are shown, e.g. `1h 30m`.
+pub fn format_estimate(minutes: u32) -> String {
+	match (minutes / 60, minutes % 60) {
+		(0, minutes) => format!("{minutes}m"),
+		(hours, 0) => format!("{hours}h"),
+		(hours, minutes) => format!("{hours}h {minutes}m"),
+	}
+}
@@ -1,11 +1,90 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+		accounts (id_account) {
+				id_account -> Text,
+				provider -> Text,
+				name -> Text,
+				remote_id -> Text,
+				created_date_time -> Timestamp,
+				needs_login -> Bool,
+		}
+}
+
+diesel::table! {
+		attachments (id_attachment) {
+				id_attachment -> Text,
+				id_task -> Text,
+				name -> Text,
+				content_type -> Text,
+				size -> BigInt,
+				created_date_time -> Timestamp,
+		}
+}
+
+diesel::table! {
+		list_groups (id_group) {
+				id_group -> Text,
+				name -> Text,
+				position -> Integer,
+				expanded -> Bool,
+		}
+}
+
 diesel::table! {
 		lists (id_list) {
 				id_list -> Text,
 				name -> Text,
 				description -> Text,
 				icon_name -> Nullable<Text>,
+				id_group -> Nullable<Text>,
+		}
+}
+
+diesel::table! {
+		my_day (id_task) {
+				id_task -> Text,
+				planned_on -> Date,
+		}
+}
+
+diesel::table! {
+		quarantined_tasks (id_task) {
+				id_task -> Text,
+				payload -> Text,
+				reason -> Text,
+				quarantined_date -> Timestamp,
+		}
+}
+
+diesel::table! {
+		sub_tasks (id_sub_task) {
+				id_sub_task -> Text,
+				parent_task -> Text,
+				root_task -> Text,
+				position -> Integer,
+				title -> Text,
+				status -> Integer,
+				notes -> Text,
+				due_date -> Nullable<Timestamp>,
+				created_date_time -> Timestamp,
+				last_modified_date_time -> Timestamp,
+		}
+}
+
+diesel::table! {
+		task_dependencies (id_task, depends_on) {
+				id_task -> Text,
+				depends_on -> Text,
+				depends_on_title -> Text,
+				resolved -> Bool,
+		}
+}
+
+diesel::table! {
+		task_overlays (id_task) {
+				id_task -> Text,
+				estimate -> Nullable<Integer>,
 		}
 }
 
@@ -18,7 +97,6 @@ diesel::table! {
 				today -> Bool,
 				status -> Integer,
 				priority -> Integer,
-				sub_tasks -> Text,
 				tags -> Text,
 				notes -> Text,
 				completion_date -> Nullable<Timestamp>,
@@ -28,7 +106,32 @@ diesel::table! {
 				recurrence -> Text,
 				created_date_time -> Timestamp,
 				last_modified_date_time -> Timestamp,
+				estimate -> Nullable<Integer>,
+				start_date -> Nullable<Timestamp>,
+				linked_resources -> Text,
+		}
+}
+
+diesel::table! {
+		time_entries (id_time_entry) {
+				id_time_entry -> Text,
+				id_task -> Text,
+				start_time -> Timestamp,
+				end_time -> Nullable<Timestamp>,
+				note -> Text,
 		}
 }
 
-diesel::allow_tables_to_appear_in_same_query!(lists, tasks,);
+diesel::allow_tables_to_appear_in_same_query!(
+	accounts,
+	attachments,
+	list_groups,
+	lists,
+	my_day,
+	quarantined_tasks,
+	sub_tasks,
+	task_dependencies,
+	task_overlays,
+	tasks,
+	time_entries,
+);
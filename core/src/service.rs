@@ -1,33 +1,39 @@
 use std::fmt::Display;
 use std::sync::OnceLock;
 
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
-use strum::IntoEnumIterator;
-use strum_macros::{EnumIter, EnumString};
+use url::Url;
+use uuid::Uuid;
 
 use crate::{
+	models::{list::List, task::Task},
 	services::{
-		local::service::ComputerStorage, microsoft::service::MicrosoftService,
+		local::{database::Database, service::ComputerStorage},
+		microsoft::service::{MicrosoftService, PROVIDER},
 		smart::Smart,
 	},
 	task_service::TodoProvider,
 };
 
 static APP_ID: OnceLock<&str> = OnceLock::new();
+/// Shared by the lookups below, which the sidebar runs for every row.
+static DATABASE: OnceLock<Database> = OnceLock::new();
 
 pub struct Services;
 
 impl Services {
 	pub fn init(app_id: &'static str) {
 		APP_ID.get_or_init(|| app_id);
+		if let Err(err) = MicrosoftService::migrate_legacy_account(app_id) {
+			tracing::error!("Failed to migrate the Microsoft account: {err:#}");
+		}
 	}
 }
 
 #[derive(
 	Debug,
 	Default,
-	EnumIter,
-	EnumString,
 	Clone,
 	Copy,
 	PartialEq,
@@ -40,7 +46,8 @@ impl Services {
 pub enum Service {
 	#[default]
 	Computer,
-	Microsoft,
+	/// A Microsoft To Do account, see [`Service::accounts`].
+	Microsoft(Uuid),
 	Smart,
 }
 
@@ -49,22 +56,157 @@ impl Service {
 	/// After implementing the Service trait in your service
 	/// struct, register your service here.
 	pub fn get_service(&self) -> Box<dyn TodoProvider> {
-		if APP_ID.get().is_none() {
-			panic!("Must call Service::init before trying to get a service");
-		}
-
-		let app_id = APP_ID.get().unwrap().to_string();
+		let app_id = app_id().to_string();
 
 		match self {
 			Service::Smart => Box::new(Smart::new()),
 			Service::Computer => Box::new(ComputerStorage::new(app_id)),
-			Service::Microsoft => Box::new(MicrosoftService::new()),
+			Service::Microsoft(account) => {
+				Box::new(MicrosoftService::new(app_id, *account))
+			},
 		}
 	}
 
-	/// Convenience method to get the list of services.
+	/// A Microsoft account that isn't signed in yet, its
+	/// [`TodoProvider::login`] adds it once the user authorizes the app.
+	pub fn new_microsoft_account() -> Self {
+		Service::Microsoft(Uuid::new_v4())
+	}
+
+	/// The service an OAuth redirect is meant for, the account is carried in
+	/// the `state` parameter.
+	pub fn from_redirect(uri: &Url) -> Option<Self> {
+		let (_, state) = uri.query_pairs().find(|(key, _)| key == "state")?;
+		Uuid::parse_str(&state).ok().map(Service::Microsoft)
+	}
+
+	/// Moves a task to a list of any service by creating it there and deleting
+	/// the original, returns the task as it was created. Local data like time
+	/// entries and dependencies follows the task to the id the service gave it.
+	pub async fn move_task(task: Task, from: Service, to: &List) -> Result<Task> {
+		// Only the local service keeps attachments in the local database,
+		// the others would lose them.
+		if task.has_attachments
+			&& (from != Service::Computer || to.service != Service::Computer)
+		{
+			bail!("Tasks with attachments can't be moved to another service.");
+		}
+		let moved = to
+			.service
+			.get_service()
+			.create_task(task.copy_to(&to.id))
+			.await?;
+		database().move_task_data(&task.id, &moved.id)?;
+		from.get_service().delete_task(task.parent, task.id).await?;
+		Ok(moved)
+	}
+
+	/// Reads the tasks of every service the user is signed in to, logging
+	/// and skipping services that fail.
+	pub async fn all_tasks() -> Vec<(Service, Task)> {
+		let mut tasks = vec![];
+		for service in Self::list() {
+			let mut provider = service.get_service();
+			if service == Service::Smart || !provider.available() {
+				continue;
+			}
+			match provider.read_tasks().await {
+				Ok(read) => tasks.extend(read.into_iter().map(|task| (service, task))),
+				Err(err) => tracing::error!("Failed to read tasks from {service}: {err}"),
+			}
+		}
+		tasks
+	}
+
+	/// Reads the lists of every service the user is signed in to.
+	pub async fn all_lists() -> Vec<List> {
+		let mut lists = vec![];
+		for service in Self::list() {
+			let mut provider = service.get_service();
+			if service == Service::Smart || !provider.available() {
+				continue;
+			}
+			match provider.read_lists().await {
+				Ok(read) => lists.extend(read),
+				Err(err) => tracing::error!("Failed to read lists from {service}: {err}"),
+			}
+		}
+		lists
+	}
+
+	/// Convenience method to get the list of services, with an entry for
+	/// each Microsoft account.
 	pub fn list() -> Vec<Self> {
-		Self::iter().collect()
+		let mut services = vec![Service::Computer];
+		services.extend(Self::accounts());
+		services.push(Service::Smart);
+		services
+	}
+
+	/// The Microsoft accounts that have been added.
+	pub fn accounts() -> Vec<Self> {
+		match database().accounts(PROVIDER) {
+			Ok(accounts) => accounts
+				.into_iter()
+				.map(|account| Service::Microsoft(account.id))
+				.collect(),
+			Err(err) => {
+				tracing::error!("Failed to read accounts: {err:#}");
+				vec![]
+			},
+		}
+	}
+
+	/// Whether the account has to be signed in again before it syncs.
+	pub fn needs_login(&self) -> bool {
+		match self {
+			Service::Microsoft(id) => database()
+				.account(*id)
+				.is_ok_and(|account| account.is_some_and(|account| account.needs_login)),
+			_ => false,
+		}
+	}
+
+	/// Refreshes the tokens of every account that are about to expire,
+	/// returns the accounts the user has to sign in to again.
+	pub async fn refresh_tokens() -> Vec<Self> {
+		let mut needs_login = vec![];
+		for service in Self::accounts() {
+			let Service::Microsoft(account) = service else {
+				continue;
+			};
+			if service.needs_login() {
+				// Its refresh token was already rejected, only signing in helps.
+				needs_login.push(service);
+				continue;
+			}
+			let mut microsoft = MicrosoftService::new(app_id().to_string(), account);
+			if !microsoft.available() {
+				continue;
+			}
+			if let Err(err) = microsoft.refresh_token().await {
+				tracing::error!("Failed to refresh the token of {service}: {err:#}");
+			}
+			if service.needs_login() {
+				needs_login.push(service);
+			}
+		}
+		needs_login
+	}
+
+	/// The name of the service, followed by the account it's signed in to.
+	pub fn label(&self) -> String {
+		match self {
+			Service::Microsoft(id) => {
+				match database().account(*id) {
+					Ok(Some(account)) if !account.name.is_empty() => {
+						format!("{self}\n{}", account.name)
+					},
+					_ => self.to_string(),
+				}
+			},
+			_ => self.to_string(),
+		}
 	}
 
 	/// Returns the icon for the service.
@@ -74,7 +216,7 @@ impl Service {
 			Service::Computer => {
 				"/dev/edfloreshz/Done/icons/scalable/services/computer.png"
 			},
-			Service::Microsoft => {
+			Service::Microsoft(_) => {
 				"/dev/edfloreshz/Done/icons/scalable/services/microsoft-todo.png"
 			},
 		}
@@ -86,8 +228,27 @@ impl Display for Service {
 		let str = match self {
 			Service::Smart => "Smart lists".to_string(),
 			Service::Computer => "Computer".to_string(),
-			Service::Microsoft => "Microsoft To Do".to_string(),
+			Service::Microsoft(_) => "Microsoft To Do".to_string(),
 		};
 		write!(f, "{}", str)
 	}
 }
+
+/// A handle on the database sharing one connection pool.
+pub(crate) fn database() -> Database {
+	DATABASE
+		.get_or_init(|| {
+			let mut database = Database::new(app_id().to_string());
+			if let Err(err) = database.establish_connection() {
+				tracing::error!("Failed to open the database: {err:#}");
+			}
+			database
+		})
+		.clone()
+}
+
+fn app_id() -> &'static str {
+	APP_ID
+		.get()
+		.expect("Must call Services::init before trying to get a service")
+}
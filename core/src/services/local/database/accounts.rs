@@ -0,0 +1,56 @@
+use anyhow::Result;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use uuid::Uuid;
+
+use crate::models::account::Account;
+use crate::schema::accounts::dsl::*;
+
+use super::models::account::QueryableAccount;
+use super::Database;
+
+impl Database {
+	/// Accounts of `account_provider`, in the order they were added.
+	pub fn accounts(&mut self, account_provider: &str) -> Result<Vec<Account>> {
+		let rows = accounts
+			.filter(provider.eq(account_provider))
+			.order(created_date_time.asc())
+			.load::<QueryableAccount>(&mut self.establish_connection()?)?;
+		let mut found = Vec::with_capacity(rows.len());
+		for row in rows {
+			match Account::try_from(row) {
+				Ok(account) => found.push(account),
+				Err(err) => tracing::warn!("Skipping account: {err:#}"),
+			}
+		}
+		Ok(found)
+	}
+
+	pub fn account(&mut self, id: Uuid) -> Result<Option<Account>> {
+		let row = accounts
+			.find(id.to_string())
+			.first::<QueryableAccount>(&mut self.establish_connection()?)
+			.ok();
+		row.map(TryInto::try_into).transpose()
+	}
+
+	/// Adds an account or updates the one with the same id.
+	pub fn save_account(&mut self, account: &Account) -> Result<()> {
+		diesel::replace_into(accounts)
+			.values(QueryableAccount::from(account.clone()))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+
+	pub fn set_account_needs_login(&mut self, id: Uuid, needed: bool) -> Result<()> {
+		diesel::update(accounts.filter(id_account.eq(id.to_string())))
+			.set(needs_login.eq(needed))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+
+	pub fn remove_account(&mut self, id: Uuid) -> Result<()> {
+		diesel::delete(accounts.filter(id_account.eq(id.to_string())))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+}
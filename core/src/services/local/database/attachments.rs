@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::models::attachment::{attachments_dir, Attachment, AttachmentStore};
+use crate::models::task::Task;
+use crate::schema::attachments::dsl::*;
+
+use super::models::attachment::QueryableAttachment;
+use super::Database;
+
+impl Database {
+	/// Attachments of a task, oldest first.
+	pub fn attachments(&mut self, task_id: &str) -> Result<Vec<Attachment>> {
+		let rows = attachments
+			.filter(id_task.eq(task_id))
+			.order(created_date_time.asc())
+			.load::<QueryableAttachment>(&mut self.establish_connection()?)?;
+		Ok(rows.into_iter().map(Into::into).collect())
+	}
+
+	/// Ids of the tasks that have at least one attachment.
+	pub fn tasks_with_attachments(&mut self) -> Result<HashSet<String>> {
+		Ok(
+			attachments
+				.select(id_task)
+				.distinct()
+				.load::<String>(&mut self.establish_connection()?)?
+				.into_iter()
+				.collect(),
+		)
+	}
+
+	/// Sets `has_attachments` on each task from the stored attachments.
+	pub fn apply_attachments(&mut self, tasks: &mut [Task]) -> Result<()> {
+		let attached = self.tasks_with_attachments()?;
+		for task in tasks {
+			task.has_attachments = attached.contains(&task.id);
+		}
+		Ok(())
+	}
+
+	/// Where the file of a local attachment is stored.
+	pub fn attachment_path(&self, attachment: &Attachment) -> Result<PathBuf> {
+		let directory =
+			attachments_dir(&self.application_id, AttachmentStore::Data)?;
+		attachment.path_in(&directory)
+	}
+
+	/// Copies the file at `source` into the app data directory and attaches
+	/// it to a task.
+	pub fn add_attachment(
+		&mut self,
+		task_id: &str,
+		source: &Path,
+	) -> Result<Attachment> {
+		let attachment = Attachment::from_path(task_id.to_string(), source)?;
+		let path = self.attachment_path(&attachment)?;
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+		std::fs::copy(source, &path)
+			.with_context(|| format!("Failed to copy {}", source.display()))?;
+		diesel::insert_into(attachments)
+			.values(QueryableAttachment::from(attachment.clone()))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(attachment)
+	}
+
+	/// Removes an attachment and its file.
+	pub fn remove_attachment(&mut self, attachment: &Attachment) -> Result<()> {
+		diesel::delete(attachments.filter(id_attachment.eq(&attachment.id)))
+			.execute(&mut self.establish_connection()?)?;
+		let path = self.attachment_path(attachment)?;
+		if let Some(parent) = path.parent().filter(|parent| parent.exists()) {
+			std::fs::remove_dir_all(parent)?;
+		}
+		Ok(())
+	}
+
+	/// Removes every attachment of a deleted task.
+	pub fn remove_task_attachments(&mut self, task_id: &str) -> Result<()> {
+		for attachment in self.attachments(task_id)? {
+			self.remove_attachment(&attachment)?;
+		}
+		Ok(())
+	}
+}
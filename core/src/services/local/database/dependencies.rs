@@ -0,0 +1,126 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use diesel::{
+	BoolExpressionMethods, Connection, ExpressionMethods, QueryDsl, RunQueryDsl,
+};
+
+use crate::models::dependency::{creates_cycle, Dependency, DependencyError};
+use crate::schema::task_dependencies::dsl::*;
+
+use super::models::dependency::QueryableDependency;
+use super::Database;
+
+impl Database {
+	/// Returns the prerequisites of a task.
+	pub fn dependencies(&mut self, task_id: &str) -> Result<Vec<Dependency>> {
+		let rows = task_dependencies
+			.filter(id_task.eq(task_id))
+			.load::<QueryableDependency>(&mut self.establish_connection()?)?;
+		Ok(rows.into_iter().map(Into::into).collect())
+	}
+
+	/// Prerequisites of every task that has any, keyed by the dependent task.
+	pub fn all_dependencies(
+		&mut self,
+	) -> Result<HashMap<String, Vec<Dependency>>> {
+		let rows = task_dependencies
+			.load::<QueryableDependency>(&mut self.establish_connection()?)?;
+		let mut dependencies: HashMap<String, Vec<Dependency>> = HashMap::new();
+		for dependency in rows.into_iter().map(Dependency::from) {
+			dependencies
+				.entry(dependency.task_id.clone())
+				.or_default()
+				.push(dependency);
+		}
+		Ok(dependencies)
+	}
+
+	/// Stores a new dependency, refusing edges that would create a cycle.
+	pub fn add_dependency(&mut self, dependency: Dependency) -> Result<()> {
+		if dependency.task_id == dependency.prerequisite_id {
+			return Err(DependencyError::SelfDependency.into());
+		}
+		let mut connection = self.establish_connection()?;
+		let existing: Vec<Dependency> = task_dependencies
+			.load::<QueryableDependency>(&mut connection)?
+			.into_iter()
+			.map(Into::into)
+			.collect();
+		if creates_cycle(
+			&existing,
+			&dependency.task_id,
+			&dependency.prerequisite_id,
+		) {
+			return Err(DependencyError::Cycle(dependency.prerequisite_title).into());
+		}
+		diesel::replace_into(task_dependencies)
+			.values(QueryableDependency::from(dependency))
+			.execute(&mut connection)?;
+		Ok(())
+	}
+
+	pub fn remove_dependency(
+		&mut self,
+		task_id: &str,
+		prerequisite_id: &str,
+	) -> Result<()> {
+		diesel::delete(
+			task_dependencies
+				.filter(id_task.eq(task_id))
+				.filter(depends_on.eq(prerequisite_id)),
+		)
+		.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+
+	/// Removes every dependency a task takes part in, either side.
+	pub fn remove_task_dependencies(&mut self, task_id: &str) -> Result<()> {
+		diesel::delete(
+			task_dependencies.filter(id_task.eq(task_id).or(depends_on.eq(task_id))),
+		)
+		.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+
+	/// Ids of the tasks that still have an unfinished prerequisite.
+	pub fn blocked_tasks(&mut self) -> Result<HashSet<String>> {
+		let ids = task_dependencies
+			.filter(resolved.eq(false))
+			.select(id_task)
+			.distinct()
+			.load::<String>(&mut self.establish_connection()?)?;
+		Ok(ids.into_iter().collect())
+	}
+
+	/// Records whether a prerequisite is done and returns the dependencies
+	/// whose state changed. Dependents of a completed prerequisite that have
+	/// nothing else blocking them can be found with [`Self::blocked_tasks`].
+	pub fn set_prerequisite_completed(
+		&mut self,
+		prerequisite_id: &str,
+		completed: bool,
+		title: &str,
+	) -> Result<Vec<Dependency>> {
+		let mut connection = self.establish_connection()?;
+		let changed = connection.transaction(|connection| {
+			let changed = task_dependencies
+				.filter(depends_on.eq(prerequisite_id))
+				.filter(resolved.ne(completed))
+				.load::<QueryableDependency>(connection)?;
+			diesel::update(task_dependencies.filter(depends_on.eq(prerequisite_id)))
+				.set((resolved.eq(completed), depends_on_title.eq(title)))
+				.execute(connection)?;
+			diesel::result::QueryResult::Ok(changed)
+		})?;
+		Ok(
+			changed
+				.into_iter()
+				.map(|row| Dependency {
+					resolved: completed,
+					..row.into()
+				})
+				.collect(),
+		)
+	}
+}
@@ -0,0 +1,281 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use anyhow::Result;
+use diesel::sql_types::Text;
+use diesel::{
+	Connection, ExpressionMethods, QueryDsl, QueryableByName, RunQueryDsl,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{priority::Priority, status::Status};
+use crate::schema::{lists, sub_tasks, tasks};
+
+use super::models::{
+	list::QueryableList, sub_task::QueryableSubTask, task::QueryableTask,
+};
+use super::Database;
+
+#[derive(QueryableByName)]
+struct IntegrityCheckRow {
+	#[diesel(sql_type = Text)]
+	integrity_check: String,
+}
+
+/// A column of a task row that holds a value the application can't read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskColumn {
+	Tags,
+	Status,
+	Priority,
+	SubTaskStatus,
+}
+
+impl Display for TaskColumn {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let str = match self {
+			TaskColumn::Tags => "tags",
+			TaskColumn::Status => "status",
+			TaskColumn::Priority => "priority",
+			TaskColumn::SubTaskStatus => "sub-task status",
+		};
+		write!(f, "{}", str)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidField {
+	pub task_id: String,
+	pub column: TaskColumn,
+	pub value: String,
+}
+
+/// Result of [`Database::check_integrity`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+	/// Messages returned by `PRAGMA integrity_check`, empty when SQLite is happy.
+	pub integrity_errors: Vec<String>,
+	/// Tasks whose `parent` does not match any list.
+	pub orphan_tasks: Vec<String>,
+	/// Sub-tasks whose parent task or sub-task no longer exists.
+	pub orphan_sub_tasks: Vec<String>,
+	/// Malformed JSON columns and out-of-range enum values.
+	pub invalid_fields: Vec<InvalidField>,
+}
+
+impl IntegrityReport {
+	pub fn is_clean(&self) -> bool {
+		self.issue_count() == 0
+	}
+
+	pub fn issue_count(&self) -> usize {
+		self.integrity_errors.len()
+			+ self.orphan_tasks.len()
+			+ self.orphan_sub_tasks.len()
+			+ self.invalid_fields.len()
+	}
+
+	/// Whether [`Database::repair`] can do anything about the issues found.
+	/// Low level corruption reported by SQLite has to be fixed by hand.
+	pub fn repairable(&self) -> bool {
+		!self.orphan_tasks.is_empty()
+			|| !self.orphan_sub_tasks.is_empty()
+			|| !self.invalid_fields.is_empty()
+	}
+}
+
+impl Display for IntegrityReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if self.is_clean() {
+			return write!(f, "No issues found.");
+		}
+		for error in &self.integrity_errors {
+			writeln!(f, "SQLite: {error}")?;
+		}
+		for task in &self.orphan_tasks {
+			writeln!(f, "Task {task} belongs to a list that no longer exists")?;
+		}
+		for sub_task in &self.orphan_sub_tasks {
+			writeln!(f, "Sub-task {sub_task} belongs to a task that no longer exists")?;
+		}
+		for field in &self.invalid_fields {
+			writeln!(
+				f,
+				"Task {} has an invalid {} value: {}",
+				field.task_id, field.column, field.value
+			)?;
+		}
+		write!(f, "{} issue(s) found.", self.issue_count())
+	}
+}
+
+/// Result of [`Database::repair`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairSummary {
+	pub reattached_tasks: usize,
+	pub removed_sub_tasks: usize,
+	pub reset_fields: usize,
+}
+
+impl Display for RepairSummary {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"Reattached {} task(s), removed {} sub-task(s) and reset {} field(s).",
+			self.reattached_tasks, self.removed_sub_tasks, self.reset_fields
+		)
+	}
+}
+
+impl Database {
+	/// Runs SQLite's own integrity check and looks for rows the application
+	/// can't load: orphaned tasks, malformed JSON and out-of-range integers.
+	pub fn check_integrity(&mut self) -> Result<IntegrityReport> {
+		let mut connection = self.establish_connection()?;
+
+		let integrity_errors = diesel::sql_query("PRAGMA integrity_check")
+			.load::<IntegrityCheckRow>(&mut connection)?
+			.into_iter()
+			.map(|row| row.integrity_check)
+			.filter(|message| message != "ok")
+			.collect();
+
+		let list_ids: HashSet<String> = lists::table
+			.select(lists::id_list)
+			.load::<String>(&mut connection)?
+			.into_iter()
+			.collect();
+
+		let rows = tasks::table.load::<QueryableTask>(&mut connection)?;
+		let sub_task_rows =
+			sub_tasks::table.load::<QueryableSubTask>(&mut connection)?;
+
+		let mut report = IntegrityReport {
+			integrity_errors,
+			..Default::default()
+		};
+
+		let mut parent_ids: HashSet<&str> =
+			rows.iter().map(|row| row.id_task.as_str()).collect();
+		parent_ids.extend(sub_task_rows.iter().map(|row| row.id_sub_task.as_str()));
+
+		for row in &sub_task_rows {
+			if !parent_ids.contains(row.root_task.as_str())
+				|| !parent_ids.contains(row.parent_task.as_str())
+			{
+				report.orphan_sub_tasks.push(row.id_sub_task.clone());
+			}
+			if Status::try_from(row.status).is_err() {
+				report.invalid_fields.push(InvalidField {
+					task_id: row.id_sub_task.clone(),
+					column: TaskColumn::SubTaskStatus,
+					value: row.status.to_string(),
+				});
+			}
+		}
+
+		for row in &rows {
+			if !list_ids.contains(&row.parent) {
+				report.orphan_tasks.push(row.id_task.clone());
+			}
+			let invalid = |column, value: String| InvalidField {
+				task_id: row.id_task.clone(),
+				column,
+				value,
+			};
+			if serde_json::from_str::<Vec<String>>(&row.tags).is_err() {
+				report
+					.invalid_fields
+					.push(invalid(TaskColumn::Tags, row.tags.clone()));
+			}
+			if Status::try_from(row.status).is_err() {
+				report
+					.invalid_fields
+					.push(invalid(TaskColumn::Status, row.status.to_string()));
+			}
+			if Priority::try_from(row.priority).is_err() {
+				report
+					.invalid_fields
+					.push(invalid(TaskColumn::Priority, row.priority.to_string()));
+			}
+		}
+
+		Ok(report)
+	}
+
+	/// Fixes the issues in `report`: orphaned tasks are moved to the list
+	/// called `list_name`, created with `list_description` when missing,
+	/// orphaned sub-tasks are dropped and unreadable fields are reset to their
+	/// defaults. Both strings come from the caller so they can be localized.
+	pub fn repair(
+		&mut self,
+		report: &IntegrityReport,
+		list_name: &str,
+		list_description: &str,
+	) -> Result<RepairSummary> {
+		let mut connection = self.establish_connection()?;
+		let summary = connection.transaction(|connection| {
+			let mut summary = RepairSummary::default();
+
+			if !report.orphan_tasks.is_empty() {
+				let recovered_list = match lists::table
+					.filter(lists::name.eq(list_name))
+					.select(lists::id_list)
+					.first::<String>(connection)
+				{
+					Ok(id) => id,
+					Err(diesel::result::Error::NotFound) => {
+						let list = QueryableList::new(
+							list_name,
+							list_description,
+							Some("🩹".to_string()),
+						);
+						diesel::insert_into(lists::table)
+							.values(&list)
+							.execute(connection)?;
+						list.id_list
+					},
+					Err(err) => return Err(err),
+				};
+
+				summary.reattached_tasks = diesel::update(
+					tasks::table.filter(tasks::id_task.eq_any(&report.orphan_tasks)),
+				)
+				.set(tasks::parent.eq(recovered_list))
+				.execute(connection)?;
+			}
+
+			summary.removed_sub_tasks = diesel::delete(
+				sub_tasks::table
+					.filter(sub_tasks::id_sub_task.eq_any(&report.orphan_sub_tasks)),
+			)
+			.execute(connection)?;
+
+			let empty = String::from("[]");
+			for field in &report.invalid_fields {
+				let task = tasks::table.filter(tasks::id_task.eq(&field.task_id));
+				summary.reset_fields += match field.column {
+					TaskColumn::Tags => diesel::update(task)
+						.set(tasks::tags.eq(&empty))
+						.execute(connection)?,
+					TaskColumn::Status => diesel::update(task)
+						.set(tasks::status.eq(i32::from(Status::default())))
+						.execute(connection)?,
+					TaskColumn::Priority => diesel::update(task)
+						.set(tasks::priority.eq(i32::from(Priority::default())))
+						.execute(connection)?,
+					TaskColumn::SubTaskStatus => diesel::update(
+						sub_tasks::table
+							.filter(sub_tasks::id_sub_task.eq(&field.task_id)),
+					)
+					.set(sub_tasks::status.eq(i32::from(Status::default())))
+					.execute(connection)?,
+				};
+			}
+
+			diesel::result::QueryResult::Ok(summary)
+		})?;
+
+		Ok(summary)
+	}
+}
@@ -1,13 +1,29 @@
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use diesel::r2d2;
 use diesel::r2d2::{ConnectionManager, PooledConnection};
-use diesel::SqliteConnection;
+use diesel::{
+	Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection,
+};
 use diesel_migrations::{
 	embed_migrations, EmbeddedMigrations, MigrationHarness,
 };
 use libset::{Config, FileType};
 
+use crate::schema::{quarantined_tasks, sub_tasks, tasks};
+
+use self::models::{
+	quarantine::QueryableQuarantinedTask, sub_task::QueryableSubTask,
+	task::QueryableTask,
+};
+
+pub mod accounts;
+pub mod attachments;
+pub mod dependencies;
+pub mod integrity;
 pub mod models;
+pub mod my_day;
+pub mod overlays;
+pub mod time_entries;
 
 pub type Pool = r2d2::Pool<r2d2::ConnectionManager<SqliteConnection>>;
 
@@ -20,17 +36,11 @@ pub struct Database {
 }
 
 impl Database {
-	pub fn new(application_id: String) -> Result<Self> {
-		let database = Self {
+	pub fn new(application_id: String) -> Self {
+		Self {
 			application_id,
 			pool: None,
-		};
-
-		database
-			.ensure_database_exists()
-			.expect("Failed to ensure database exists.");
-
-		Ok(database)
+		}
 	}
 
 	pub fn database_url(&self) -> Result<String> {
@@ -46,22 +56,24 @@ impl Database {
 		&mut self,
 	) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>> {
 		if self.pool.is_none() {
+			self
+				.ensure_database_exists()
+				.context("Failed to ensure database exists.")?;
 			let manager =
 				ConnectionManager::<SqliteConnection>::new(self.database_url()?);
 			let pool = Pool::builder()
 				.build(manager)
-				.expect("Failed to create pool");
+				.context("Failed to create pool")?;
 			self.pool = Some(pool);
+			self
+				.ensure_migrations_up_to_date()
+				.context("Failed to ensure migrations are up to date")?;
 		}
 
 		let Some(pool) = &self.pool else {
 			bail!("Failed to get pool");
 		};
 
-		self
-			.ensure_migrations_up_to_date()
-			.expect("Failed to ensure migrations are up to date");
-
 		pool.get().map_err(|e| anyhow!(e))
 	}
 
@@ -92,4 +104,70 @@ impl Database {
 		}
 		Ok(())
 	}
+
+	/// Moves a task row that can no longer be read into the quarantine table,
+	/// keeping its original contents so it can be inspected or repaired later.
+	/// Deleting the task also deletes its sub-tasks, so those are kept in the
+	/// quarantined payload too.
+	pub fn quarantine_task(
+		&mut self,
+		task: QueryableTask,
+		reason: String,
+	) -> Result<()> {
+		self.establish_connection()?.transaction(|connection| {
+			let sub_task_rows = sub_tasks::table
+				.filter(sub_tasks::root_task.eq(&task.id_task))
+				.order(sub_tasks::position.asc())
+				.load::<QueryableSubTask>(connection)?;
+			let quarantined =
+				QueryableQuarantinedTask::new(&task, &sub_task_rows, reason);
+			diesel::replace_into(quarantined_tasks::table)
+				.values(&quarantined)
+				.execute(connection)?;
+			diesel::delete(tasks::table.filter(tasks::id_task.eq(task.id_task)))
+				.execute(connection)?;
+			diesel::result::QueryResult::Ok(())
+		})?;
+		Ok(())
+	}
+
+	/// Points the local data of a task, like its attachments, dependencies,
+	/// time entries, overlay and My Day entry, at the id it got when it was
+	/// moved.
+	pub fn move_task_data(&mut self, from: &str, to: &str) -> Result<()> {
+		use crate::schema::{
+			attachments as a, my_day as m, task_dependencies as d,
+			task_overlays as o, time_entries as e,
+		};
+
+		self.establish_connection()?.transaction(|connection| {
+			diesel::update(a::table.filter(a::id_task.eq(from)))
+				.set(a::id_task.eq(to))
+				.execute(connection)?;
+			diesel::update(d::table.filter(d::id_task.eq(from)))
+				.set(d::id_task.eq(to))
+				.execute(connection)?;
+			diesel::update(d::table.filter(d::depends_on.eq(from)))
+				.set(d::depends_on.eq(to))
+				.execute(connection)?;
+			diesel::update(e::table.filter(e::id_task.eq(from)))
+				.set(e::id_task.eq(to))
+				.execute(connection)?;
+			diesel::update(o::table.filter(o::id_task.eq(from)))
+				.set(o::id_task.eq(to))
+				.execute(connection)?;
+			diesel::update(m::table.filter(m::id_task.eq(from)))
+				.set(m::id_task.eq(to))
+				.execute(connection)?;
+			diesel::result::QueryResult::Ok(())
+		})?;
+		Ok(())
+	}
+
+	/// Returns every task row that was quarantined because it was corrupt.
+	pub fn quarantined_tasks(&mut self) -> Result<Vec<QueryableQuarantinedTask>> {
+		let rows = quarantined_tasks::table
+			.load::<QueryableQuarantinedTask>(&mut self.establish_connection()?)?;
+		Ok(rows)
+	}
 }
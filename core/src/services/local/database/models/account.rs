@@ -0,0 +1,47 @@
+use anyhow::Context;
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{models::account::Account, schema::accounts};
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = accounts)]
+pub struct QueryableAccount {
+	pub id_account: String,
+	pub provider: String,
+	pub name: String,
+	pub remote_id: String,
+	pub created_date_time: NaiveDateTime,
+	pub needs_login: bool,
+}
+
+impl TryFrom<QueryableAccount> for Account {
+	type Error = anyhow::Error;
+
+	fn try_from(value: QueryableAccount) -> Result<Self, Self::Error> {
+		Ok(Self {
+			id: Uuid::parse_str(&value.id_account)
+				.with_context(|| format!("Invalid account id: {}", value.id_account))?,
+			provider: value.provider,
+			name: value.name,
+			remote_id: value.remote_id,
+			created_date_time: value.created_date_time.and_utc(),
+			needs_login: value.needs_login,
+		})
+	}
+}
+
+impl From<Account> for QueryableAccount {
+	fn from(value: Account) -> Self {
+		Self {
+			id_account: value.id.to_string(),
+			provider: value.provider,
+			name: value.name,
+			remote_id: value.remote_id,
+			created_date_time: value.created_date_time.naive_utc(),
+			needs_login: value.needs_login,
+		}
+	}
+}
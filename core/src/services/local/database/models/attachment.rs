@@ -0,0 +1,42 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{models::attachment::Attachment, schema::attachments};
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = attachments)]
+pub struct QueryableAttachment {
+	pub id_attachment: String,
+	pub id_task: String,
+	pub name: String,
+	pub content_type: String,
+	pub size: i64,
+	pub created_date_time: NaiveDateTime,
+}
+
+impl From<QueryableAttachment> for Attachment {
+	fn from(value: QueryableAttachment) -> Self {
+		Self {
+			id: value.id_attachment,
+			task_id: value.id_task,
+			name: value.name,
+			content_type: value.content_type,
+			size: value.size as u64,
+			created_date_time: value.created_date_time.and_utc(),
+		}
+	}
+}
+
+impl From<Attachment> for QueryableAttachment {
+	fn from(value: Attachment) -> Self {
+		Self {
+			id_attachment: value.id,
+			id_task: value.task_id,
+			name: value.name,
+			content_type: value.content_type,
+			size: value.size as i64,
+			created_date_time: value.created_date_time.naive_utc(),
+		}
+	}
+}
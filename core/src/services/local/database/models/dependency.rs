@@ -0,0 +1,35 @@
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{models::dependency::Dependency, schema::task_dependencies};
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = task_dependencies)]
+pub struct QueryableDependency {
+	pub id_task: String,
+	pub depends_on: String,
+	pub depends_on_title: String,
+	pub resolved: bool,
+}
+
+impl From<QueryableDependency> for Dependency {
+	fn from(value: QueryableDependency) -> Self {
+		Self {
+			task_id: value.id_task,
+			prerequisite_id: value.depends_on,
+			prerequisite_title: value.depends_on_title,
+			resolved: value.resolved,
+		}
+	}
+}
+
+impl From<Dependency> for QueryableDependency {
+	fn from(value: Dependency) -> Self {
+		Self {
+			id_task: value.task_id,
+			depends_on: value.prerequisite_id,
+			depends_on_title: value.prerequisite_title,
+			resolved: value.resolved,
+		}
+	}
+}
@@ -11,6 +11,7 @@ pub struct QueryableList {
 	pub name: String,
 	pub description: String,
 	pub icon_name: Option<String>,
+	pub id_group: Option<String>,
 }
 
 impl QueryableList {
@@ -24,6 +25,7 @@ impl QueryableList {
 			name: display_name.to_string(),
 			description: description.to_string(),
 			icon_name,
+			id_group: None,
 		}
 	}
 }
@@ -36,6 +38,8 @@ impl From<QueryableList> for List {
 			service: Service::Computer,
 			icon: value.icon_name,
 			description: value.description,
+			group: value.id_group,
+			..Default::default()
 		}
 	}
 }
@@ -47,6 +51,7 @@ impl From<List> for QueryableList {
 			name: list.name,
 			description: list.description,
 			icon_name: list.icon,
+			id_group: list.group,
 		}
 	}
 }
@@ -0,0 +1,38 @@
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::list_group::ListGroup, schema::list_groups, service::Service,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Queryable, Insertable)]
+#[diesel(table_name = list_groups)]
+pub struct QueryableListGroup {
+	pub id_group: String,
+	pub name: String,
+	pub position: i32,
+	pub expanded: bool,
+}
+
+impl From<QueryableListGroup> for ListGroup {
+	fn from(value: QueryableListGroup) -> Self {
+		ListGroup {
+			id: value.id_group,
+			name: value.name,
+			position: value.position,
+			expanded: value.expanded,
+			service: Service::Computer,
+		}
+	}
+}
+
+impl From<ListGroup> for QueryableListGroup {
+	fn from(group: ListGroup) -> Self {
+		Self {
+			id_group: group.id,
+			name: group.name,
+			position: group.position,
+			expanded: group.expanded,
+		}
+	}
+}
@@ -1,3 +1,20 @@
+pub mod account;
+pub mod attachment;
+
+pub mod dependency;
+
 pub mod list;
 
+pub mod list_group;
+
+pub mod my_day;
+
+pub mod quarantine;
+
+pub mod sub_task;
+
 pub mod task;
+
+pub mod task_overlay;
+
+pub mod time_entry;
@@ -0,0 +1,13 @@
+use chrono::NaiveDate;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::my_day;
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = my_day)]
+pub struct QueryableMyDay {
+	pub id_task: String,
+	/// Local date the task was planned for.
+	pub planned_on: NaiveDate,
+}
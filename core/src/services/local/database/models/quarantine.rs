@@ -0,0 +1,35 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::quarantined_tasks;
+
+use super::{sub_task::QueryableSubTask, task::QueryableTask};
+
+/// A task row that could not be converted into a [`Task`](crate::models::task::Task)
+/// and was moved out of the `tasks` table so it no longer breaks reads. The
+/// payload holds the task row along with its sub-task rows.
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = quarantined_tasks)]
+pub struct QueryableQuarantinedTask {
+	pub id_task: String,
+	pub payload: String,
+	pub reason: String,
+	pub quarantined_date: NaiveDateTime,
+}
+
+impl QueryableQuarantinedTask {
+	pub fn new(
+		task: &QueryableTask,
+		sub_tasks: &[QueryableSubTask],
+		reason: String,
+	) -> Self {
+		let payload = serde_json::json!({ "task": task, "sub_tasks": sub_tasks });
+		Self {
+			id_task: task.id_task.clone(),
+			payload: payload.to_string(),
+			reason,
+			quarantined_date: Utc::now().naive_utc(),
+		}
+	}
+}
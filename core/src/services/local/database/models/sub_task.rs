@@ -0,0 +1,76 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	models::{status::Status, task::Task},
+	schema::sub_tasks,
+};
+
+/// A row of the `sub_tasks` table. `parent_task` points at the task or
+/// sub-task directly above this one, while `root_task` always points at the
+/// top level task so a whole tree can be loaded with a single query.
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = sub_tasks)]
+pub struct QueryableSubTask {
+	pub id_sub_task: String,
+	pub parent_task: String,
+	pub root_task: String,
+	pub position: i32,
+	pub title: String,
+	pub status: i32,
+	pub notes: String,
+	pub due_date: Option<NaiveDateTime>,
+	pub created_date_time: NaiveDateTime,
+	pub last_modified_date_time: NaiveDateTime,
+}
+
+impl QueryableSubTask {
+	/// Flattens the sub-task tree of `task` into rows, depth first.
+	pub fn from_tree(task: &Task) -> Vec<Self> {
+		let mut rows = vec![];
+		Self::flatten(&task.id, &task.id, &task.sub_tasks, &mut rows);
+		rows
+	}
+
+	fn flatten(
+		root_task: &str,
+		parent_task: &str,
+		sub_tasks: &[Task],
+		rows: &mut Vec<Self>,
+	) {
+		for (position, sub_task) in sub_tasks.iter().enumerate() {
+			rows.push(Self {
+				id_sub_task: sub_task.id.clone(),
+				parent_task: parent_task.to_string(),
+				root_task: root_task.to_string(),
+				position: position as i32,
+				title: sub_task.title.clone(),
+				status: sub_task.status.into(),
+				notes: sub_task.notes.clone(),
+				due_date: sub_task.due_date.map(|dt| dt.naive_utc()),
+				created_date_time: sub_task.created_date_time.naive_utc(),
+				last_modified_date_time: sub_task.last_modified_date_time.naive_utc(),
+			});
+			Self::flatten(root_task, &sub_task.id, &sub_task.sub_tasks, rows);
+		}
+	}
+}
+
+impl TryFrom<QueryableSubTask> for Task {
+	type Error = anyhow::Error;
+
+	fn try_from(value: QueryableSubTask) -> Result<Self, Self::Error> {
+		Ok(Task {
+			id: value.id_sub_task,
+			parent: value.parent_task,
+			title: value.title,
+			status: Status::try_from(value.status)?,
+			notes: value.notes,
+			due_date: value.due_date.map(|ndt| ndt.and_utc()),
+			created_date_time: value.created_date_time.and_utc(),
+			last_modified_date_time: value.last_modified_date_time.and_utc(),
+			..Default::default()
+		})
+	}
+}
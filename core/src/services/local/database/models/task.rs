@@ -1,3 +1,4 @@
+use anyhow::Context;
 use chrono::{NaiveDateTime, Utc};
 use diesel::{Insertable, Queryable};
 use serde::{Deserialize, Serialize};
@@ -20,7 +21,6 @@ pub struct QueryableTask {
 	pub today: bool,
 	pub status: i32,
 	pub priority: i32,
-	pub sub_tasks: String,
 	pub tags: String,
 	pub notes: String,
 	pub completion_date: Option<NaiveDateTime>,
@@ -30,6 +30,9 @@ pub struct QueryableTask {
 	pub recurrence: String,
 	pub created_date_time: NaiveDateTime,
 	pub last_modified_date_time: NaiveDateTime,
+	pub estimate: Option<i32>,
+	pub start_date: Option<NaiveDateTime>,
+	pub linked_resources: String,
 }
 
 impl QueryableTask {
@@ -44,7 +47,6 @@ impl QueryableTask {
 			notes: String::new(),
 			status: Status::NotStarted as i32,
 			priority: Priority::Low as i32,
-			sub_tasks: serde_json::to_string(&empty_vec).unwrap(),
 			tags: serde_json::to_string(&empty_vec).unwrap(),
 			completion_date: None,
 			deletion_date: None,
@@ -53,6 +55,9 @@ impl QueryableTask {
 			recurrence: String::new(),
 			created_date_time: Utc::now().naive_utc(),
 			last_modified_date_time: Utc::now().naive_utc(),
+			estimate: None,
+			start_date: None,
+			linked_resources: serde_json::to_string(&empty_vec).unwrap(),
 		}
 	}
 }
@@ -68,39 +73,50 @@ impl From<Task> for QueryableTask {
 			notes: value.notes,
 			status: value.status.into(),
 			priority: value.priority.into(),
-			sub_tasks: serde_json::to_string(&value.sub_tasks).unwrap(),
 			tags: serde_json::to_string(&value.tags).unwrap(),
-			completion_date: value.completion_date.map(|dt| dt.naive_local()),
-			deletion_date: value.deletion_date.map(|dt| dt.naive_local()),
-			due_date: value.due_date.map(|dt| dt.naive_local()),
-			reminder_date: value.reminder_date.map(|dt| dt.naive_local()),
+			completion_date: value.completion_date.map(|dt| dt.naive_utc()),
+			deletion_date: value.deletion_date.map(|dt| dt.naive_utc()),
+			due_date: value.due_date.map(|dt| dt.naive_utc()),
+			reminder_date: value.reminder_date.map(|dt| dt.naive_utc()),
 			recurrence: value.recurrence.to_string(),
-			created_date_time: value.created_date_time.naive_local(),
-			last_modified_date_time: value.last_modified_date_time.naive_local(),
+			created_date_time: value.created_date_time.naive_utc(),
+			last_modified_date_time: value.last_modified_date_time.naive_utc(),
+			estimate: value.estimate.map(|minutes| minutes as i32),
+			start_date: value.start_date.map(|dt| dt.naive_utc()),
+			linked_resources: serde_json::to_string(&value.linked_resources)
+				.unwrap(),
 		}
 	}
 }
 
-impl From<QueryableTask> for Task {
-	fn from(value: QueryableTask) -> Self {
-		Task {
+impl TryFrom<QueryableTask> for Task {
+	type Error = anyhow::Error;
+
+	fn try_from(value: QueryableTask) -> Result<Self, Self::Error> {
+		Ok(Task {
 			id: value.id_task,
 			parent: value.parent,
 			title: value.title,
 			favorite: value.favorite,
 			today: value.today,
 			notes: value.notes,
-			status: value.status.into(),
-			priority: value.priority.into(),
-			sub_tasks: serde_json::from_str(&value.sub_tasks).unwrap(),
-			tags: serde_json::from_str(&value.tags).unwrap(),
+			status: value.status.try_into()?,
+			priority: value.priority.try_into()?,
+			sub_tasks: vec![],
+			tags: serde_json::from_str(&value.tags)
+				.context("Malformed tags column")?,
 			completion_date: value.completion_date.map(|ndt| ndt.and_utc()),
 			deletion_date: value.deletion_date.map(|ndt| ndt.and_utc()),
 			due_date: value.due_date.map(|ndt| ndt.and_utc()),
+			start_date: value.start_date.map(|ndt| ndt.and_utc()),
 			reminder_date: value.reminder_date.map(|ndt| ndt.and_utc()),
 			recurrence: Recurrence::from_string(value.recurrence),
 			created_date_time: value.created_date_time.and_utc(),
 			last_modified_date_time: value.last_modified_date_time.and_utc(),
-		}
+			estimate: value.estimate.map(|minutes| minutes as u32),
+			has_attachments: false,
+			linked_resources: serde_json::from_str(&value.linked_resources)
+				.context("Malformed linked resources column")?,
+		})
 	}
 }
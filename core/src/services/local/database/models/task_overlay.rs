@@ -0,0 +1,12 @@
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::task_overlays;
+
+/// Fields kept locally for tasks whose service has nowhere to store them.
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = task_overlays)]
+pub struct QueryableTaskOverlay {
+	pub id_task: String,
+	pub estimate: Option<i32>,
+}
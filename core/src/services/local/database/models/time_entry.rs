@@ -0,0 +1,39 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+
+use crate::{models::time_entry::TimeEntry, schema::time_entries};
+
+#[derive(Debug, Clone, Insertable, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = time_entries)]
+pub struct QueryableTimeEntry {
+	pub id_time_entry: String,
+	pub id_task: String,
+	pub start_time: NaiveDateTime,
+	pub end_time: Option<NaiveDateTime>,
+	pub note: String,
+}
+
+impl From<QueryableTimeEntry> for TimeEntry {
+	fn from(value: QueryableTimeEntry) -> Self {
+		Self {
+			id: value.id_time_entry,
+			task_id: value.id_task,
+			start: value.start_time.and_utc(),
+			end: value.end_time.map(|date| date.and_utc()),
+			note: value.note,
+		}
+	}
+}
+
+impl From<TimeEntry> for QueryableTimeEntry {
+	fn from(value: TimeEntry) -> Self {
+		Self {
+			id_time_entry: value.id,
+			id_task: value.task_id,
+			start_time: value.start.naive_utc(),
+			end_time: value.end.map(|date| date.naive_utc()),
+			note: value.note,
+		}
+	}
+}
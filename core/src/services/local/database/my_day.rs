@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::Duration;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::models::dates::local_today;
+use crate::models::task::Task;
+use crate::schema::my_day::dsl::*;
+use crate::schema::tasks;
+
+use super::models::my_day::QueryableMyDay;
+use super::Database;
+
+impl Database {
+	/// Ids of the tasks planned for today.
+	pub fn my_day(&mut self) -> Result<HashSet<String>> {
+		Ok(
+			my_day
+				.filter(planned_on.eq(local_today()))
+				.select(id_task)
+				.load::<String>(&mut self.establish_connection()?)?
+				.into_iter()
+				.collect(),
+		)
+	}
+
+	/// Ids of the tasks that were planned for an earlier day and not for
+	/// today, whether or not they got done.
+	pub fn my_day_leftovers(&mut self) -> Result<HashSet<String>> {
+		Ok(
+			my_day
+				.filter(planned_on.lt(local_today()))
+				.select(id_task)
+				.load::<String>(&mut self.establish_connection()?)?
+				.into_iter()
+				.collect(),
+		)
+	}
+
+	/// Sets the `today` flag of `tasks` from the plan of the current day.
+	pub fn apply_my_day(&mut self, tasks: &mut [Task]) -> Result<()> {
+		let planned = self.my_day()?;
+		for task in tasks {
+			task.today = planned.contains(&task.id);
+		}
+		Ok(())
+	}
+
+	/// Adds `task` to today's plan or removes it, following its `today` flag.
+	pub fn save_my_day(&mut self, task: &Task) -> Result<()> {
+		let mut connection = self.establish_connection()?;
+		if task.today {
+			diesel::replace_into(my_day)
+				.values(QueryableMyDay {
+					id_task: task.id.clone(),
+					planned_on: local_today(),
+				})
+				.execute(&mut connection)?;
+		} else {
+			diesel::delete(my_day.filter(id_task.eq(&task.id)))
+				.execute(&mut connection)?;
+		}
+		Ok(())
+	}
+
+	pub fn remove_from_my_day(&mut self, task_id: &str) -> Result<()> {
+		diesel::delete(my_day.filter(id_task.eq(task_id)))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+
+	/// Starts a new day: plans older than yesterday are forgotten and local
+	/// tasks that weren't planned for today lose their `today` flag.
+	pub fn reset_my_day(&mut self) -> Result<()> {
+		let today = local_today();
+		let mut connection = self.establish_connection()?;
+		diesel::delete(my_day.filter(planned_on.lt(today - Duration::days(1))))
+			.execute(&mut connection)?;
+		diesel::update(
+			tasks::table.filter(tasks::today.eq(true)).filter(
+				tasks::id_task
+					.ne_all(my_day.filter(planned_on.eq(today)).select(id_task)),
+			),
+		)
+		.set(tasks::today.eq(false))
+		.execute(&mut connection)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+use crate::models::task::Task;
+use crate::schema::task_overlays::dsl::*;
+
+use super::models::task_overlay::QueryableTaskOverlay;
+use super::Database;
+
+impl Database {
+	/// Estimates stored locally for remote tasks, keyed by task id.
+	pub fn overlay_estimates(&mut self) -> Result<HashMap<String, u32>> {
+		let rows = task_overlays
+			.load::<QueryableTaskOverlay>(&mut self.establish_connection()?)?;
+		Ok(
+			rows
+				.into_iter()
+				.filter_map(|row| {
+					row.estimate.map(|minutes| (row.id_task, minutes as u32))
+				})
+				.collect(),
+		)
+	}
+
+	/// Copies the locally stored fields onto tasks read from a remote service.
+	pub fn apply_overlay(&mut self, tasks: &mut [Task]) -> Result<()> {
+		let estimates = self.overlay_estimates()?;
+		for task in tasks.iter_mut() {
+			task.estimate = estimates.get(&task.id).copied();
+		}
+		self.apply_my_day(tasks)
+	}
+
+	/// Stores the fields of `task` a remote service can't hold.
+	pub fn save_overlay(&mut self, task: &Task) -> Result<()> {
+		self.save_my_day(task)?;
+		let mut connection = self.establish_connection()?;
+		match task.estimate {
+			Some(minutes) => {
+				diesel::replace_into(task_overlays)
+					.values(QueryableTaskOverlay {
+						id_task: task.id.clone(),
+						estimate: Some(minutes as i32),
+					})
+					.execute(&mut connection)?;
+			},
+			None => {
+				diesel::delete(task_overlays.filter(id_task.eq(&task.id)))
+					.execute(&mut connection)?;
+			},
+		}
+		Ok(())
+	}
+
+	pub fn remove_overlay(&mut self, task_id: &str) -> Result<()> {
+		diesel::delete(task_overlays.filter(id_task.eq(task_id)))
+			.execute(&mut self.establish_connection()?)?;
+		self.remove_from_my_day(task_id)
+	}
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use diesel::{
+	Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+};
+
+use crate::models::time_entry::TimeEntry;
+use crate::schema::time_entries::dsl::*;
+
+use super::models::time_entry::QueryableTimeEntry;
+use super::Database;
+
+impl Database {
+	/// Returns the time entries of a task, oldest first.
+	pub fn time_entries(&mut self, task_id: &str) -> Result<Vec<TimeEntry>> {
+		let rows = time_entries
+			.filter(id_task.eq(task_id))
+			.order(start_time.asc())
+			.load::<QueryableTimeEntry>(&mut self.establish_connection()?)?;
+		Ok(rows.into_iter().map(Into::into).collect())
+	}
+
+	pub fn all_time_entries(&mut self) -> Result<Vec<TimeEntry>> {
+		let rows = time_entries
+			.order(start_time.asc())
+			.load::<QueryableTimeEntry>(&mut self.establish_connection()?)?;
+		Ok(rows.into_iter().map(Into::into).collect())
+	}
+
+	/// Total time tracked on a task, including a running timer.
+	pub fn tracked_time(&mut self, task_id: &str) -> Result<Duration> {
+		Ok(
+			self
+				.time_entries(task_id)?
+				.iter()
+				.fold(Duration::zero(), |total, entry| total + entry.duration()),
+		)
+	}
+
+	/// Time tracked on every task that has time entries, keyed by task id.
+	pub fn tracked_times(&mut self) -> Result<HashMap<String, Duration>> {
+		let mut tracked: HashMap<String, Duration> = HashMap::new();
+		for entry in self.all_time_entries()? {
+			let total = tracked.entry(entry.task_id.clone()).or_insert(Duration::zero());
+			*total += entry.duration();
+		}
+		Ok(tracked)
+	}
+
+	/// The timer currently running, if any. Only one timer runs at a time.
+	pub fn running_timer(&mut self) -> Result<Option<TimeEntry>> {
+		let row = time_entries
+			.filter(end_time.is_null())
+			.first::<QueryableTimeEntry>(&mut self.establish_connection()?)
+			.optional()?;
+		Ok(row.map(Into::into))
+	}
+
+	/// Starts a timer on a task, stopping whatever timer was running before.
+	pub fn start_timer(&mut self, task_id: &str, entry_note: &str) -> Result<TimeEntry> {
+		let entry = TimeEntry::start(task_id.to_string(), entry_note.to_string());
+		let mut connection = self.establish_connection()?;
+		connection.transaction(|connection| {
+			diesel::update(time_entries.filter(end_time.is_null()))
+				.set(end_time.eq(entry.start.naive_utc()))
+				.execute(connection)?;
+			diesel::insert_into(time_entries)
+				.values(QueryableTimeEntry::from(entry.clone()))
+				.execute(connection)?;
+			diesel::result::QueryResult::Ok(())
+		})?;
+		Ok(entry)
+	}
+
+	/// Stops the running timer of a task and returns the finished entry.
+	pub fn stop_timer(&mut self, task_id: &str) -> Result<Option<TimeEntry>> {
+		let mut connection = self.establish_connection()?;
+		let running = time_entries
+			.filter(id_task.eq(task_id))
+			.filter(end_time.is_null())
+			.first::<QueryableTimeEntry>(&mut connection)
+			.optional()?;
+		let Some(mut running) = running else {
+			return Ok(None);
+		};
+		running.end_time = Some(Utc::now().naive_utc());
+		diesel::update(time_entries.filter(id_time_entry.eq(&running.id_time_entry)))
+			.set(end_time.eq(running.end_time))
+			.execute(&mut connection)?;
+		Ok(Some(running.into()))
+	}
+
+	pub fn set_time_entry_note(&mut self, entry_id: &str, entry_note: &str) -> Result<()> {
+		diesel::update(time_entries.filter(id_time_entry.eq(entry_id)))
+			.set(note.eq(entry_note))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+
+	pub fn delete_time_entry(&mut self, entry_id: &str) -> Result<()> {
+		diesel::delete(time_entries.filter(id_time_entry.eq(entry_id)))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+
+	/// Removes the time entries of a task that lives in a remote service.
+	pub fn remove_task_time_entries(&mut self, task_id: &str) -> Result<()> {
+		diesel::delete(time_entries.filter(id_task.eq(task_id)))
+			.execute(&mut self.establish_connection()?)?;
+		Ok(())
+	}
+}
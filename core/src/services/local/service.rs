@@ -1,22 +1,35 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::pin::Pin;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
 use futures::Stream;
 use url::Url;
 
 use crate::{
-	models::{list::List, task::Task},
+	models::{
+		attachment::Attachment,
+		list::List,
+		list_group::ListGroup,
+		login::{Login, LoginPrompter},
+		task::Task,
+	},
+	schema::list_groups,
 	schema::lists::dsl::lists,
 	schema::lists::*,
+	schema::sub_tasks,
 	schema::tasks::dsl::tasks,
 	schema::tasks::*,
 	task_service::TodoProvider,
 };
 
 use super::database::{
-	models::{list::QueryableList, task::QueryableTask},
+	models::{
+		list::QueryableList, list_group::QueryableListGroup,
+		sub_task::QueryableSubTask, task::QueryableTask,
+	},
 	Database,
 };
 
@@ -27,11 +40,79 @@ pub struct ComputerStorage {
 
 impl ComputerStorage {
 	pub(crate) fn new(application_id: String) -> Self {
-		let database =
-			Database::new(application_id).expect("Failed to create database");
+		Self {
+			database: Database::new(application_id),
+		}
+	}
+
+	/// Converts database rows into tasks, quarantining any row that can't be
+	/// read instead of failing the whole request.
+	fn convert_tasks(&mut self, rows: Vec<QueryableTask>) -> Result<Vec<Task>> {
+		let mut task_list = Vec::with_capacity(rows.len());
+		for row in rows {
+			match Task::try_from(row.clone()) {
+				Ok(task) => task_list.push(task),
+				Err(err) => {
+					tracing::warn!("Skipping corrupt task {}: {err:#}", row.id_task);
+					let id = row.id_task.clone();
+					if let Err(err) = self.database.quarantine_task(row, format!("{err:#}"))
+					{
+						tracing::error!("Failed to quarantine task {id}: {err:#}");
+					}
+				},
+			}
+		}
+		self.database.apply_my_day(&mut task_list)?;
+		self.database.apply_attachments(&mut task_list)?;
+		Ok(task_list)
+	}
+
+	/// Builds the sub-task trees of `task_list` from the given rows.
+	fn attach_sub_tasks(task_list: &mut [Task], rows: Vec<QueryableSubTask>) {
+		let mut children: HashMap<String, Vec<Task>> = HashMap::new();
+		for row in rows {
+			let row_id = row.id_sub_task.clone();
+			match Task::try_from(row) {
+				Ok(sub_task) => children
+					.entry(sub_task.parent.clone())
+					.or_default()
+					.push(sub_task),
+				Err(err) => tracing::warn!("Skipping corrupt sub-task {row_id}: {err:#}"),
+			}
+		}
+		for task in task_list {
+			task.sub_tasks = take_children(&task.id, &mut children);
+		}
+	}
+
+	/// Replaces the stored sub-task tree of `task` with its current one.
+	fn save_sub_tasks(&mut self, task: &Task) -> Result<()> {
+		let rows = QueryableSubTask::from_tree(task);
+		self.database.establish_connection()?.transaction(|connection| {
+			diesel::delete(
+				sub_tasks::table.filter(sub_tasks::root_task.eq(&task.id)),
+			)
+			.execute(connection)?;
+			diesel::insert_into(sub_tasks::table)
+				.values(&rows)
+				.execute(connection)?;
+			diesel::result::QueryResult::Ok(())
+		})?;
+		Ok(())
+	}
+}
 
-		Self { database }
+/// Removes the children of `parent_id` from `children`, recursively attaching
+/// their own children. Entries are consumed so malformed cycles terminate.
+fn take_children(
+	parent_id: &str,
+	children: &mut HashMap<String, Vec<Task>>,
+) -> Vec<Task> {
+	let mut sub_tasks = children.remove(parent_id).unwrap_or_default();
+	for sub_task in &mut sub_tasks {
+		sub_task.sub_tasks = take_children(&sub_task.id, children);
 	}
+	sub_tasks
 }
 
 #[async_trait]
@@ -40,8 +121,8 @@ impl TodoProvider for ComputerStorage {
 		Ok(())
 	}
 
-	fn login(&self) -> Result<()> {
-		Ok(())
+	async fn login(&mut self, _prompt: LoginPrompter) -> Result<Login> {
+		Ok(Login::Completed)
 	}
 
 	fn logout(&self) -> Result<()> {
@@ -57,12 +138,14 @@ impl TodoProvider for ComputerStorage {
 	}
 
 	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
-		let task_list: Vec<Task> = tasks
-			.load::<QueryableTask>(&mut self.database.establish_connection()?)?
-			.iter()
-			.map(|t| t.clone().into())
-			.collect();
-
+		let mut connection = self.database.establish_connection()?;
+		let rows = tasks.load::<QueryableTask>(&mut connection)?;
+		let sub_task_rows = sub_tasks::table
+			.order(sub_tasks::position.asc())
+			.load::<QueryableSubTask>(&mut connection)?;
+
+		let mut task_list = self.convert_tasks(rows)?;
+		Self::attach_sub_tasks(&mut task_list, sub_task_rows);
 		Ok(task_list)
 	}
 
@@ -77,14 +160,21 @@ impl TodoProvider for ComputerStorage {
 		&mut self,
 		parent_list: String,
 	) -> Result<Vec<Task>> {
-		let response: Vec<Task> = tasks
-			.filter(parent.eq(parent_list))
-			.load::<QueryableTask>(&mut self.database.establish_connection()?)?
-			.iter()
-			.map(|t| t.clone().into())
-			.collect();
-
-		Ok(response)
+		let mut connection = self.database.establish_connection()?;
+		let rows = tasks
+			.filter(parent.eq(&parent_list))
+			.load::<QueryableTask>(&mut connection)?;
+		let sub_task_rows = sub_tasks::table
+			.filter(
+				sub_tasks::root_task
+					.eq_any(tasks.filter(parent.eq(&parent_list)).select(id_task)),
+			)
+			.order(sub_tasks::position.asc())
+			.load::<QueryableSubTask>(&mut connection)?;
+
+		let mut task_list = self.convert_tasks(rows)?;
+		Self::attach_sub_tasks(&mut task_list, sub_task_rows);
+		Ok(task_list)
 	}
 
 	async fn read_task(
@@ -92,22 +182,50 @@ impl TodoProvider for ComputerStorage {
 		_task_list_id: String,
 		task_id: String,
 	) -> Result<Task> {
+		let mut connection = self.database.establish_connection()?;
 		let task: QueryableTask = tasks
-			.find(task_id)
-			.first(&mut self.database.establish_connection()?)
+			.find(&task_id)
+			.first(&mut connection)
 			.context("Failed to fetch list of tasks.")?;
-
-		Ok(task.into())
+		let sub_task_rows = sub_tasks::table
+			.filter(sub_tasks::root_task.eq(&task_id))
+			.order(sub_tasks::position.asc())
+			.load::<QueryableSubTask>(&mut connection)?;
+
+		match Task::try_from(task.clone()) {
+			Ok(task) => {
+				let mut task_list = [task];
+				self.database.apply_my_day(&mut task_list)?;
+				self.database.apply_attachments(&mut task_list)?;
+				Self::attach_sub_tasks(&mut task_list, sub_task_rows);
+				let [task] = task_list;
+				Ok(task)
+			},
+			Err(err) => {
+				let id = task.id_task.clone();
+				if let Err(err) = self.database.quarantine_task(task, format!("{err:#}"))
+				{
+					tracing::error!("Failed to quarantine task {id}: {err:#}");
+				}
+				Err(err)
+			},
+		}
 	}
 
-	async fn create_task(&mut self, task: Task) -> Result<()> {
-		let queryable_task: QueryableTask = task.into();
+	async fn create_task(&mut self, task: Task) -> Result<Task> {
+		let list = self.read_list(task.parent.clone()).await;
+		if list.is_ok_and(|list| list.read_only()) {
+			bail!("Tasks can't be added to this list")
+		}
+		let queryable_task: QueryableTask = task.clone().into();
 
 		diesel::insert_into(tasks)
 			.values(&queryable_task)
 			.execute(&mut self.database.establish_connection()?)?;
 
-		Ok(())
+		self.database.save_my_day(&task)?;
+		self.save_sub_tasks(&task)?;
+		Ok(task)
 	}
 
 	async fn update_task(&mut self, task: Task) -> Result<Task> {
@@ -123,7 +241,6 @@ impl TodoProvider for ComputerStorage {
 				today.eq(queryable_task.today),
 				status.eq(queryable_task.status),
 				priority.eq(queryable_task.priority),
-				sub_tasks.eq(queryable_task.sub_tasks),
 				tags.eq(queryable_task.tags),
 				notes.eq(queryable_task.notes),
 				completion_date.eq(queryable_task.completion_date),
@@ -133,9 +250,17 @@ impl TodoProvider for ComputerStorage {
 				recurrence.eq(queryable_task.recurrence),
 				created_date_time.eq(queryable_task.created_date_time),
 				last_modified_date_time.eq(queryable_task.last_modified_date_time),
+				estimate.eq(queryable_task.estimate),
+				start_date.eq(queryable_task.start_date),
+				linked_resources.eq(queryable_task.linked_resources),
 			))
 			.execute(&mut self.database.establish_connection()?)
 			.context("Failed to update task.")?;
+		self.database.save_my_day(&original_task)?;
+
+		self
+			.save_sub_tasks(&original_task)
+			.context("Failed to update sub-tasks.")?;
 
 		Ok(original_task)
 	}
@@ -145,8 +270,12 @@ impl TodoProvider for ComputerStorage {
 		_list_id: String,
 		task_id: String,
 	) -> Result<()> {
-		diesel::delete(tasks.filter(id_task.eq(task_id)))
+		diesel::delete(tasks.filter(id_task.eq(&task_id)))
 			.execute(&mut self.database.establish_connection()?)?;
+		self.database.remove_from_my_day(&task_id)?;
+		self.database.remove_task_attachments(&task_id)?;
+		self.database.remove_task_dependencies(&task_id)?;
+		self.database.remove_task_time_entries(&task_id)?;
 
 		Ok(())
 	}
@@ -186,7 +315,11 @@ impl TodoProvider for ComputerStorage {
 		let list: QueryableList = list.into();
 
 		diesel::update(lists.filter(id_list.eq(list.id_list.clone())))
-			.set((name.eq(list.name.clone()), icon_name.eq(list.icon_name)))
+			.set((
+				name.eq(list.name.clone()),
+				icon_name.eq(list.icon_name),
+				id_group.eq(list.id_group),
+			))
 			.execute(&mut self.database.establish_connection()?)
 			.context("Failed to update list.")?;
 
@@ -194,8 +327,87 @@ impl TodoProvider for ComputerStorage {
 	}
 
 	async fn delete_list(&mut self, id: String) -> Result<()> {
+		let list = self.read_list(id.clone()).await;
+		if list.is_ok_and(|list| !list.can_delete()) {
+			bail!("This list can't be deleted")
+		}
 		diesel::delete(lists.filter(id_list.eq(id)))
 			.execute(&mut self.database.establish_connection()?)?;
 		Ok(())
 	}
+
+	fn list_groups_support(&self) -> bool {
+		true
+	}
+
+	async fn read_list_groups(&mut self) -> Result<Vec<ListGroup>> {
+		let results = list_groups::table
+			.order((list_groups::position, list_groups::name))
+			.load::<QueryableListGroup>(&mut self.database.establish_connection()?)?;
+		Ok(results.into_iter().map(Into::into).collect())
+	}
+
+	async fn create_list_group(&mut self, group: ListGroup) -> Result<ListGroup> {
+		let group: QueryableListGroup = group.into();
+		diesel::insert_into(list_groups::table)
+			.values(&group)
+			.execute(&mut self.database.establish_connection()?)?;
+		Ok(group.into())
+	}
+
+	async fn update_list_group(&mut self, group: ListGroup) -> Result<()> {
+		diesel::update(list_groups::table.find(&group.id))
+			.set((
+				list_groups::name.eq(group.name),
+				list_groups::position.eq(group.position),
+				list_groups::expanded.eq(group.expanded),
+			))
+			.execute(&mut self.database.establish_connection()?)
+			.context("Failed to update list group.")?;
+		Ok(())
+	}
+
+	async fn delete_list_group(&mut self, id: String) -> Result<()> {
+		self.database.establish_connection()?.transaction(|connection| {
+			diesel::update(lists.filter(id_group.eq(&id)))
+				.set(id_group.eq(None::<String>))
+				.execute(connection)?;
+			diesel::delete(list_groups::table.find(&id)).execute(connection)?;
+			diesel::QueryResult::Ok(())
+		})?;
+		Ok(())
+	}
+
+	async fn read_attachments(
+		&mut self,
+		_task_list_id: String,
+		task_id: String,
+	) -> Result<Vec<Attachment>> {
+		self.database.attachments(&task_id)
+	}
+
+	async fn add_attachment(
+		&mut self,
+		_task_list_id: String,
+		task_id: String,
+		path: PathBuf,
+	) -> Result<Attachment> {
+		self.database.add_attachment(&task_id, &path)
+	}
+
+	async fn open_attachment(
+		&mut self,
+		_task_list_id: String,
+		attachment: Attachment,
+	) -> Result<PathBuf> {
+		self.database.attachment_path(&attachment)
+	}
+
+	async fn remove_attachment(
+		&mut self,
+		_task_list_id: String,
+		attachment: Attachment,
+	) -> Result<()> {
+		self.database.remove_attachment(&attachment)
+	}
 }
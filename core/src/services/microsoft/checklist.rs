@@ -0,0 +1,415 @@
+use anyhow::{bail, Result};
+use graph_rs_sdk::Graph;
+
+use super::models::checklist_item::{ChecklistChanges, ChecklistItem};
+use super::models::collection::Collection;
+use super::requests::{
+	create_with_retry, send_batch, send_with_retry, BatchRequest,
+};
+
+/// Creates, updates, reorders and deletes the checklist items of a task so
+/// they match `checklist_items`, returns the items as they are on the
+/// server. Creations go one by one since Graph orders items by when they were
+/// created, deletions and updates follow in JSON batches.
+///
+/// New items are created before anything is deleted, so a failure never loses
+/// an item that was on the server. Any failure is returned, which lets the
+/// caller keep its local copy and try again.
+pub(crate) async fn sync_checklist_items(
+	client: &Graph,
+	todo_task_list_id: &str,
+	todo_task_id: &str,
+	checklist_items: &[ChecklistItem],
+) -> Result<Vec<ChecklistItem>> {
+	let response = send_with_retry(|| {
+		client
+			.me()
+			.todo()
+			.list(todo_task_list_id)
+			.task(todo_task_id)
+			.list_checklist_items()
+			.send()
+	})
+	.await?;
+	let remote: Collection<ChecklistItem> =
+		response.error_for_status()?.json().await?;
+	let changes = ChecklistChanges::between(checklist_items, &remote.value);
+
+	let mut created = vec![];
+	for item in &changes.created {
+		match create_checklist_item(client, todo_task_list_id, todo_task_id, item)
+			.await
+		{
+			Ok(item) => created.push(item),
+			Err(err) => {
+				// Leave the server as it was, the items being replaced are
+				// still there.
+				let ids = created.iter().map(|item: &ChecklistItem| item.id.clone());
+				delete_checklist_items(client, todo_task_list_id, todo_task_id, ids)
+					.await
+					.unwrap_or_else(|err| tracing::error!("{err:#}"));
+				return Err(err.context("Failed to add a check list item"));
+			},
+		}
+	}
+
+	let url = |id: &str| {
+		format!(
+			"/me/todo/lists/{todo_task_list_id}/tasks/{todo_task_id}/checklistItems/{id}"
+		)
+	};
+	let updates: Vec<BatchRequest> = changes
+		.kept
+		.iter()
+		.filter(|(_, changed)| *changed)
+		.map(|(item, _)| {
+			BatchRequest::new(format!("update-{}", item.id), "PATCH", url(&item.id))
+				.json(serde_json::json!(item))
+		})
+		.collect();
+	let updated = send_checklist_batch(client, updates).await;
+	let deleted = delete_checklist_items(
+		client,
+		todo_task_list_id,
+		todo_task_id,
+		changes.deleted.iter().cloned(),
+	)
+	.await;
+	updated.and(deleted)?;
+
+	Ok(
+		changes
+			.kept
+			.into_iter()
+			.map(|(item, _)| item)
+			.chain(created)
+			.collect(),
+	)
+}
+
+async fn create_checklist_item(
+	client: &Graph,
+	todo_task_list_id: &str,
+	todo_task_id: &str,
+	item: &ChecklistItem,
+) -> Result<ChecklistItem> {
+	let response = create_with_retry(|| {
+		client
+			.me()
+			.todo()
+			.list(todo_task_list_id)
+			.task(todo_task_id)
+			.create_checklist_items(&serde_json::json!(item))
+			.send()
+	})
+	.await?;
+	Ok(response.error_for_status()?.json().await?)
+}
+
+async fn delete_checklist_items(
+	client: &Graph,
+	todo_task_list_id: &str,
+	todo_task_id: &str,
+	ids: impl Iterator<Item = String>,
+) -> Result<()> {
+	let requests = ids
+		.map(|id| {
+			BatchRequest::new(
+				format!("delete-{id}"),
+				"DELETE",
+				format!(
+					"/me/todo/lists/{todo_task_list_id}/tasks/{todo_task_id}/checklistItems/{id}"
+				),
+			)
+		})
+		.collect();
+	send_checklist_batch(client, requests).await
+}
+
+/// Sends the requests in batches, fails if any of them failed.
+async fn send_checklist_batch(
+	client: &Graph,
+	requests: Vec<BatchRequest>,
+) -> Result<()> {
+	if requests.is_empty() {
+		return Ok(());
+	}
+	let failed: Vec<String> = send_batch(client, requests)
+		.await?
+		.into_iter()
+		.filter(|response| !response.is_success())
+		.map(|response| {
+			format!("{} ({}): {:?}", response.id, response.status, response.body)
+		})
+		.collect();
+	if !failed.is_empty() {
+		bail!("Failed to sync check list items: {}", failed.join(", "))
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Mutex};
+
+	use super::*;
+	use crate::services::microsoft::mock::{MockRequest, MockResponse, MockServer};
+
+	const ITEMS: &str = "/me/todo/lists/list/tasks/task/checklistItems";
+
+	fn item(id: &str, name: &str, checked: bool) -> ChecklistItem {
+		ChecklistItem {
+			id: id.to_string(),
+			display_name: name.to_string(),
+			is_checked: checked,
+			..Default::default()
+		}
+	}
+
+	/// Request bodies leave out the id, which Graph assigns.
+	fn from_json(id: &str, body: &serde_json::Value) -> ChecklistItem {
+		let mut body = body.clone();
+		body["id"] = serde_json::json!(id);
+		serde_json::from_value(body).unwrap()
+	}
+
+	fn to_json(item: &ChecklistItem) -> serde_json::Value {
+		let mut json = serde_json::json!(item);
+		json["id"] = serde_json::json!(item.id);
+		json
+	}
+
+	/// Applies a request to the checklist of a task, the way Graph would.
+	fn apply(
+		items: &mut Vec<ChecklistItem>,
+		created: &mut usize,
+		method: &str,
+		path: &str,
+		body: &serde_json::Value,
+	) -> MockResponse {
+		let id = path.strip_prefix(ITEMS).unwrap_or_default().trim_start_matches('/');
+		match (method, id) {
+			("GET", "") => {
+				let value: Vec<_> = items.iter().map(to_json).collect();
+				MockResponse::json(200, serde_json::json!({ "value": value }))
+			},
+			("POST", "") => {
+				*created += 1;
+				let new = from_json(&format!("server-{created}"), body);
+				items.push(new.clone());
+				MockResponse::json(201, to_json(&new))
+			},
+			("PATCH", id) => match items.iter_mut().find(|item| item.id == id) {
+				Some(item) => {
+					let patch = from_json(id, body);
+					item.display_name = patch.display_name;
+					item.is_checked = patch.is_checked;
+					MockResponse::json(200, to_json(item))
+				},
+				None => MockResponse::new(404),
+			},
+			("DELETE", id) => {
+				items.retain(|item| item.id != id);
+				MockResponse::new(204)
+			},
+			_ => MockResponse::new(400),
+		}
+	}
+
+	/// A Graph stand-in holding the checklist of one task.
+	async fn fake_graph(
+		initial: Vec<ChecklistItem>,
+	) -> (MockServer, Arc<Mutex<Vec<ChecklistItem>>>) {
+		failing_graph(initial, |_| false).await
+	}
+
+	/// Like [`fake_graph`], answers 400 to the requests using a `failing`
+	/// method.
+	async fn failing_graph(
+		initial: Vec<ChecklistItem>,
+		failing: fn(&str) -> bool,
+	) -> (MockServer, Arc<Mutex<Vec<ChecklistItem>>>) {
+		let apply = move |items: &mut Vec<ChecklistItem>,
+		                  created: &mut usize,
+		                  method: &str,
+		                  path: &str,
+		                  body: &serde_json::Value| {
+			if failing(method) {
+				MockResponse::new(400)
+			} else {
+				apply(items, created, method, path, body)
+			}
+		};
+		let items = Arc::new(Mutex::new(initial));
+		let state = items.clone();
+		let created = Mutex::new(0);
+		let server = MockServer::start(move |request: &MockRequest| {
+			let mut items = state.lock().unwrap();
+			let mut created = created.lock().unwrap();
+			if request.path == "/$batch" {
+				let responses: Vec<_> = request.json()["requests"]
+					.as_array()
+					.unwrap()
+					.iter()
+					.map(|inner| {
+						let response = apply(
+							&mut items,
+							&mut created,
+							inner["method"].as_str().unwrap(),
+							inner["url"].as_str().unwrap(),
+							&inner["body"],
+						);
+						serde_json::json!({ "id": inner["id"], "status": response.status() })
+					})
+					.collect();
+				MockResponse::json(200, serde_json::json!({ "responses": responses }))
+			} else {
+				apply(
+					&mut items,
+					&mut created,
+					&request.method,
+					&request.path,
+					&request.json(),
+				)
+			}
+		})
+		.await;
+		(server, items)
+	}
+
+	fn names(items: &[ChecklistItem]) -> Vec<&str> {
+		items.iter().map(|item| item.display_name.as_str()).collect()
+	}
+
+	#[tokio::test]
+	async fn creates_items_added_locally() {
+		let (server, items) = fake_graph(vec![item("server-a", "A", false)]).await;
+		let local = vec![item("server-a", "A", false), item("local-b", "B", false)];
+
+		let synced = sync_checklist_items(&server.graph(), "list", "task", &local)
+			.await
+			.unwrap();
+
+		assert_eq!(names(&items.lock().unwrap()), ["A", "B"]);
+		assert_eq!(synced[1].id, "server-1");
+		let methods: Vec<_> = server.requests().iter().map(|r| r.method.clone()).collect();
+		assert_eq!(methods, ["GET", "POST"]);
+	}
+
+	#[tokio::test]
+	async fn updates_changed_items() {
+		let (server, items) = fake_graph(vec![
+			item("server-a", "A", false),
+			item("server-b", "B", false),
+		])
+		.await;
+		let local = vec![item("server-a", "A", true), item("server-b", "Bee", false)];
+
+		let synced = sync_checklist_items(&server.graph(), "list", "task", &local)
+			.await
+			.unwrap();
+
+		let items = items.lock().unwrap();
+		assert_eq!(names(&items), ["A", "Bee"]);
+		assert!(items[0].is_checked);
+		assert_eq!(synced.len(), 2);
+		let batch = &server.requests()[1];
+		assert_eq!(batch.path, "/$batch");
+		assert_eq!(batch.json()["requests"].as_array().unwrap().len(), 2);
+	}
+
+	#[tokio::test]
+	async fn deletes_items_removed_locally() {
+		let (server, items) = fake_graph(vec![
+			item("server-a", "A", false),
+			item("server-b", "B", false),
+		])
+		.await;
+		let local = vec![item("server-a", "A", false)];
+
+		let synced = sync_checklist_items(&server.graph(), "list", "task", &local)
+			.await
+			.unwrap();
+
+		assert_eq!(names(&items.lock().unwrap()), ["A"]);
+		assert_eq!(names(&synced), ["A"]);
+	}
+
+	#[tokio::test]
+	async fn recreates_moved_items_in_order() {
+		let (server, items) = fake_graph(vec![
+			item("server-a", "A", false),
+			item("server-b", "B", false),
+			item("server-c", "C", true),
+		])
+		.await;
+		let local = vec![
+			item("server-a", "A", false),
+			item("server-c", "C", true),
+			item("server-b", "B", false),
+		];
+
+		let synced = sync_checklist_items(&server.graph(), "list", "task", &local)
+			.await
+			.unwrap();
+
+		let items = items.lock().unwrap();
+		assert_eq!(names(&items), ["A", "C", "B"]);
+		assert!(items[1].is_checked);
+		assert_eq!(names(&synced), ["A", "C", "B"]);
+		assert_eq!(synced[0].id, "server-a");
+	}
+
+	#[tokio::test]
+	async fn leaves_matching_items_alone() {
+		let (server, _) = fake_graph(vec![item("server-a", "A", false)]).await;
+		let local = vec![item("server-a", "A", false)];
+
+		sync_checklist_items(&server.graph(), "list", "task", &local)
+			.await
+			.unwrap();
+
+		assert_eq!(server.requests().len(), 1);
+	}
+
+	#[tokio::test]
+	async fn keeps_remote_items_when_a_creation_fails() {
+		let (server, items) = failing_graph(
+			vec![
+				item("server-a", "A", false),
+				item("server-b", "B", false),
+				item("server-c", "C", false),
+			],
+			|method| method == "POST",
+		)
+		.await;
+		let local = vec![
+			item("server-a", "A", false),
+			item("server-c", "C", false),
+			item("server-b", "B", false),
+		];
+
+		let result =
+			sync_checklist_items(&server.graph(), "list", "task", &local).await;
+
+		assert!(result.is_err());
+		assert_eq!(names(&items.lock().unwrap()), ["A", "B", "C"]);
+		assert!(server.requests().iter().all(|r| r.path != "/$batch"));
+	}
+
+	#[tokio::test]
+	async fn fails_when_a_deletion_fails() {
+		let (server, items) = failing_graph(
+			vec![item("server-a", "A", false), item("server-b", "B", false)],
+			|method| method == "DELETE",
+		)
+		.await;
+		let local = vec![item("server-a", "A", false)];
+
+		let result =
+			sync_checklist_items(&server.graph(), "list", "task", &local).await;
+
+		assert!(result.is_err());
+		assert_eq!(names(&items.lock().unwrap()), ["A", "B"]);
+	}
+}
@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use graph_rs_sdk::oauth::AccessToken;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::models::login::{LoginPrompt, LoginPrompter};
+
+use super::oauth::{OAuthError, OAuthSettings};
+
+/// How long the loopback listener waits for the user to sign in.
+const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const LOOPBACK_PAGE: &str = "<!DOCTYPE html><html><body>\
+	<p>You can close this window and go back to Done.</p></body></html>";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Whether an installed desktop file handles `done://` links.
+pub(crate) fn uri_scheme_registered(application_id: &str) -> bool {
+	let system_dirs = std::env::var("XDG_DATA_DIRS")
+		.unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+	let mut data_dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+	data_dirs.extend(std::env::split_paths(&system_dirs));
+	data_dirs.iter().any(|dir| {
+		let desktop = dir
+			.join("applications")
+			.join(format!("{application_id}.desktop"));
+		std::fs::read_to_string(desktop)
+			.is_ok_and(|desktop| desktop.contains("x-scheme-handler/done"))
+	})
+}
+
+/// Waits on 127.0.0.1 for the browser to bring back the authorization code.
+pub(crate) struct LoopbackListener {
+	listener: TcpListener,
+}
+
+impl LoopbackListener {
+	/// Listens on a free port, the redirect URI has to be the one of the
+	/// app registration but Microsoft ignores the port of loopback URIs.
+	pub async fn bind() -> Result<Self> {
+		let listener = TcpListener::bind(("127.0.0.1", 0))
+			.await
+			.context("Failed to listen on 127.0.0.1")?;
+		Ok(Self { listener })
+	}
+
+	pub fn redirect_uri(&self) -> Result<String> {
+		Ok(format!("http://127.0.0.1:{}", self.listener.local_addr()?.port()))
+	}
+
+	/// Returns the URL the browser was redirected to, skipping any other
+	/// request it makes, like the one for the favicon.
+	pub async fn redirect(self) -> Result<Url> {
+		tokio::time::timeout(LOOPBACK_TIMEOUT, self.accept())
+			.await
+			.context("Timed out waiting for the browser to sign in")?
+	}
+
+	async fn accept(&self) -> Result<Url> {
+		loop {
+			let (mut stream, _) = self.listener.accept().await?;
+			let mut request_line = String::new();
+			BufReader::new(&mut stream)
+				.read_line(&mut request_line)
+				.await?;
+			let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+			let url = Url::parse(&format!("http://127.0.0.1{path}"))?;
+			let is_redirect = url
+				.query_pairs()
+				.any(|(key, _)| key == "code" || key == "error");
+			let response = if is_redirect {
+				format!(
+					"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+					Content-Length: {}\r\nConnection: close\r\n\r\n{LOOPBACK_PAGE}",
+					LOOPBACK_PAGE.len()
+				)
+			} else {
+				"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+					.to_string()
+			};
+			stream.write_all(response.as_bytes()).await?;
+			if is_redirect {
+				return Ok(url);
+			}
+		}
+	}
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+	device_code: String,
+	user_code: String,
+	verification_uri: String,
+	expires_in: u64,
+	#[serde(default = "default_interval")]
+	interval: u64,
+	message: String,
+}
+
+fn default_interval() -> u64 {
+	5
+}
+
+/// Signs in with a code the user enters at Microsoft's site, which works
+/// without a browser on this device.
+pub(crate) async fn device_code_token(
+	settings: &OAuthSettings,
+	prompt: &LoginPrompter,
+) -> Result<AccessToken> {
+	let client = reqwest::Client::new();
+	let client_id = settings.client_id.trim();
+	let scopes = settings.scopes().join(" ");
+	let response = client
+		.post(settings.device_code_url())
+		.form(&[("client_id", client_id), ("scope", scopes.as_str())])
+		.send()
+		.await?;
+	if !response.status().is_success() {
+		let error: OAuthError = response.json().await?;
+		bail!("Failed to request a device code: {error}")
+	}
+	let authorization: DeviceAuthorization = response.json().await?;
+	prompt(LoginPrompt::DeviceCode {
+		user_code: authorization.user_code,
+		verification_uri: authorization.verification_uri,
+		message: authorization.message,
+	});
+
+	let deadline = Instant::now() + Duration::from_secs(authorization.expires_in);
+	let mut interval = Duration::from_secs(authorization.interval.max(1));
+	while Instant::now() < deadline {
+		tokio::time::sleep(interval).await;
+		let response = client
+			.post(settings.token_url())
+			.form(&[
+				("grant_type", DEVICE_CODE_GRANT),
+				("client_id", client_id),
+				("device_code", authorization.device_code.as_str()),
+			])
+			.send()
+			.await?;
+		if response.status().is_success() {
+			return Ok(response.json().await?);
+		}
+		let error: OAuthError = response.json().await?;
+		match error.error.as_str() {
+			"authorization_pending" => {},
+			"slow_down" => interval += Duration::from_secs(5),
+			_ => bail!("Signing in with a device code failed: {error}"),
+		}
+	}
+	bail!("The device code expired before the sign-in finished")
+}
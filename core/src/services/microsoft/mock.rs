@@ -0,0 +1,164 @@
+//! A stand-in for Graph in tests, answering requests with the responses a
+//! closure picks for them.
+
+use std::sync::{Arc, Mutex};
+
+use graph_rs_sdk::{Graph, GraphClientConfiguration};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockRequest {
+	pub method: String,
+	/// Path and query, without the Graph version.
+	pub path: String,
+	pub body: String,
+}
+
+impl MockRequest {
+	pub fn json(&self) -> serde_json::Value {
+		serde_json::from_str(&self.body).unwrap_or_default()
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+	status: u16,
+	headers: Vec<(String, String)>,
+	body: String,
+}
+
+impl MockResponse {
+	pub fn new(status: u16) -> Self {
+		Self {
+			status,
+			headers: vec![],
+			body: String::new(),
+		}
+	}
+
+	pub fn json(status: u16, body: serde_json::Value) -> Self {
+		Self {
+			body: body.to_string(),
+			..Self::new(status)
+		}
+		.header("Content-Type", "application/json")
+	}
+
+	pub fn status(&self) -> u16 {
+		self.status
+	}
+
+	pub fn header(mut self, name: &str, value: &str) -> Self {
+		self.headers.push((name.to_string(), value.to_string()));
+		self
+	}
+}
+
+type Responder = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+pub(crate) struct MockServer {
+	url: String,
+	requests: Arc<Mutex<Vec<MockRequest>>>,
+	handle: JoinHandle<()>,
+}
+
+impl MockServer {
+	pub async fn start(
+		respond: impl Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+	) -> Self {
+		let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		let requests = Arc::new(Mutex::new(vec![]));
+		let respond: Arc<Responder> = Arc::new(respond);
+		let recorded = requests.clone();
+		let handle = tokio::spawn(async move {
+			while let Ok((stream, _)) = listener.accept().await {
+				let Some((request, mut stream)) = read_request(stream).await else {
+					continue;
+				};
+				recorded.lock().unwrap().push(request.clone());
+				let response = respond(&request);
+				let _ = stream.write_all(&encode(&response)).await;
+			}
+		});
+		Self {
+			url,
+			requests,
+			handle,
+		}
+	}
+
+	pub fn url(&self) -> &str {
+		&self.url
+	}
+
+	/// A client that sends its requests to this server.
+	pub fn graph(&self) -> Graph {
+		let mut client = Graph::from(
+			GraphClientConfiguration::new()
+				.access_token("token")
+				.https_only(false),
+		);
+		client.use_endpoint(&format!("{}/v1.0", self.url));
+		client
+	}
+
+	pub fn requests(&self) -> Vec<MockRequest> {
+		self.requests.lock().unwrap().clone()
+	}
+}
+
+impl Drop for MockServer {
+	fn drop(&mut self) {
+		self.handle.abort();
+	}
+}
+
+async fn read_request(
+	stream: tokio::net::TcpStream,
+) -> Option<(MockRequest, tokio::net::TcpStream)> {
+	let mut reader = BufReader::new(stream);
+	let mut request_line = String::new();
+	reader.read_line(&mut request_line).await.ok()?;
+	let mut parts = request_line.split_whitespace();
+	let method = parts.next()?.to_string();
+	let path = parts.next()?.to_string();
+	let mut content_length = 0;
+	loop {
+		let mut line = String::new();
+		reader.read_line(&mut line).await.ok()?;
+		let line = line.trim_end();
+		if line.is_empty() {
+			break;
+		}
+		if let Some((name, value)) = line.split_once(':') {
+			if name.eq_ignore_ascii_case("content-length") {
+				content_length = value.trim().parse().unwrap_or(0);
+			}
+		}
+	}
+	let mut body = vec![0; content_length];
+	reader.read_exact(&mut body).await.ok()?;
+	let request = MockRequest {
+		method,
+		path: path.trim_start_matches("/v1.0").to_string(),
+		body: String::from_utf8_lossy(&body).to_string(),
+	};
+	Some((request, reader.into_inner()))
+}
+
+fn encode(response: &MockResponse) -> Vec<u8> {
+	let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+	for (name, value) in &response.headers {
+		head.push_str(&format!("{name}: {value}\r\n"));
+	}
+	head.push_str(&format!(
+		"Content-Length: {}\r\nConnection: close\r\n\r\n",
+		response.body.len()
+	));
+	let mut bytes = head.into_bytes();
+	bytes.extend_from_slice(response.body.as_bytes());
+	bytes
+}
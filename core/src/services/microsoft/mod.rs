@@ -1,2 +1,9 @@
+pub(crate) mod checklist;
+pub(crate) mod login;
+#[cfg(test)]
+pub(crate) mod mock;
 pub(crate) mod models;
+pub mod oauth;
+pub(crate) mod requests;
 pub(crate) mod service;
+pub(crate) mod tokens;
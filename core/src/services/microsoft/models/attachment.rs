@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::attachment::Attachment;
+
+/// A `taskFileAttachment`, `content_bytes` is only set when uploading.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFileAttachment {
+	#[serde(rename = "@odata.type")]
+	pub odata_type: String,
+	#[serde(skip_serializing)]
+	pub id: String,
+	pub name: String,
+	pub content_type: String,
+	#[serde(skip_serializing)]
+	pub size: u64,
+	#[serde(skip_serializing)]
+	pub last_modified_date_time: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub content_bytes: Option<String>,
+}
+
+/// Describes a file too large to be sent in a single request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInfo {
+	pub attachment_type: String,
+	pub name: String,
+	pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadSession {
+	pub upload_url: String,
+	#[serde(default)]
+	pub next_expected_ranges: Vec<String>,
+}
+
+impl TaskFileAttachment {
+	pub const ODATA_TYPE: &'static str = "#microsoft.graph.taskFileAttachment";
+
+	pub fn into_attachment(self, task_id: &str) -> anyhow::Result<Attachment> {
+		let created_date_time = match &self.last_modified_date_time {
+			Some(date) => DateTime::<Utc>::from_str(date)
+				.with_context(|| format!("Invalid attachment date: {date}"))?,
+			None => Utc::now(),
+		};
+		Ok(Attachment {
+			id: self.id,
+			task_id: task_id.to_string(),
+			name: self.name,
+			content_type: self.content_type,
+			size: self.size,
+			created_date_time,
+		})
+	}
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::notes::html_to_markdown;
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ItemBody {
@@ -7,6 +9,16 @@ pub struct ItemBody {
 	pub content_type: BodyType,
 }
 
+impl ItemBody {
+	/// The body as the Markdown notes of a task.
+	pub fn to_markdown(&self) -> String {
+		match self.content_type {
+			BodyType::Html => html_to_markdown(&self.content),
+			BodyType::Text => self.content.clone(),
+		}
+	}
+}
+
 #[derive(
 	Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord,
 )]
@@ -11,3 +11,49 @@ pub struct ChecklistItem {
 	pub created_date_time: Option<String>,
 	pub checked_date_time: Option<String>,
 }
+
+/// What has to change on the server for the checklist of a task to match the
+/// local sub-tasks.
+#[derive(Debug, Default)]
+pub struct ChecklistChanges {
+	/// Items kept in place, in order, along with whether they changed.
+	pub kept: Vec<(ChecklistItem, bool)>,
+	/// Ids of the items to delete, removed ones and the ones recreated.
+	pub deleted: Vec<String>,
+	/// Items to create in this order after the kept ones.
+	pub created: Vec<ChecklistItem>,
+}
+
+impl ChecklistChanges {
+	/// Compares the `local` items with the `remote` ones, both in display
+	/// order. Graph keeps items in the order they were created and has no
+	/// way to move them, so everything after the first item out of place is
+	/// deleted and created again.
+	pub fn between(local: &[ChecklistItem], remote: &[ChecklistItem]) -> Self {
+		let (remaining, removed): (Vec<&ChecklistItem>, Vec<&ChecklistItem>) =
+			remote
+				.iter()
+				.partition(|item| local.iter().any(|local| local.id == item.id));
+		let mut changes = Self {
+			deleted: removed.into_iter().map(|item| item.id.clone()).collect(),
+			..Default::default()
+		};
+		let mut remaining = remaining.into_iter().peekable();
+		for item in local {
+			match remaining.peek() {
+				Some(remote) if changes.created.is_empty() && remote.id == item.id => {
+					let changed = remote.display_name != item.display_name
+						|| remote.is_checked != item.is_checked;
+					changes.kept.push((item.clone(), changed));
+					remaining.next();
+				},
+				_ => changes.created.push(item.clone()),
+			}
+		}
+		// Whatever is left was moved and is created again.
+		changes
+			.deleted
+			.extend(remaining.map(|item| item.id.clone()));
+		changes
+	}
+}
@@ -1,6 +1,9 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::dates::{all_day, parse_time_zone};
+
 #[derive(
 	Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord,
 )]
@@ -10,16 +13,56 @@ pub struct DateTimeTimeZone {
 	pub time_zone: String,
 }
 
-impl From<DateTimeTimeZone> for DateTime<Utc> {
-	fn from(date: DateTimeTimeZone) -> Self {
+impl DateTimeTimeZone {
+	/// Graph sends all-day dates as midnight of the zone they were written
+	/// in, so the calendar day is the date part regardless of the zone.
+	pub fn date(&self) -> anyhow::Result<NaiveDate> {
+		let date = self.date_time.get(..10).unwrap_or(&self.date_time);
+		NaiveDate::parse_from_str(date, "%Y-%m-%d").with_context(|| {
+			format!("Failed to parse date string: {}", self.date_time)
+		})
+	}
+
+	/// An all-day date, written as midnight UTC of that day.
+	pub fn all_day(date: NaiveDate) -> Self {
+		all_day(date).into()
+	}
+}
+
+impl TryFrom<DateTimeTimeZone> for DateTime<Utc> {
+	type Error = anyhow::Error;
+
+	fn try_from(date: DateTimeTimeZone) -> Result<Self, Self::Error> {
 		let datetime =
 			NaiveDateTime::parse_from_str(&date.date_time, "%Y-%m-%dT%H:%M:%S%.f")
 				.or_else(|_| {
 					NaiveDateTime::parse_from_str(&date.date_time, "%Y-%m-%dT%H:%M:%S")
 				})
-				.expect("Failed to parse date string");
+				.with_context(|| {
+					format!("Failed to parse date string: {}", date.date_time)
+				})?;
 
-		DateTime::<Utc>::from_naive_utc_and_offset(datetime, Utc)
+		match parse_time_zone(&date.time_zone) {
+			Some(zone) => zone
+				.from_local_datetime(&datetime)
+				.earliest()
+				.map(|date| date.with_timezone(&Utc))
+				.with_context(|| {
+					format!(
+						"{} does not exist in {}",
+						date.date_time, date.time_zone
+					)
+				}),
+			None => {
+				if !date.time_zone.is_empty() {
+					tracing::warn!(
+						"Unknown time zone {}, assuming UTC.",
+						date.time_zone
+					);
+				}
+				Ok(DateTime::<Utc>::from_naive_utc_and_offset(datetime, Utc))
+			},
+		}
 	}
 }
 
@@ -27,7 +70,7 @@ impl From<DateTime<Utc>> for DateTimeTimeZone {
 	fn from(date: DateTime<Utc>) -> Self {
 		Self {
 			date_time: date.format("%Y-%m-%dT%H:%M:%S").to_string(),
-			time_zone: date.timezone().to_string(),
+			time_zone: "UTC".to_string(),
 		}
 	}
 }
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::linked_resource::{display_name, LinkedResource};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoLinkedResource {
+	#[serde(skip_serializing)]
+	pub id: String,
+	pub web_url: Option<String>,
+	pub application_name: Option<String>,
+	pub display_name: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub external_id: Option<String>,
+}
+
+impl From<TodoLinkedResource> for LinkedResource {
+	fn from(value: TodoLinkedResource) -> Self {
+		let web_url = value.web_url.unwrap_or_default();
+		Self {
+			id: value.id,
+			display_name: value
+				.display_name
+				.filter(|name| !name.is_empty())
+				.unwrap_or_else(|| display_name(&web_url)),
+			application_name: value.application_name.unwrap_or_default(),
+			web_url,
+		}
+	}
+}
+
+impl From<LinkedResource> for TodoLinkedResource {
+	fn from(value: LinkedResource) -> Self {
+		Self {
+			id: value.id,
+			web_url: Some(value.web_url),
+			application_name: Some(value.application_name),
+			display_name: Some(value.display_name),
+			external_id: None,
+		}
+	}
+}
@@ -1,10 +1,13 @@
+pub mod attachment;
 pub mod body;
 pub mod checklist_item;
 pub mod collection;
 pub mod date_time_zone;
 pub mod importance;
+pub mod linked_resource;
 pub mod list;
 pub mod recurrence;
 pub mod status;
 pub mod task;
 pub mod token;
+pub mod user;
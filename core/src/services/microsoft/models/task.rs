@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use super::{
 	body::ItemBody, checklist_item::ChecklistItem,
 	date_time_zone::DateTimeTimeZone, importance::TaskImportance,
-	recurrence::TaskRecurrence, status::TaskStatus,
+	linked_resource::TodoLinkedResource, recurrence::TaskRecurrence,
+	status::TaskStatus,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -25,4 +26,7 @@ pub struct TodoTask {
 	pub last_modified_date_time: String,
 	pub reminder_date_time: Option<DateTimeTimeZone>,
 	pub start_date_time: Option<DateTimeTimeZone>,
+	/// Only returned when expanded, and only written when creating a task.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub linked_resources: Option<Vec<TodoLinkedResource>>,
 }
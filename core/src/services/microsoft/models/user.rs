@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// The signed in user, as returned by `/me`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+	pub id: String,
+	pub display_name: Option<String>,
+	pub mail: Option<String>,
+	pub user_principal_name: Option<String>,
+}
@@ -0,0 +1,227 @@
+use std::sync::RwLock;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use url::Url;
+
+/// The client registered for Done, it accepts personal accounts only.
+pub const DEFAULT_CLIENT_ID: &str = "75d8509b-cf9b-4245-9550-1e5f1d7c66e4";
+pub const REDIRECT_URI: &str = "done://msft";
+const LOGIN_HOST: &str = "https://login.microsoftonline.com";
+/// Scopes every sign-in asks for, refreshing tokens and reading the user
+/// don't work without them.
+const REQUIRED_SCOPES: [&str; 2] = ["offline_access", "user.read"];
+const DEFAULT_SCOPES: [&str; 4] = [
+	"tasks.read",
+	"tasks.read.shared",
+	"tasks.readwrite",
+	"tasks.readwrite.shared",
+];
+
+/// The settings chosen by the user, `None` uses the defaults.
+static SETTINGS: RwLock<Option<OAuthSettings>> = RwLock::new(None);
+
+/// The OAuth client used to sign in to Microsoft accounts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuthSettings {
+	/// Application (client) id of the app registration.
+	pub client_id: String,
+	/// Who can sign in: `consumers`, `organizations`, `common`, a tenant id
+	/// or domain, or the URL of another authority.
+	pub authority: String,
+	pub scopes: Vec<String>,
+	#[serde(default)]
+	pub flow: SignInFlow,
+}
+
+impl Default for OAuthSettings {
+	fn default() -> Self {
+		Self {
+			client_id: DEFAULT_CLIENT_ID.to_string(),
+			authority: Authority::Consumers.to_string(),
+			scopes: DEFAULT_SCOPES.iter().map(|scope| scope.to_string()).collect(),
+			flow: SignInFlow::default(),
+		}
+	}
+}
+
+impl OAuthSettings {
+	pub fn authorize_url(&self) -> String {
+		format!("{}/oauth2/v2.0/authorize", self.authority_url())
+	}
+
+	pub fn token_url(&self) -> String {
+		format!("{}/oauth2/v2.0/token", self.authority_url())
+	}
+
+	pub fn device_code_url(&self) -> String {
+		format!("{}/oauth2/v2.0/devicecode", self.authority_url())
+	}
+
+	fn authority_url(&self) -> String {
+		let authority = self.authority.trim().trim_end_matches('/');
+		if authority.starts_with("https://") {
+			authority.to_string()
+		} else {
+			format!("{LOGIN_HOST}/{authority}")
+		}
+	}
+
+	/// The configured scopes along with the ones sign-in depends on.
+	pub fn scopes(&self) -> Vec<String> {
+		let mut scopes: Vec<String> =
+			REQUIRED_SCOPES.iter().map(|scope| scope.to_string()).collect();
+		for scope in &self.scopes {
+			let scope = scope.trim();
+			if !scope.is_empty()
+				&& !scopes.iter().any(|added| added.eq_ignore_ascii_case(scope))
+			{
+				scopes.push(scope.to_string());
+			}
+		}
+		scopes
+	}
+
+	pub fn validate(&self) -> Result<()> {
+		if self.client_id.trim().is_empty() {
+			bail!("The OAuth client id is empty")
+		}
+		if self.authority.trim().is_empty() {
+			bail!("The OAuth authority is empty")
+		}
+		if self.authority.contains("://") && !self.authority.starts_with("https://")
+		{
+			bail!("The OAuth authority has to use HTTPS")
+		}
+		Ok(())
+	}
+}
+
+/// The well-known authorities, anything else is a tenant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Authority {
+	/// Personal Microsoft accounts.
+	Consumers,
+	/// Work and school accounts of any tenant.
+	Organizations,
+	/// Both personal and work or school accounts.
+	Common,
+}
+
+/// How the authorization code gets back to Done.
+#[derive(
+	Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display,
+)]
+pub enum SignInFlow {
+	/// The `done://` scheme when the desktop file is installed, the loopback
+	/// listener otherwise and the device code when neither works.
+	#[default]
+	Automatic,
+	/// The browser redirects to `done://msft`, which needs the desktop file.
+	UriScheme,
+	/// The browser redirects to a listener on 127.0.0.1.
+	Loopback,
+	/// The user enters a code at Microsoft's site from any device.
+	DeviceCode,
+}
+
+impl SignInFlow {
+	pub const ALL: [SignInFlow; 4] = [
+		SignInFlow::Automatic,
+		SignInFlow::UriScheme,
+		SignInFlow::Loopback,
+		SignInFlow::DeviceCode,
+	];
+}
+
+impl Authority {
+	pub const ALL: [Authority; 3] =
+		[Authority::Consumers, Authority::Organizations, Authority::Common];
+}
+
+/// An error response of the authorization or token endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OAuthError {
+	pub error: String,
+	pub error_description: Option<String>,
+}
+
+impl OAuthError {
+	/// The error of a redirect, if the sign-in failed.
+	pub fn from_redirect(uri: &Url) -> Option<Self> {
+		let param = |name: &str| {
+			uri.query_pairs()
+				.find(|(key, _)| key == name)
+				.map(|(_, value)| value.to_string())
+		};
+		param("error").map(|error| Self {
+			error,
+			error_description: param("error_description"),
+		})
+	}
+
+	/// Whether the refresh token was revoked or expired, or the user has to
+	/// consent again, only signing in again fixes those.
+	pub fn requires_login(&self) -> bool {
+		matches!(
+			self.error.as_str(),
+			"invalid_grant"
+				| "interaction_required"
+				| "login_required"
+				| "consent_required"
+		)
+	}
+}
+
+impl std::fmt::Display for OAuthError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.error_description {
+			Some(description) => write!(f, "{}: {description}", self.error),
+			None => write!(f, "{}", self.error),
+		}
+	}
+}
+
+impl std::error::Error for OAuthError {}
+
+/// The authorization code a sign-in redirect brings back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationResponse {
+	pub code: String,
+	pub state: Option<String>,
+}
+
+impl AuthorizationResponse {
+	pub fn from_redirect(uri: &Url) -> Result<Self> {
+		if let Some(error) = OAuthError::from_redirect(uri) {
+			return Err(error).context("Signing in failed");
+		}
+		let param = |name: &str| {
+			uri.query_pairs()
+				.find(|(key, _)| key == name)
+				.map(|(_, value)| value.to_string())
+		};
+		Ok(Self {
+			code: param("code").context("The redirect has no authorization code")?,
+			state: param("state"),
+		})
+	}
+}
+
+/// Settings used for accounts signed in from now on. Accounts keep the
+/// settings they signed in with, their tokens are tied to the client.
+pub fn set_oauth_settings(settings: Option<OAuthSettings>) {
+	if let Ok(mut current) = SETTINGS.write() {
+		*current = settings;
+	}
+}
+
+pub fn oauth_settings() -> OAuthSettings {
+	SETTINGS
+		.read()
+		.ok()
+		.and_then(|settings| settings.clone())
+		.unwrap_or_default()
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use graph_rs_sdk::Graph;
+use reqwest::{header::HeaderMap, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+/// Requests in flight at once across every account, Graph throttles
+/// clients that open too many.
+const MAX_CONCURRENT_REQUESTS: usize = 4;
+const MAX_RETRIES: u32 = 5;
+const BASE_DELAY: Duration = Duration::from_millis(500);
+/// Longest wait between retries, including the ones Graph asks for.
+const MAX_DELAY: Duration = Duration::from_secs(120);
+/// Graph accepts at most 20 requests in a JSON batch.
+const MAX_BATCH: usize = 20;
+
+static PERMITS: Semaphore = Semaphore::const_new(MAX_CONCURRENT_REQUESTS);
+
+/// Sends the request built by `request`, retrying throttled and transient
+/// failures. Waits as long as `Retry-After` says, or backs off exponentially
+/// with jitter when it's missing. Only for idempotent requests, see
+/// [`create_with_retry`].
+pub(crate) async fn send_with_retry<F, Fut, E>(request: F) -> Result<Response>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = Result<Response, E>>,
+	E: Into<anyhow::Error>,
+{
+	send(request, true).await
+}
+
+/// Like [`send_with_retry`] for requests that create something. A gateway
+/// error or a timeout doesn't mean the item wasn't created, so only throttled
+/// requests and connections that failed are retried.
+pub(crate) async fn create_with_retry<F, Fut, E>(request: F) -> Result<Response>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = Result<Response, E>>,
+	E: Into<anyhow::Error>,
+{
+	send(request, false).await
+}
+
+async fn send<F, Fut, E>(mut request: F, idempotent: bool) -> Result<Response>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = Result<Response, E>>,
+	E: Into<anyhow::Error>,
+{
+	let mut attempt = 0;
+	loop {
+		let result = {
+			let _permit = PERMITS.acquire().await?;
+			request().await.map_err(Into::into)
+		};
+		let delay = match &result {
+			Ok(response) if should_retry(response.status(), idempotent) => {
+				retry_after(response.headers()).unwrap_or_else(|| backoff(attempt))
+			},
+			Err(err) if is_transient_error(err, idempotent) => backoff(attempt),
+			_ => return result,
+		};
+		if attempt >= MAX_RETRIES {
+			return result;
+		}
+		match &result {
+			Ok(response) => tracing::warn!(
+				"Graph answered {}, retrying in {delay:?}",
+				response.status()
+			),
+			Err(err) => tracing::warn!("{err:#}, retrying in {delay:?}"),
+		}
+		tokio::time::sleep(delay).await;
+		attempt += 1;
+	}
+}
+
+/// A request of a JSON batch, `url` is relative to the Graph version.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BatchRequest {
+	id: String,
+	method: &'static str,
+	url: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	body: Option<serde_json::Value>,
+	#[serde(skip_serializing_if = "HashMap::is_empty")]
+	headers: HashMap<String, String>,
+}
+
+impl BatchRequest {
+	pub fn new(id: impl ToString, method: &'static str, url: String) -> Self {
+		Self {
+			id: id.to_string(),
+			method,
+			url,
+			body: None,
+			headers: HashMap::new(),
+		}
+	}
+
+	pub fn json(mut self, body: serde_json::Value) -> Self {
+		self.body = Some(body);
+		self.header("Content-Type", "application/json")
+	}
+
+	fn header(mut self, name: &str, value: &str) -> Self {
+		self.headers.insert(name.to_string(), value.to_string());
+		self
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct BatchResponse {
+	pub id: String,
+	pub status: u16,
+	#[serde(default)]
+	pub headers: HashMap<String, String>,
+	#[serde(default)]
+	pub body: Option<serde_json::Value>,
+}
+
+impl BatchResponse {
+	pub fn is_success(&self) -> bool {
+		StatusCode::from_u16(self.status).is_ok_and(|status| status.is_success())
+	}
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchResponses {
+	responses: Vec<BatchResponse>,
+}
+
+/// Sends `requests` in as few JSON batches as Graph allows, retrying the
+/// requests of a batch that were throttled. Returns the response of each
+/// request, in no particular order.
+pub(crate) async fn send_batch(
+	client: &Graph,
+	requests: Vec<BatchRequest>,
+) -> Result<Vec<BatchResponse>> {
+	let mut pending = requests;
+	let mut responses = Vec::with_capacity(pending.len());
+	let mut attempt = 0;
+	while !pending.is_empty() {
+		let mut throttled = vec![];
+		let mut delay = Duration::ZERO;
+		for chunk in pending.chunks(MAX_BATCH) {
+			let body = serde_json::json!({ "requests": chunk });
+			// A batch that failed on the way back may have created items
+			// already, only retry those the way a single creation would be.
+			let response = if chunk.iter().any(|request| request.method == "POST") {
+				create_with_retry(|| client.batch(&body).send()).await?
+			} else {
+				send_with_retry(|| client.batch(&body).send()).await?
+			}
+			.error_for_status()?;
+			let batch: BatchResponses = response.json().await?;
+			for response in batch.responses {
+				let status = StatusCode::from_u16(response.status)
+					.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+				let request = chunk.iter().find(|request| request.id == response.id);
+				let idempotent = request.is_some_and(|request| request.method != "POST");
+				if attempt < MAX_RETRIES && should_retry(status, idempotent) {
+					let wait = response
+						.headers
+						.iter()
+						.find(|(name, _)| name.eq_ignore_ascii_case("retry-after"))
+						.and_then(|(_, value)| parse_retry_after(value))
+						.unwrap_or_else(|| backoff(attempt));
+					delay = delay.max(wait);
+					throttled.extend(request.cloned());
+				} else {
+					responses.push(response);
+				}
+			}
+		}
+		if !throttled.is_empty() {
+			tracing::warn!(
+				"Graph throttled {} batched requests, retrying in {delay:?}",
+				throttled.len()
+			);
+			tokio::time::sleep(delay).await;
+			attempt += 1;
+		}
+		pending = throttled;
+	}
+	Ok(responses)
+}
+
+/// Throttled requests weren't processed, gateway errors may have been.
+fn should_retry(status: StatusCode, idempotent: bool) -> bool {
+	match status {
+		StatusCode::TOO_MANY_REQUESTS => true,
+		StatusCode::BAD_GATEWAY
+		| StatusCode::SERVICE_UNAVAILABLE
+		| StatusCode::GATEWAY_TIMEOUT => idempotent,
+		_ => false,
+	}
+}
+
+/// Dropped connections, and timeouts for idempotent requests. A request that
+/// timed out may have reached Graph.
+fn is_transient_error(err: &anyhow::Error, idempotent: bool) -> bool {
+	err.chain().any(|cause| {
+		cause
+			.downcast_ref::<reqwest::Error>()
+			.is_some_and(|err| err.is_connect() || (idempotent && err.is_timeout()))
+	})
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+	headers
+		.get(reqwest::header::RETRY_AFTER)
+		.and_then(|value| value.to_str().ok())
+		.and_then(parse_retry_after)
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+	let delay = match value.trim().parse::<u64>() {
+		Ok(seconds) => Duration::from_secs(seconds),
+		Err(_) => {
+			let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+			(date.with_timezone(&Utc) - Utc::now())
+				.to_std()
+				.unwrap_or_default()
+		},
+	};
+	Some(delay.min(MAX_DELAY))
+}
+
+/// Exponential backoff with jitter, so clients throttled together don't
+/// retry together.
+fn backoff(attempt: u32) -> Duration {
+	let delay = BASE_DELAY
+		.saturating_mul(2u32.saturating_pow(attempt))
+		.min(MAX_DELAY);
+	delay.mul_f64(0.5 + fastrand::f64() / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::sync::Arc;
+
+	use tokio::time::Instant;
+
+	use super::*;
+	use crate::services::microsoft::mock::{MockResponse, MockServer};
+
+	/// Answers with `responses` in order, then repeats the last one.
+	async fn scripted(responses: Vec<MockResponse>) -> MockServer {
+		let count = AtomicUsize::new(0);
+		MockServer::start(move |_| {
+			let index = count.fetch_add(1, Ordering::SeqCst);
+			responses[index.min(responses.len() - 1)].clone()
+		})
+		.await
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn waits_as_long_as_retry_after_says() {
+		let server = scripted(vec![
+			MockResponse::new(429).header("Retry-After", "7"),
+			MockResponse::new(200),
+		])
+		.await;
+		let client = reqwest::Client::new();
+		let start = Instant::now();
+
+		let response = send_with_retry(|| client.get(server.url()).send())
+			.await
+			.unwrap();
+
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(server.requests().len(), 2);
+		assert!(start.elapsed() >= Duration::from_secs(7));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn backs_off_exponentially_without_retry_after() {
+		let server = scripted(vec![
+			MockResponse::new(503),
+			MockResponse::new(503),
+			MockResponse::new(503),
+			MockResponse::new(200),
+		])
+		.await;
+		let client = reqwest::Client::new();
+		let start = Instant::now();
+
+		let response = send_with_retry(|| client.get(server.url()).send())
+			.await
+			.unwrap();
+
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(server.requests().len(), 4);
+		// 500ms, 1s and 2s, each at least halved by the jitter.
+		assert!(start.elapsed() >= Duration::from_millis(1750));
+	}
+
+	#[test]
+	fn backoff_doubles_up_to_the_maximum() {
+		for attempt in 0..10 {
+			let full = BASE_DELAY
+				.saturating_mul(2u32.pow(attempt))
+				.min(MAX_DELAY);
+			let delay = backoff(attempt);
+			assert!(delay >= full / 2 && delay <= full, "{attempt}: {delay:?}");
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn gives_up_after_the_last_retry() {
+		let server = scripted(vec![MockResponse::new(503)]).await;
+		let client = reqwest::Client::new();
+
+		let response = send_with_retry(|| client.get(server.url()).send())
+			.await
+			.unwrap();
+
+		assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+		assert_eq!(server.requests().len(), MAX_RETRIES as usize + 1);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn retries_creates_only_when_throttled() {
+		let server = scripted(vec![MockResponse::new(503)]).await;
+		let client = reqwest::Client::new();
+		let response = create_with_retry(|| client.post(server.url()).send())
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+		assert_eq!(server.requests().len(), 1);
+		assert_eq!(server.requests()[0].method, "POST");
+
+		let server = scripted(vec![
+			MockResponse::new(429).header("Retry-After", "1"),
+			MockResponse::new(201),
+		])
+		.await;
+		let response = create_with_retry(|| client.post(server.url()).send())
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::CREATED);
+		assert_eq!(server.requests().len(), 2);
+	}
+
+	#[test]
+	fn parses_retry_after_dates() {
+		let in_a_minute = (Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+		let delay = parse_retry_after(&in_a_minute).unwrap();
+		assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(60));
+		assert_eq!(parse_retry_after("3"), Some(Duration::from_secs(3)));
+		assert_eq!(parse_retry_after("3600"), Some(MAX_DELAY));
+		assert_eq!(parse_retry_after("soon"), None);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn resends_only_the_throttled_requests_of_a_batch() {
+		let batches = Arc::new(AtomicUsize::new(0));
+		let count = batches.clone();
+		let server = MockServer::start(move |request| {
+			let first = count.fetch_add(1, Ordering::SeqCst) == 0;
+			let responses: Vec<serde_json::Value> = request.json()["requests"]
+				.as_array()
+				.unwrap()
+				.iter()
+				.map(|request| {
+					let id = request["id"].as_str().unwrap();
+					if first && id == "1" {
+						serde_json::json!({
+							"id": id,
+							"status": 429,
+							"headers": { "Retry-After": "2" },
+						})
+					} else {
+						serde_json::json!({ "id": id, "status": 204 })
+					}
+				})
+				.collect();
+			MockResponse::json(200, serde_json::json!({ "responses": responses }))
+		})
+		.await;
+		let requests = (0..3)
+			.map(|id| BatchRequest::new(id, "DELETE", format!("/items/{id}")))
+			.collect();
+
+		let responses = send_batch(&server.graph(), requests).await.unwrap();
+
+		assert_eq!(responses.len(), 3);
+		assert!(responses.iter().all(BatchResponse::is_success));
+		let sent = server.requests();
+		assert_eq!(sent.len(), 2);
+		assert_eq!(sent[0].path, "/$batch");
+		assert_eq!(sent[1].json()["requests"].as_array().unwrap().len(), 1);
+		assert_eq!(sent[1].json()["requests"][0]["id"], "1");
+	}
+}
@@ -1,177 +1,636 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
 
+use crate::credentials::{credential_store, CredentialStore};
+use crate::models::account::Account;
+use crate::models::attachment::{attachments_dir, Attachment, AttachmentStore};
+use crate::models::linked_resource::LinkedResource;
 use crate::models::list::List;
+use crate::models::list_group::ListGroup;
+use crate::models::login::{Login, LoginPrompter};
 use crate::models::task::Task;
+use crate::service::{database, Service};
+use crate::services::local::database::Database;
 use crate::services::microsoft::models::{
-	checklist_item::ChecklistItem, collection::Collection, list::TodoTaskList,
+	attachment::{AttachmentInfo, TaskFileAttachment, UploadSession},
+	collection::Collection,
+	linked_resource::TodoLinkedResource,
+	list::TodoTaskList,
 	task::TodoTask,
 };
 use crate::task_service::TodoProvider;
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
+use base64::Engine;
 use futures::{Stream, StreamExt};
 use graph_rs_sdk::{
-	oauth::{AccessToken, OAuth},
-	Graph,
+	oauth::{AccessToken, OAuth, OAuthCredential},
+	Graph, ODataQuery,
 };
+use libset::{Config, FileType};
 use reqwest::StatusCode;
 use url::Url;
+use uuid::Uuid;
 
-pub const APP_ID: &str = "dev.edfloreshz.Done";
-const CLIENT_ID: &str = "75d8509b-cf9b-4245-9550-1e5f1d7c66e4";
-const REDIRECT_URI: &str = "done://msft";
+use super::checklist::sync_checklist_items;
+use super::models::user::User;
+use super::requests::{create_with_retry, send_with_retry};
+use super::login::{device_code_token, uri_scheme_registered, LoopbackListener};
+use super::oauth::{
+	oauth_settings, AuthorizationResponse, OAuthError, OAuthSettings, SignInFlow,
+	REDIRECT_URI,
+};
+use super::tokens::{needs_refresh, refresh_lock};
+
+/// The provider name of Microsoft [`Account`]s.
+pub const PROVIDER: &str = "microsoft";
+/// Where the token was kept in the keyring before the credential store.
+const LEGACY_KEYRING_SERVICE: &str = "dev.edfloreshz.Done";
+/// Where the token was kept in the credential store before accounts.
+const LEGACY_NAMESPACE: &str = "microsoft";
+const TOKEN_KEY: &str = "access_token";
+/// Created once the legacy token was looked for, so the keyring isn't queried
+/// on every start.
+const LEGACY_MIGRATED_FILE: &str = "legacy-account-migrated";
+/// The [`OAuthSettings`] the account signed in with.
+const SETTINGS_KEY: &str = "oauth_settings";
+/// PKCE verifier of a sign-in waiting for its redirect.
+const VERIFIER_KEY: &str = "code_verifier";
+/// Files larger than this have to be sent through an upload session.
+const MAX_INLINE_ATTACHMENT: u64 = 3 * 1024 * 1024;
+/// The largest file Microsoft To Do accepts as an attachment.
+const MAX_ATTACHMENT: u64 = 25 * 1024 * 1024;
+/// Upload session chunks have to be a multiple of 320 KiB.
+const UPLOAD_CHUNK: usize = 10 * 320 * 1024;
 
 #[derive(Debug, Clone)]
 
 pub struct MicrosoftService {
 	client: Graph,
 	token: AccessToken,
+	account: Uuid,
+	credentials: Arc<dyn CredentialStore>,
+	application_id: String,
+	/// Local storage for task fields Microsoft To Do has no place for.
+	overlay: Database,
 }
 
 #[allow(unused)]
 impl MicrosoftService {
-	pub fn new() -> Self {
+	pub fn new(application_id: String, account: Uuid) -> Self {
+		let credentials = credential_store(&application_id);
 		let mut token = AccessToken::default();
 
-		if let Ok(access_token) = keytar::get_password(APP_ID, "access_token") {
-			if (!access_token.password.is_empty()) {
-				token = serde_json::from_str(access_token.password.as_str()).unwrap();
-			}
+		match credentials.get(&namespace(account), TOKEN_KEY) {
+			Ok(Some(stored)) => match serde_json::from_str(&stored) {
+				Ok(stored) => token = stored,
+				Err(err) => tracing::error!("Stored access token is invalid: {err}"),
+			},
+			Ok(None) => {},
+			Err(err) => tracing::error!("Failed to read the access token: {err:#}"),
 		}
 		Self {
 			client: Graph::new(token.bearer_token()),
 			token,
+			account,
+			credentials,
+			application_id: application_id.clone(),
+			overlay: database(),
 		}
 	}
 
-	fn oauth_client() -> OAuth {
+	fn oauth_client(settings: &OAuthSettings, redirect_uri: &str) -> OAuth {
 		let mut oauth = OAuth::new();
 		oauth
-			.client_id(CLIENT_ID)
-			.redirect_uri(REDIRECT_URI)
-			.add_scope("offline_access")
-			.add_scope("user.read")
-			.add_scope("tasks.read")
-			.add_scope("tasks.read.shared")
-			.add_scope("tasks.readwrite")
-			.add_scope("tasks.readwrite.shared")
-			.authorize_url(
-				"https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize",
-			)
-			.access_token_url(
-				"https://login.microsoftonline.com/consumers/oauth2/v2.0/token",
-			)
-			.refresh_token_url(
-				"https://login.microsoftonline.com/consumers/oauth2/v2.0/token",
-			)
+			.client_id(settings.client_id.trim())
+			.redirect_uri(redirect_uri)
+			.extend_scopes(settings.scopes())
+			.authorize_url(&settings.authorize_url())
+			.access_token_url(&settings.token_url())
+			.refresh_token_url(&settings.token_url())
 			.response_type("code");
 		oauth
 	}
 
-	async fn refresh_token(&mut self) -> Result<()> {
-		if self.token.is_expired() {
-			if let Some(refresh_token) = self.token.refresh_token() {
-				let mut oauth = Self::oauth_client();
-				oauth.access_token(self.token.clone());
-				let token: AccessToken = oauth
-					.build_async()
-					.authorization_code_grant()
-					.refresh_token()
-					.send()
-					.await?
-					.json()
-					.await?;
-				self.store_token(token)?;
+	/// The settings the account signed in with, accounts added before they
+	/// were configurable used the defaults.
+	fn settings(&self) -> OAuthSettings {
+		match self.credentials.get(&namespace(self.account), SETTINGS_KEY) {
+			Ok(Some(stored)) => serde_json::from_str(&stored).unwrap_or_else(|err| {
+				tracing::error!("Stored OAuth settings are invalid: {err}");
+				OAuthSettings::default()
+			}),
+			Ok(None) => OAuthSettings::default(),
+			Err(err) => {
+				tracing::error!("Failed to read the OAuth settings: {err:#}");
+				OAuthSettings::default()
+			},
+		}
+	}
+
+	fn store_settings(&self, settings: &OAuthSettings) -> Result<()> {
+		self.credentials.set(
+			&namespace(self.account),
+			SETTINGS_KEY,
+			&serde_json::to_string(settings)?,
+		)
+	}
+
+	/// Refreshes the token when it's about to expire. When Microsoft rejects
+	/// the refresh token the account is marked as needing to sign in again.
+	pub(crate) async fn refresh_token(&mut self) -> Result<()> {
+		if !needs_refresh(&self.token) {
+			return Ok(());
+		}
+		let _guard = refresh_lock(self.account).await;
+		// Another service could have refreshed it while this one waited.
+		if let Some(stored) = self.stored_token()? {
+			if !needs_refresh(&stored) {
+				self.client = Graph::new(stored.bearer_token());
+				self.token = stored;
+				return Ok(());
+			}
+			self.token = stored;
+		}
+		let Some(refresh_token) = self.token.refresh_token() else {
+			return Ok(());
+		};
+
+		let mut oauth = Self::oauth_client(&self.settings(), REDIRECT_URI);
+		oauth.access_token(self.token.clone());
+		let response = oauth
+			.build_async()
+			.authorization_code_grant()
+			.refresh_token()
+			.send()
+			.await?;
+		if !response.status().is_success() {
+			let status = response.status();
+			let error = response.json::<OAuthError>().await.with_context(|| {
+				format!("Refreshing the access token failed with {status}")
+			})?;
+			if error.requires_login() {
+				self.overlay.set_account_needs_login(self.account, true)?;
 			}
+			return Err(error).context("Refreshing the access token failed");
+		}
+		let mut token: AccessToken = response.json().await?;
+		if token.refresh_token().is_none() {
+			token.set_refresh_token(&refresh_token);
+		}
+		self.store_token(token)
+	}
+
+	fn stored_token(&self) -> Result<Option<AccessToken>> {
+		self.credentials
+			.get(&namespace(self.account), TOKEN_KEY)?
+			.map(|stored| serde_json::from_str(&stored))
+			.transpose()
+			.context("Stored access token is invalid")
+	}
+
+	fn service(&self) -> Service {
+		Service::Microsoft(self.account)
+	}
+
+	/// Turns the token of the single account supported before into an
+	/// [`Account`], wherever it was stored. Only runs until it succeeds once.
+	pub fn migrate_legacy_account(application_id: &str) -> Result<()> {
+		let config = Config::new(application_id, 1, None)?;
+		if config.path(LEGACY_MIGRATED_FILE, FileType::Plain)?.exists() {
+			return Ok(());
 		}
+		Self::migrate_legacy_token(application_id)?;
+		config.set_plain(LEGACY_MIGRATED_FILE, String::new())?;
 		Ok(())
 	}
 
-	fn store_token(&mut self, token: AccessToken) -> Result<()> {
-		keytar::set_password(
-			APP_ID,
-			"access_token",
+	fn migrate_legacy_token(application_id: &str) -> Result<()> {
+		let credentials = credential_store(application_id);
+		let mut token = credentials.get(LEGACY_NAMESPACE, TOKEN_KEY)?;
+		let from_keyring = token.is_none();
+		if from_keyring {
+			token = keytar::get_password(LEGACY_KEYRING_SERVICE, TOKEN_KEY)
+				.ok()
+				.filter(|legacy| legacy.success && !legacy.password.is_empty())
+				.map(|legacy| legacy.password);
+		}
+		let Some(token) = token else {
+			return Ok(());
+		};
+
+		let account = Account::new(Uuid::new_v4(), PROVIDER, "", "");
+		credentials.set(&namespace(account.id), TOKEN_KEY, &token)?;
+		database().save_account(&account)?;
+		if from_keyring {
+			if let Err(err) =
+				keytar::delete_password(LEGACY_KEYRING_SERVICE, TOKEN_KEY)
+			{
+				tracing::warn!("Failed to remove the old access token: {err}");
+			}
+		} else {
+			credentials.delete(LEGACY_NAMESPACE, TOKEN_KEY)?;
+		}
+		Ok(())
+	}
+
+	/// Adds the account once it's signed in, or signs an existing account
+	/// in again if the user already added it.
+	async fn register_account(&mut self) -> Result<()> {
+		let response =
+			send_with_retry(|| self.client.me().get_user().send()).await?;
+		let user: User = response.error_for_status()?.json().await?;
+		let name = user
+			.user_principal_name
+			.or(user.mail)
+			.or(user.display_name)
+			.unwrap_or_default();
+
+		let mut accounts = self.overlay.accounts(PROVIDER)?;
+		let existing = accounts.iter_mut().find(|account| {
+			account.id == self.account
+				|| (!user.id.is_empty() && account.remote_id == user.id)
+		});
+		let account = match existing {
+			Some(account) => {
+				if account.id != self.account {
+					// The same user signed in again as a new account, keep the
+					// old one so its lists and overlays stay attached.
+					let settings = self.settings();
+					self.credentials.delete(&namespace(self.account), TOKEN_KEY)?;
+					self.credentials.delete(&namespace(self.account), SETTINGS_KEY)?;
+					self.account = account.id;
+					self.store_token(self.token.clone())?;
+					self.store_settings(&settings)?;
+				}
+				account.name = name;
+				account.remote_id = user.id;
+				account.needs_login = false;
+				account.clone()
+			},
+			None => Account::new(self.account, PROVIDER, &name, &user.id),
+		};
+		self.overlay.save_account(&account)
+	}
+
+	fn store_token(&mut self, mut token: AccessToken) -> Result<()> {
+		// Token responses only have the lifetime, not when it ends.
+		if token.timestamp().is_none() {
+			token.gen_timestamp();
+		}
+		self.credentials.set(
+			&namespace(self.account),
+			TOKEN_KEY,
 			&serde_json::to_string(&token)?,
-		);
+		)?;
+		self.client = Graph::new(token.bearer_token());
 		self.token = token;
 		Ok(())
 	}
 
-	pub async fn request_token(&mut self, access_code: String) -> Result<()> {
-		let mut oauth = Self::oauth_client();
+	pub async fn request_token(
+		&mut self,
+		access_code: String,
+		redirect_uri: &str,
+	) -> Result<()> {
+		let mut oauth = Self::oauth_client(&self.settings(), redirect_uri);
 		oauth.access_code(access_code.as_str());
+		let verifier = self.credentials.get(&namespace(self.account), VERIFIER_KEY)?;
+		if let Some(verifier) = verifier {
+			oauth.code_verifier(&verifier);
+			self.credentials.delete(&namespace(self.account), VERIFIER_KEY)?;
+		}
 		let mut request = oauth.build_async().authorization_code_grant();
 
 		let response = request.access_token().send().await?;
 
-		match response.error_for_status() {
-			Ok(response) => {
-				let access_token: AccessToken = response.json().await?;
-				oauth.access_token(access_token.clone());
-				self.store_token(access_token);
-				Ok(())
-			},
-			Err(error) => Err(error.into()),
+		if !response.status().is_success() {
+			let status = response.status();
+			let error = response.json::<OAuthError>().await.with_context(|| {
+				format!("Requesting the access token failed with {status}")
+			})?;
+			return Err(error).context("Requesting the access token failed");
+		}
+		let access_token: AccessToken = response.json().await?;
+		self.store_token(access_token)?;
+		self.register_account().await
+	}
+
+	/// Opens the browser at the sign-in page, PKCE protects the code it
+	/// redirects back with.
+	fn open_sign_in_page(
+		&self,
+		settings: &OAuthSettings,
+		redirect_uri: &str,
+	) -> Result<()> {
+		let mut oauth = Self::oauth_client(settings, redirect_uri);
+		oauth.state(&self.account.to_string());
+		oauth.generate_sha256_challenge_and_verifier()?;
+		let verifier = oauth
+			.get(OAuthCredential::CodeVerifier)
+			.context("No PKCE verifier was generated")?;
+		self.credentials.set(&namespace(self.account), VERIFIER_KEY, &verifier)?;
+		let url = oauth
+			.build_async()
+			.authorization_code_grant()
+			.authorization_url()?;
+		open::that(url.as_str()).context("Failed to open the browser")
+	}
+
+	async fn start_loopback_login(
+		&self,
+		settings: &OAuthSettings,
+	) -> Result<LoopbackListener> {
+		let listener = LoopbackListener::bind().await?;
+		self.open_sign_in_page(settings, &listener.redirect_uri()?)?;
+		Ok(listener)
+	}
+
+	async fn finish_loopback_login(
+		&mut self,
+		listener: LoopbackListener,
+	) -> Result<Login> {
+		let redirect_uri = listener.redirect_uri()?;
+		let redirect = listener.redirect().await?;
+		self.handle_authorization(&redirect, &redirect_uri).await?;
+		Ok(Login::Completed)
+	}
+
+	/// Signs in with the code of a redirect, after checking it was meant for
+	/// this account.
+	async fn handle_authorization(
+		&mut self,
+		redirect: &Url,
+		redirect_uri: &str,
+	) -> Result<()> {
+		let response = AuthorizationResponse::from_redirect(redirect)?;
+		if response.state != Some(self.account.to_string()) {
+			bail!("The sign-in redirect is for another account")
 		}
+		self.request_token(response.code, redirect_uri).await
+	}
+
+	async fn device_code_login(
+		&mut self,
+		settings: &OAuthSettings,
+		prompt: &LoginPrompter,
+	) -> Result<Login> {
+		let token = device_code_token(settings, prompt).await?;
+		self.store_token(token)?;
+		self.register_account().await?;
+		Ok(Login::Completed)
+	}
+
+	/// A task as Graph stores it, along with its linked resources.
+	async fn read_todo_task(
+		&self,
+		task_list_id: &str,
+		task_id: &str,
+	) -> Result<TodoTask> {
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(task_list_id)
+				.task(task_id)
+				.get_tasks()
+				.expand(&["linkedResources"])
+				.send()
+		})
+		.await?;
+		Ok(response.error_for_status()?.json().await?)
 	}
 
-	pub async fn update_check_list_items(
+	/// Creates and deletes the linked resources of a task so the `remote`
+	/// ones match `linked_resources`, links are compared by URL since Graph
+	/// assigns its own ids.
+	async fn sync_linked_resources(
 		&self,
 		todo_task_list_id: &str,
 		todo_task_id: &str,
-		checklist_items: &Option<Vec<ChecklistItem>>,
+		remote: &[TodoLinkedResource],
+		linked_resources: &[LinkedResource],
 	) -> Result<()> {
-		if let Some(checklist_items) = checklist_items {
-			for item in checklist_items {
-				let response = self
-					.client
-					.me()
-					.todo()
-					.list(todo_task_list_id)
-					.task(todo_task_id)
-					.update_checklist_items(&item.id, &serde_json::json!(item))
-					.send()
-					.await?;
+		for resource in remote {
+			let kept = linked_resources.iter().any(|link| {
+				Some(&link.web_url) == resource.web_url.as_ref()
+			});
+			if !kept {
+				let response = send_with_retry(|| {
+					self.client
+						.me()
+						.todo()
+						.list(todo_task_list_id)
+						.task(todo_task_id)
+						.delete_linked_resources(&resource.id)
+						.send()
+				})
+				.await?;
+				if let Err(err) = response.error_for_status() {
+					tracing::error!("There was an error removing a link: {err}")
+				}
+			}
+		}
 
+		for link in linked_resources {
+			let exists = remote.iter().any(|resource| {
+				resource.web_url.as_ref() == Some(&link.web_url)
+			});
+			if !exists {
+				let resource = TodoLinkedResource::from(link.clone());
+				let response = create_with_retry(|| {
+					self.client
+						.me()
+						.todo()
+						.list(todo_task_list_id)
+						.task(todo_task_id)
+						.create_linked_resources(&serde_json::json!(resource))
+						.send()
+				})
+				.await?;
 				if let Err(err) = response.error_for_status() {
-					tracing::error!(
-						"There was an error updating a check list item: {}",
-						err.to_string()
-					)
+					tracing::error!("There was an error adding a link: {err}")
 				}
 			}
 		}
 		Ok(())
 	}
+
+	async fn list_attachments(
+		&self,
+		task_list_id: &str,
+		task_id: &str,
+	) -> Result<Vec<Attachment>> {
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(task_list_id)
+				.task(task_id)
+				.list_attachments()
+				.send()
+		})
+		.await?;
+		let collection: Collection<TaskFileAttachment> =
+			response.error_for_status()?.json().await?;
+		collection
+			.value
+			.into_iter()
+			.map(|attachment| attachment.into_attachment(task_id))
+			.collect()
+	}
+
+	/// Sends a file in a single request with its content encoded in base64.
+	async fn upload_attachment(
+		&self,
+		task_list_id: &str,
+		task_id: &str,
+		attachment: &Attachment,
+		bytes: Vec<u8>,
+	) -> Result<Attachment> {
+		let body = TaskFileAttachment {
+			odata_type: TaskFileAttachment::ODATA_TYPE.to_string(),
+			name: attachment.name.clone(),
+			content_type: attachment.content_type.clone(),
+			content_bytes: Some(
+				base64::engine::general_purpose::STANDARD.encode(bytes),
+			),
+			..Default::default()
+		};
+		let response = create_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(task_list_id)
+				.task(task_id)
+				.create_attachments(&serde_json::json!(body))
+				.send()
+		})
+		.await?;
+		match response.error_for_status() {
+			Ok(response) => {
+				let created: TaskFileAttachment = response.json().await?;
+				created.into_attachment(task_id)
+			},
+			Err(err) => bail!("An error ocurred while attaching the file: {err}"),
+		}
+	}
+
+	/// Sends a large file in chunks through an upload session.
+	async fn upload_attachment_in_session(
+		&self,
+		task_list_id: &str,
+		task_id: &str,
+		attachment: &Attachment,
+		bytes: Vec<u8>,
+	) -> Result<Attachment> {
+		let info = AttachmentInfo {
+			attachment_type: "file".to_string(),
+			name: attachment.name.clone(),
+			size: attachment.size,
+		};
+		let response = create_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(task_list_id)
+				.task(task_id)
+				.create_upload_session(&serde_json::json!({ "attachmentInfo": info }))
+				.send()
+		})
+		.await?;
+		let session: UploadSession = match response.error_for_status() {
+			Ok(response) => response.json().await?,
+			Err(err) => bail!("An error ocurred while starting the upload: {err}"),
+		};
+
+		// The upload URL is pre-authenticated, so it must not get the bearer
+		// token.
+		let client = reqwest::Client::new();
+		let total = bytes.len();
+		for (index, chunk) in bytes.chunks(UPLOAD_CHUNK).enumerate() {
+			let start = index * UPLOAD_CHUNK;
+			let end = start + chunk.len() - 1;
+			let response = send_with_retry(|| {
+				client
+					.put(&session.upload_url)
+					.header(reqwest::header::CONTENT_LENGTH, chunk.len())
+					.header(
+						reqwest::header::CONTENT_RANGE,
+						format!("bytes {start}-{end}/{total}"),
+					)
+					.body(chunk.to_vec())
+					.send()
+			})
+			.await?;
+			if let Err(err) = response.error_for_status() {
+				bail!("An error ocurred while uploading the file: {err}")
+			}
+		}
+
+		self
+			.list_attachments(task_list_id, task_id)
+			.await?
+			.into_iter()
+			.filter(|uploaded| uploaded.name == attachment.name)
+			.max_by_key(|uploaded| uploaded.created_date_time)
+			.context("The uploaded attachment was not found")
+	}
 }
 
 #[async_trait]
 #[allow(unused)]
 impl TodoProvider for MicrosoftService {
 	async fn handle_uri_params(&mut self, uri: Url) -> Result<()> {
-		let mut pairs = uri.query_pairs();
 		if uri.as_str().contains("msft") {
-			let code = pairs.next().unwrap().1.to_string();
-			self.request_token(code).await?;
+			self.handle_authorization(&uri, REDIRECT_URI).await?;
 		}
 		Ok(())
 	}
 
-	fn login(&self) -> anyhow::Result<()> {
-		let mut oauth = MicrosoftService::oauth_client();
-		let mut request = oauth.build_async().authorization_code_grant();
-		request.browser_authorization().open()?;
-		Ok(())
+	async fn login(&mut self, prompt: LoginPrompter) -> Result<Login> {
+		let settings = oauth_settings();
+		settings.validate()?;
+		self.store_settings(&settings)?;
+		match settings.flow {
+			SignInFlow::UriScheme => {
+				self.open_sign_in_page(&settings, REDIRECT_URI)?;
+				Ok(Login::Redirect)
+			},
+			SignInFlow::Loopback => {
+				let listener = self.start_loopback_login(&settings).await?;
+				self.finish_loopback_login(listener).await
+			},
+			SignInFlow::DeviceCode => self.device_code_login(&settings, &prompt).await,
+			SignInFlow::Automatic => {
+				if uri_scheme_registered(&self.application_id) {
+					match self.open_sign_in_page(&settings, REDIRECT_URI) {
+						Ok(_) => return Ok(Login::Redirect),
+						Err(err) => tracing::warn!("{err:#}"),
+					}
+				}
+				match self.start_loopback_login(&settings).await {
+					Ok(listener) => self.finish_loopback_login(listener).await,
+					Err(err) => {
+						tracing::warn!("Signing in with a device code: {err:#}");
+						self.device_code_login(&settings, &prompt).await
+					},
+				}
+			},
+		}
 	}
 
 	fn logout(&self) -> anyhow::Result<()> {
-		keytar::delete_password(APP_ID, "access_token")?;
-		Ok(())
+		for key in [TOKEN_KEY, SETTINGS_KEY, VERIFIER_KEY] {
+			self.credentials.delete(&namespace(self.account), key)?;
+		}
+		database().remove_account(self.account)
 	}
 
 	fn available(&self) -> bool {
-		let password = keytar::get_password(APP_ID, "access_token");
-		password.is_ok() && !password.unwrap().password.is_empty()
+		!self.token.bearer_token().is_empty()
 	}
 
 	fn stream_support(&self) -> bool {
@@ -179,7 +638,11 @@ impl TodoProvider for MicrosoftService {
 	}
 
 	async fn read_tasks(&mut self) -> Result<Vec<Task>> {
-		Ok(vec![])
+		let mut tasks = vec![];
+		for list in self.read_lists().await? {
+			tasks.extend(self.read_tasks_from_list(list.id).await?);
+		}
+		Ok(tasks)
 	}
 
 	async fn read_tasks_from_list(
@@ -187,27 +650,25 @@ impl TodoProvider for MicrosoftService {
 		parent_list: String,
 	) -> Result<Vec<Task>> {
 		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(parent_list.clone())
-			.tasks()
-			.list_tasks()
-			.send()
-			.await?;
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(parent_list.clone())
+				.tasks()
+				.list_tasks()
+				.expand(&["linkedResources"])
+				.send()
+		})
+		.await?;
 		let collection: Collection<TodoTask> = response.json().await?;
-		Ok(
-			collection
-				.value
-				.iter()
-				.map(|task| {
-					let mut task: Task = task.clone().into();
-					task.parent = parent_list.clone();
-					task
-				})
-				.collect(),
-		)
+		let mut tasks: Vec<Task> = collection
+			.value
+			.into_iter()
+			.filter_map(|task| convert_task(task, &parent_list))
+			.collect();
+		self.overlay.apply_overlay(&mut tasks)?;
+		Ok(tasks)
 	}
 
 	async fn get_tasks(
@@ -215,6 +676,8 @@ impl TodoProvider for MicrosoftService {
 		parent_list: String,
 	) -> Result<Pin<Box<dyn Stream<Item = Task> + Send>>> {
 		self.refresh_token().await?;
+		let estimates = self.overlay.overlay_estimates()?;
+		let planned = self.overlay.my_day()?;
 		let mut stream = self
 			.client
 			.me()
@@ -222,27 +685,38 @@ impl TodoProvider for MicrosoftService {
 			.list(parent_list.clone())
 			.tasks()
 			.list_tasks()
+			.expand(&["linkedResources"])
 			.paging()
 			.stream::<serde_json::Value>()?
-			.filter_map(|response| async move {
-				match response {
-					Ok(response) => {
-						let value = response.into_body().ok()?;
-						let tasks: Vec<serde_json::Value> =
-							value["value"].as_array().cloned()?;
+			.filter_map(move |response| {
+				let parent_list = parent_list.clone();
+				let estimates = estimates.clone();
+				let planned = planned.clone();
+				async move {
+					match response {
+						Ok(response) => {
+							let value = response.into_body().ok()?;
+							let tasks: Vec<serde_json::Value> =
+								value["value"].as_array().cloned()?;
 
-						let task_list = tasks
-							.iter()
-							.flat_map(|t| serde_json::from_value(t.clone()).ok())
-							.flat_map(|t: TodoTask| Some(t.into()))
-							.collect::<Vec<Task>>();
+							let task_list = tasks
+								.iter()
+								.flat_map(|t| serde_json::from_value(t.clone()).ok())
+								.filter_map(|t: TodoTask| convert_task(t, &parent_list))
+								.map(|mut task| {
+									task.estimate = estimates.get(&task.id).copied();
+									task.today = planned.contains(&task.id);
+									task
+								})
+								.collect::<Vec<Task>>();
 
-						Some(task_list)
-					},
-					Err(err) => {
-						tracing::error!("There was an error getting the tasks: {}", err);
-						None
-					},
+							Some(task_list)
+						},
+						Err(err) => {
+							tracing::error!("There was an error getting the tasks: {}", err);
+							None
+						},
+					}
 				}
 			})
 			.flat_map(futures::stream::iter)
@@ -257,68 +731,131 @@ impl TodoProvider for MicrosoftService {
 		task_id: String,
 	) -> Result<Task> {
 		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(task_list_id.clone())
-			.task(task_id)
-			.get_tasks()
-			.send()
-			.await?;
-		let task: TodoTask = response.json().await?;
-		let mut task: Task = task.clone().into();
+		let task = self.read_todo_task(&task_list_id, &task_id).await?;
+		let mut task: Task = task.try_into()?;
 		task.parent = task_list_id;
+		self.overlay.apply_overlay(std::slice::from_mut(&mut task))?;
 		Ok(task)
 	}
 
-	async fn create_task(&mut self, task: Task) -> Result<()> {
+	async fn create_task(&mut self, task: Task) -> Result<Task> {
 		self.refresh_token().await?;
-		let todo_task: TodoTask = task.clone().into();
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(task.parent)
-			.tasks()
-			.create_tasks(&serde_json::json!(todo_task))
-			.send()
-			.await?;
+		if self.read_list(task.parent.clone()).await?.read_only() {
+			bail!("Tasks can't be added to this list")
+		}
+		let mut todo_task: TodoTask = task.clone().into();
+		// Checklist items are added once the task exists, like on updates.
+		let checklist_items = todo_task.checklist_items.take().unwrap_or_default();
+		let response = create_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(task.parent.clone())
+				.tasks()
+				.create_tasks(&serde_json::json!(todo_task))
+				.send()
+		})
+		.await?;
 
-		if response.status() == StatusCode::CREATED {
-			Ok(())
-		} else {
+		if response.status() != StatusCode::CREATED {
 			bail!("An error ocurred while creating the task.")
 		}
+		let created: TodoTask = response.json().await?;
+		let mut created: Task = created.try_into()?;
+		created.parent = task.parent;
+		created.estimate = task.estimate;
+		created.today = task.today;
+		created.linked_resources = task.linked_resources;
+		if created.estimate.is_some() || created.today {
+			self.overlay.save_overlay(&created)?;
+		}
+		if !checklist_items.is_empty() {
+			created.sub_tasks = sync_checklist_items(
+				&self.client,
+				&created.parent,
+				&created.id,
+				&checklist_items,
+			)
+			.await?
+			.into_iter()
+			.map(TryInto::try_into)
+			.collect::<Result<Vec<Task>>>()?;
+		}
+		Ok(created)
 	}
 
 	async fn update_task(&mut self, task: Task) -> Result<Task> {
 		self.refresh_token().await?;
 		let mut todo_task: TodoTask = task.clone().into();
-		self
-			.update_check_list_items(
-				&task.parent,
-				&task.id,
-				&todo_task.checklist_items,
-			)
-			.await?;
-		todo_task.checklist_items = None;
-		println!("{}", serde_json::json!(todo_task));
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(task.parent)
-			.task(todo_task.id.clone())
-			.update_tasks(&serde_json::json!(todo_task))
-			.send()
-			.await?;
+		let checklist_items = sync_checklist_items(
+			&self.client,
+			&task.parent,
+			&task.id,
+			&todo_task.checklist_items.take().unwrap_or_default(),
+		)
+		.await?;
+		// The task as Graph has it, so links and notes are only written when
+		// they changed.
+		let current = self
+			.read_todo_task(&task.parent, &task.id)
+			.await
+			.inspect_err(|err| {
+				tracing::error!("Failed to read task {}: {err:#}", task.id)
+			})
+			.ok();
+		// Links are stored apart from the task, a failure doesn't stop the
+		// rest from being saved.
+		if let Some(current) = &current {
+			let remote = current.linked_resources.clone().unwrap_or_default();
+			if links_changed(&remote, &task.linked_resources) {
+				if let Err(err) = self
+					.sync_linked_resources(
+						&task.parent,
+						&task.id,
+						&remote,
+						&task.linked_resources,
+					)
+					.await
+				{
+					tracing::error!("Failed to save the links of {}: {err:#}", task.id)
+				}
+			}
+		}
+		todo_task.linked_resources = None;
+		let mut patch = serde_json::json!(todo_task);
+		// Rendering the notes back to HTML loses the formatting of bodies
+		// written in Outlook, so the body is only sent when they were edited.
+		if current.is_some_and(|current| current.body.to_markdown() == task.notes) {
+			if let Some(patch) = patch.as_object_mut() {
+				patch.remove("body");
+			}
+		}
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(task.parent.clone())
+				.task(todo_task.id.clone())
+				.update_tasks(&patch)
+				.send()
+		})
+		.await?;
 
 		let status = response.status();
 		match response.error_for_status() {
 			Ok(response) => {
-				let task: TodoTask = response.json().await?;
-				Ok(task.into())
+				self.overlay.save_overlay(&task)?;
+				let updated: TodoTask = response.json().await?;
+				let mut updated: Task = updated.try_into()?;
+				updated.parent = task.parent;
+				updated.estimate = task.estimate;
+				updated.today = task.today;
+				updated.linked_resources = task.linked_resources;
+				updated.sub_tasks = checklist_items
+					.into_iter()
+					.map(TryInto::try_into)
+					.collect::<Result<Vec<Task>>>()?;
+				Ok(updated)
 			},
 			Err(err) => {
 				bail!("An error ocurred while updating the list: {err}")
@@ -332,34 +869,46 @@ impl TodoProvider for MicrosoftService {
 		task_id: String,
 	) -> Result<()> {
 		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(list_id)
-			.task(task_id)
-			.delete_tasks()
-			.send()
-			.await?;
-		if response.status() == StatusCode::NO_CONTENT {
-			Ok(())
-		} else {
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(&list_id)
+				.task(task_id.clone())
+				.delete_tasks()
+				.send()
+		})
+		.await?;
+		if response.status() != StatusCode::NO_CONTENT {
 			bail!("An error ocurred while deleting the task.")
 		}
+		self.overlay.remove_overlay(&task_id)?;
+		self.overlay.remove_task_dependencies(&task_id)?;
+		self.overlay.remove_task_time_entries(&task_id)
 	}
 
 	async fn read_lists(&mut self) -> Result<Vec<List>> {
 		self.refresh_token().await?;
-		let response = self.client.me().todo().lists().list_lists().send().await?;
+		let response =
+			send_with_retry(|| self.client.me().todo().lists().list_lists().send())
+				.await?;
 
 		let lists: Collection<TodoTaskList> = response.json().await?;
-		Ok(lists.value.iter().map(|t| t.clone().into()).collect())
+		let service = self.service();
+		Ok(
+			lists
+				.value
+				.into_iter()
+				.map(|list| List::from_todo_list(list, service))
+				.collect(),
+		)
 	}
 
 	async fn get_lists(
 		&mut self,
 	) -> Result<Pin<Box<dyn Stream<Item = List> + Send>>> {
 		self.refresh_token().await?;
+		let service = self.service();
 		let mut stream = self
 			.client
 			.me()
@@ -368,7 +917,7 @@ impl TodoProvider for MicrosoftService {
 			.list_lists()
 			.paging()
 			.stream::<serde_json::Value>()?
-			.filter_map(|response| async move {
+			.filter_map(move |response| async move {
 				match response {
 					Ok(response) => {
 						let value = response.into_body().ok()?;
@@ -378,7 +927,7 @@ impl TodoProvider for MicrosoftService {
 						let list = lists
 							.iter()
 							.flat_map(|t| serde_json::from_value(t.clone()).ok())
-							.flat_map(|t: TodoTaskList| Some(t.into()))
+							.map(|t: TodoTaskList| List::from_todo_list(t, service))
 							.collect::<Vec<List>>();
 
 						Some(list)
@@ -397,27 +946,30 @@ impl TodoProvider for MicrosoftService {
 
 	async fn read_list(&mut self, id: String) -> Result<List> {
 		self.refresh_token().await?;
-		let response = self.client.me().todo().list(id).get_lists().send().await?;
+		let response =
+			send_with_retry(|| self.client.me().todo().list(&id).get_lists().send())
+				.await?;
 		let list: TodoTaskList = response.json().await?;
-		Ok(list.into())
+		Ok(List::from_todo_list(list, self.service()))
 	}
 
 	async fn create_list(&mut self, list: List) -> Result<List> {
 		self.refresh_token().await?;
 		let list: TodoTaskList = list.into();
 		println!("{}", serde_json::json!(list));
-		let response = self
-			.client
-			.me()
-			.todo()
-			.lists()
-			.create_lists(&serde_json::json!(list))
-			.send()
-			.await?;
+		let response = create_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.lists()
+				.create_lists(&serde_json::json!(list))
+				.send()
+		})
+		.await?;
 		match response.error_for_status() {
 			Ok(response) => {
 				let list: TodoTaskList = response.json().await?;
-				Ok(list.into())
+				Ok(List::from_todo_list(list, self.service()))
 			},
 			Err(err) => bail!("An error ocurred while creating the list: {err}"),
 		}
@@ -426,14 +978,15 @@ impl TodoProvider for MicrosoftService {
 	async fn update_list(&mut self, list: List) -> Result<()> {
 		self.refresh_token().await?;
 		let list: TodoTaskList = list.into();
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(list.id.clone())
-			.update_lists(&serde_json::json!(list))
-			.send()
-			.await?;
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(list.id.clone())
+				.update_lists(&serde_json::json!(list))
+				.send()
+		})
+		.await?;
 
 		if response.status() == StatusCode::OK {
 			Ok(())
@@ -444,18 +997,172 @@ impl TodoProvider for MicrosoftService {
 
 	async fn delete_list(&mut self, id: String) -> Result<()> {
 		self.refresh_token().await?;
-		let response = self
-			.client
-			.me()
-			.todo()
-			.list(id)
-			.delete_lists()
-			.send()
-			.await?;
+		if !self.read_list(id.clone()).await?.can_delete() {
+			bail!("This list can't be deleted")
+		}
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(&id)
+				.delete_lists()
+				.send()
+		})
+		.await?;
 
 		match response.error_for_status() {
 			Ok(_) => Ok(()),
 			Err(err) => bail!("An error ocurred while deleting the list: {err}"),
 		}
 	}
+
+	/// Microsoft To Do has list groups, but Graph doesn't expose them.
+	fn list_groups_support(&self) -> bool {
+		false
+	}
+
+	async fn read_list_groups(&mut self) -> Result<Vec<ListGroup>> {
+		Ok(vec![])
+	}
+
+	async fn create_list_group(&mut self, group: ListGroup) -> Result<ListGroup> {
+		bail!("Microsoft Graph doesn't support list groups")
+	}
+
+	async fn update_list_group(&mut self, group: ListGroup) -> Result<()> {
+		bail!("Microsoft Graph doesn't support list groups")
+	}
+
+	async fn delete_list_group(&mut self, id: String) -> Result<()> {
+		bail!("Microsoft Graph doesn't support list groups")
+	}
+
+	async fn read_attachments(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+	) -> Result<Vec<Attachment>> {
+		self.refresh_token().await?;
+		self.list_attachments(&task_list_id, &task_id).await
+	}
+
+	async fn add_attachment(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+		path: PathBuf,
+	) -> Result<Attachment> {
+		self.refresh_token().await?;
+		let attachment = Attachment::from_path(task_id.clone(), &path)?;
+		if attachment.size > MAX_ATTACHMENT {
+			bail!("{} is larger than 25 MB", attachment.name)
+		}
+		let bytes = tokio::fs::read(&path)
+			.await
+			.with_context(|| format!("Failed to read {}", path.display()))?;
+		if attachment.size > MAX_INLINE_ATTACHMENT {
+			self
+				.upload_attachment_in_session(
+					&task_list_id,
+					&task_id,
+					&attachment,
+					bytes,
+				)
+				.await
+		} else {
+			self
+				.upload_attachment(&task_list_id, &task_id, &attachment, bytes)
+				.await
+		}
+	}
+
+	async fn open_attachment(
+		&mut self,
+		task_list_id: String,
+		attachment: Attachment,
+	) -> Result<PathBuf> {
+		let directory =
+			attachments_dir(&self.application_id, AttachmentStore::Cache)?;
+		let path = attachment.path_in(&directory)?;
+		if path.exists() {
+			return Ok(path);
+		}
+
+		self.refresh_token().await?;
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(&task_list_id)
+				.task(attachment.task_id.clone())
+				.get_attachments_content(attachment.id.clone())
+				.send()
+		})
+		.await?;
+		let bytes = match response.error_for_status() {
+			Ok(response) => response.bytes().await?,
+			Err(err) => bail!("An error ocurred while downloading the file: {err}"),
+		};
+		if let Some(parent) = path.parent() {
+			tokio::fs::create_dir_all(parent).await?;
+		}
+		tokio::fs::write(&path, bytes).await?;
+		Ok(path)
+	}
+
+	async fn remove_attachment(
+		&mut self,
+		task_list_id: String,
+		attachment: Attachment,
+	) -> Result<()> {
+		self.refresh_token().await?;
+		let response = send_with_retry(|| {
+			self.client
+				.me()
+				.todo()
+				.list(&task_list_id)
+				.task(attachment.task_id.clone())
+				.delete_attachments(attachment.id.clone())
+				.send()
+		})
+		.await?;
+		match response.error_for_status() {
+			Ok(_) => Ok(()),
+			Err(err) => bail!("An error ocurred while removing the file: {err}"),
+		}
+	}
+}
+
+
+/// Converts a Graph task into a [`Task`], logging and skipping payloads that
+/// can't be represented locally.
+fn convert_task(task: TodoTask, parent_list: &str) -> Option<Task> {
+	let id = task.id.clone();
+	match Task::try_from(task) {
+		Ok(mut task) => {
+			task.parent = parent_list.to_string();
+			Some(task)
+		},
+		Err(err) => {
+			tracing::error!("Skipping task {id} with an unexpected payload: {err:#}");
+			None
+		},
+	}
+}
+
+/// Whether the links of a task differ from the ones Graph has, by URL.
+fn links_changed(
+	remote: &[TodoLinkedResource],
+	linked_resources: &[LinkedResource],
+) -> bool {
+	let remote: HashSet<&str> =
+		remote.iter().filter_map(|link| link.web_url.as_deref()).collect();
+	let local: HashSet<&str> =
+		linked_resources.iter().map(|link| link.web_url.as_str()).collect();
+	remote != local
+}
+
+/// The credential store namespace of an account.
+fn namespace(account: Uuid) -> String {
+	format!("{PROVIDER}/{account}")
 }
@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{Duration, Utc};
+use graph_rs_sdk::oauth::AccessToken;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use uuid::Uuid;
+
+/// Tokens are refreshed this long before they expire, so requests never
+/// start with a token that runs out while they're in flight.
+const REFRESH_MARGIN: Duration = Duration::minutes(5);
+
+/// One lock per account, see [`refresh_lock`].
+static REFRESHING: Mutex<BTreeMap<Uuid, Arc<AsyncMutex<()>>>> =
+	Mutex::new(BTreeMap::new());
+
+/// Whether the token expires soon, tokens without an expiry are refreshed.
+pub(crate) fn needs_refresh(token: &AccessToken) -> bool {
+	token
+		.timestamp()
+		.map_or(true, |expiry| expiry - Utc::now() < REFRESH_MARGIN)
+}
+
+/// Serializes the refreshes of an account. Refresh tokens are rotated, when
+/// two services refresh at once one of them would end up with a spent one.
+pub(crate) async fn refresh_lock(account: Uuid) -> OwnedMutexGuard<()> {
+	let lock = REFRESHING
+		.lock()
+		.unwrap()
+		.entry(account)
+		.or_default()
+		.clone();
+	lock.lock_owned().await
+}
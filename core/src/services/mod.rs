@@ -1,3 +1,3 @@
 pub mod local;
-pub(crate) mod microsoft;
+pub mod microsoft;
 pub(crate) mod smart;
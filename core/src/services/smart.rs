@@ -1,10 +1,17 @@
+use std::path::PathBuf;
 use std::pin::Pin;
 
 use crate::{
-	models::{list::List, task::Task},
+	models::{
+		attachment::Attachment,
+		list::List,
+		list_group::ListGroup,
+		login::{Login, LoginPrompter},
+		task::Task,
+	},
 	task_service::TodoProvider,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use futures::Stream;
 use url::Url;
@@ -25,8 +32,8 @@ impl TodoProvider for Smart {
 		Ok(())
 	}
 
-	fn login(&self) -> anyhow::Result<()> {
-		Ok(())
+	async fn login(&mut self, _prompt: LoginPrompter) -> Result<Login> {
+		Ok(Login::Completed)
 	}
 
 	fn logout(&self) -> anyhow::Result<()> {
@@ -67,8 +74,8 @@ impl TodoProvider for Smart {
 		Ok(Task::default())
 	}
 
-	async fn create_task(&mut self, task: Task) -> Result<()> {
-		Ok(())
+	async fn create_task(&mut self, task: Task) -> Result<Task> {
+		Ok(task)
 	}
 
 	async fn update_task(&mut self, task: Task) -> Result<Task> {
@@ -108,4 +115,57 @@ impl TodoProvider for Smart {
 	async fn delete_list(&mut self, id: String) -> Result<()> {
 		Ok(())
 	}
+
+	fn list_groups_support(&self) -> bool {
+		false
+	}
+
+	async fn read_list_groups(&mut self) -> Result<Vec<ListGroup>> {
+		Ok(vec![])
+	}
+
+	async fn create_list_group(&mut self, group: ListGroup) -> Result<ListGroup> {
+		bail!("Smart lists can't be grouped")
+	}
+
+	async fn update_list_group(&mut self, group: ListGroup) -> Result<()> {
+		bail!("Smart lists can't be grouped")
+	}
+
+	async fn delete_list_group(&mut self, id: String) -> Result<()> {
+		Ok(())
+	}
+
+	async fn read_attachments(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+	) -> Result<Vec<Attachment>> {
+		Ok(vec![])
+	}
+
+	async fn add_attachment(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+		path: PathBuf,
+	) -> Result<Attachment> {
+		bail!("Smart lists do not store attachments")
+	}
+
+	async fn open_attachment(
+		&mut self,
+		task_list_id: String,
+		attachment: Attachment,
+	) -> Result<PathBuf> {
+		bail!("Smart lists do not store attachments")
+	}
+
+	async fn remove_attachment(
+		&mut self,
+		task_list_id: String,
+		attachment: Attachment,
+	) -> Result<()> {
+		Ok(())
+	}
 }
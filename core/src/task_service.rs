@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::pin::Pin;
 
 use anyhow::Result;
@@ -5,15 +6,22 @@ use async_trait::async_trait;
 use futures::Stream;
 use url::Url;
 
-use crate::models::{list::List, task::Task};
+use crate::models::{
+	attachment::Attachment,
+	list::List,
+	list_group::ListGroup,
+	login::{Login, LoginPrompter},
+	task::Task,
+};
 
 #[async_trait]
 pub trait TodoProvider: Sync + Send {
 	/// Sets the initial config for this service.
 	async fn handle_uri_params(&mut self, uri: Url) -> Result<()>;
 
-	/// Handles the login action.
-	fn login(&self) -> Result<()>;
+	/// Handles the login action, `prompt` shows the user what to do when
+	/// the sign-in needs their input.
+	async fn login(&mut self, prompt: LoginPrompter) -> Result<Login>;
 
 	/// Handles the logout action.
 	fn logout(&self) -> Result<()>;
@@ -46,8 +54,9 @@ pub trait TodoProvider: Sync + Send {
 		task_id: String,
 	) -> Result<Task>;
 
-	/// Creates a single task.
-	async fn create_task(&mut self, task: Task) -> Result<()>;
+	/// Creates a single task and returns it as it was stored, with the id
+	/// the service assigned to it.
+	async fn create_task(&mut self, task: Task) -> Result<Task>;
 
 	/// Updates a single task.
 	async fn update_task(&mut self, task: Task) -> Result<Task>;
@@ -78,4 +87,49 @@ pub trait TodoProvider: Sync + Send {
 
 	/// Deletes a single task list.
 	async fn delete_list(&mut self, id: String) -> Result<()>;
+
+	/// Checks to see if lists can be sorted into groups.
+	fn list_groups_support(&self) -> bool;
+
+	/// Read the groups of lists, ordered by position.
+	async fn read_list_groups(&mut self) -> Result<Vec<ListGroup>>;
+
+	/// Creates a single group of lists.
+	async fn create_list_group(&mut self, group: ListGroup) -> Result<ListGroup>;
+
+	/// Updates the name, position or expand state of a group.
+	async fn update_list_group(&mut self, group: ListGroup) -> Result<()>;
+
+	/// Deletes a group, its lists are kept outside of any group.
+	async fn delete_list_group(&mut self, id: String) -> Result<()>;
+
+	/// Reads the files attached to a task.
+	async fn read_attachments(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+	) -> Result<Vec<Attachment>>;
+
+	/// Attaches the file at `path` to a task.
+	async fn add_attachment(
+		&mut self,
+		task_list_id: String,
+		task_id: String,
+		path: PathBuf,
+	) -> Result<Attachment>;
+
+	/// Returns a local path to the attachment's file, downloading it first
+	/// if needed.
+	async fn open_attachment(
+		&mut self,
+		task_list_id: String,
+		attachment: Attachment,
+	) -> Result<PathBuf>;
+
+	/// Removes an attachment from its task.
+	async fn remove_attachment(
+		&mut self,
+		task_list_id: String,
+		attachment: Attachment,
+	) -> Result<()>;
 }
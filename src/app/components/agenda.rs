@@ -0,0 +1,350 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use relm4::component::{AsyncComponent, AsyncComponentParts, AsyncComponentSender};
+use relm4::gtk::traits::{ButtonExt, ToggleButtonExt};
+use relm4::{
+    RelmWidgetExt,
+    gtk::{
+        self, gdk,
+        prelude::{BoxExt, GridExt, OrientableExt, StaticType, ToValue, WidgetExt},
+    },
+};
+use relm4_icons::icon_name;
+
+use done_core::models::agenda::{AgendaEntry, AgendaEntryKind, agenda_entries};
+use done_core::models::dates::{all_day, from_local, local_today, to_local};
+use done_core::models::task::Task;
+use done_core::service::Service;
+
+use crate::fl;
+
+/// Number of entries shown in a day of the month view before collapsing them.
+const MONTH_CELL_ENTRIES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgendaMode {
+    Month,
+    Week,
+}
+
+pub struct AgendaModel {
+    mode: AgendaMode,
+    anchor: NaiveDate,
+    tasks: Vec<(Service, Task)>,
+    loading: bool,
+}
+
+#[derive(Debug)]
+pub enum AgendaInput {
+    Reload,
+    Previous,
+    Next,
+    Today,
+    SetMode(AgendaMode),
+    Reschedule(String, NaiveDate),
+}
+
+#[derive(Debug)]
+pub enum AgendaOutput {
+    Error(String),
+}
+
+#[relm4::component(pub async)]
+impl AsyncComponent for AgendaModel {
+    type CommandOutput = ();
+    type Input = AgendaInput;
+    type Output = AgendaOutput;
+    type Init = ();
+
+    view! {
+        #[root]
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            set_margin_all: 10,
+            set_spacing: 10,
+            gtk::Box {
+                set_spacing: 10,
+                gtk::Box {
+                    set_css_classes: &["linked"],
+                    gtk::Button {
+                        set_icon_name: icon_name::ARROW1_LEFT,
+                        set_tooltip: fl!("previous"),
+                        connect_clicked => AgendaInput::Previous,
+                    },
+                    gtk::Button {
+                        set_label: fl!("today"),
+                        connect_clicked => AgendaInput::Today,
+                    },
+                    gtk::Button {
+                        set_icon_name: icon_name::ARROW1_RIGHT,
+                        set_tooltip: fl!("next"),
+                        connect_clicked => AgendaInput::Next,
+                    },
+                },
+                gtk::Label {
+                    set_css_classes: &["title-3"],
+                    set_hexpand: true,
+                    #[watch]
+                    set_text: &model.title(),
+                },
+                gtk::Spinner {
+                    #[watch]
+                    set_spinning: model.loading,
+                },
+                gtk::Box {
+                    set_css_classes: &["linked"],
+                    #[name(month_button)]
+                    gtk::ToggleButton {
+                        set_label: fl!("month"),
+                        #[watch]
+                        set_active: model.mode == AgendaMode::Month,
+                        connect_toggled[sender] => move |toggle| {
+                            if toggle.is_active() {
+                                sender.input(AgendaInput::SetMode(AgendaMode::Month));
+                            }
+                        },
+                    },
+                    gtk::ToggleButton {
+                        set_label: fl!("week"),
+                        set_group: Some(&month_button),
+                        #[watch]
+                        set_active: model.mode == AgendaMode::Week,
+                        connect_toggled[sender] => move |toggle| {
+                            if toggle.is_active() {
+                                sender.input(AgendaInput::SetMode(AgendaMode::Week));
+                            }
+                        },
+                    },
+                },
+            },
+            gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hexpand: true,
+                #[name(grid)]
+                gtk::Grid {
+                    set_row_homogeneous: true,
+                    set_column_homogeneous: true,
+                    set_row_spacing: 5,
+                    set_column_spacing: 5,
+                },
+            },
+        }
+    }
+
+    async fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: AsyncComponentSender<Self>,
+    ) -> AsyncComponentParts<Self> {
+        let model = AgendaModel {
+            mode: AgendaMode::Month,
+            anchor: local_today(),
+            tasks: vec![],
+            loading: false,
+        };
+
+        let widgets = view_output!();
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: AsyncComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            AgendaInput::Reload => {
+                self.loading = true;
+                self.update_view(widgets, sender.clone());
+                self.tasks = Service::all_tasks().await;
+                self.loading = false;
+            }
+            AgendaInput::Previous => self.anchor = self.shift(-1),
+            AgendaInput::Next => self.anchor = self.shift(1),
+            AgendaInput::Today => self.anchor = local_today(),
+            AgendaInput::SetMode(mode) => self.mode = mode,
+            AgendaInput::Reschedule(payload, day) => {
+                if let Err(err) = self.reschedule(&payload, day).await {
+                    tracing::error!("{err}");
+                    sender
+                        .output(AgendaOutput::Error(err.to_string()))
+                        .unwrap_or_default();
+                }
+            }
+        }
+        self.fill_grid(&widgets.grid, &sender);
+        self.update_view(widgets, sender)
+    }
+}
+
+impl AgendaModel {
+    /// First and last day displayed by the current mode.
+    fn range(&self) -> (NaiveDate, NaiveDate) {
+        let start = match self.mode {
+            AgendaMode::Month => self.anchor.with_day(1).unwrap_or(self.anchor),
+            AgendaMode::Week => self.anchor,
+        };
+        let start = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+        let days = match self.mode {
+            AgendaMode::Month => 42,
+            AgendaMode::Week => 7,
+        };
+        (start, start + Duration::days(days - 1))
+    }
+
+    fn shift(&self, direction: i32) -> NaiveDate {
+        match self.mode {
+            AgendaMode::Week => self.anchor + Duration::weeks(direction as i64),
+            AgendaMode::Month => {
+                let month = self.anchor.month0() as i32 + direction;
+                let year = self.anchor.year() + month.div_euclid(12);
+                NaiveDate::from_ymd_opt(year, month.rem_euclid(12) as u32 + 1, 1)
+                    .unwrap_or(self.anchor)
+            }
+        }
+    }
+
+    fn title(&self) -> String {
+        match self.mode {
+            AgendaMode::Month => self.anchor.format("%B %Y").to_string(),
+            AgendaMode::Week => {
+                let (start, end) = self.range();
+                format!("{} – {}", start.format("%b %d"), end.format("%b %d %Y"))
+            }
+        }
+    }
+
+    fn fill_grid(&self, grid: &gtk::Grid, sender: &AsyncComponentSender<Self>) {
+        while let Some(child) = grid.first_child() {
+            grid.remove(&child);
+        }
+
+        let (start, end) = self.range();
+        let entries = agenda_entries(&self.tasks, start, end);
+        let today = local_today();
+
+        let mut day = start;
+        let mut position = 0;
+        while day <= end {
+            let cell = gtk::Box::new(gtk::Orientation::Vertical, 3);
+            cell.add_css_class("card");
+            cell.set_height_request(match self.mode {
+                AgendaMode::Month => 100,
+                AgendaMode::Week => 400,
+            });
+
+            let label = gtk::Label::new(Some(&day.format("%a %d").to_string()));
+            label.set_halign(gtk::Align::Start);
+            label.set_margin_all(5);
+            label.add_css_class("caption-heading");
+            if day == today {
+                label.add_css_class("accent");
+            }
+            if self.mode == AgendaMode::Month && day.month() != self.anchor.month() {
+                cell.add_css_class("dim-label");
+            }
+            cell.append(&label);
+
+            let day_entries: Vec<&AgendaEntry> =
+                entries.iter().filter(|entry| entry.date == day).collect();
+            let shown = match self.mode {
+                AgendaMode::Month => MONTH_CELL_ENTRIES,
+                AgendaMode::Week => day_entries.len(),
+            };
+            for entry in day_entries.iter().take(shown) {
+                cell.append(&entry_widget(entry));
+            }
+            if day_entries.len() > shown {
+                let more = gtk::Label::new(Some(&format!("+{}", day_entries.len() - shown)));
+                more.add_css_class("caption");
+                more.add_css_class("dim-label");
+                more.set_tooltip_text(Some(
+                    &day_entries
+                        .iter()
+                        .skip(shown)
+                        .map(|entry| entry.task.title.as_str())
+                        .collect::<Vec<&str>>()
+                        .join("\n"),
+                ));
+                cell.append(&more);
+            }
+
+            let drop_target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+            {
+                let sender = sender.clone();
+                drop_target.connect_drop(move |_, value, _, _| match value.get::<String>() {
+                    Ok(payload) => {
+                        sender.input(AgendaInput::Reschedule(payload, day));
+                        true
+                    }
+                    Err(_) => false,
+                });
+            }
+            cell.add_controller(drop_target);
+
+            grid.attach(&cell, position % 7, position / 7, 1, 1);
+            position += 1;
+            day += Duration::days(1);
+        }
+    }
+
+    async fn reschedule(&mut self, payload: &str, day: NaiveDate) -> anyhow::Result<()> {
+        let Some((kind, task_id)) = payload.split_once(':') else {
+            return Ok(());
+        };
+        let Some((service, task)) = self.tasks.iter_mut().find(|(_, task)| task.id == task_id)
+        else {
+            return Ok(());
+        };
+        match AgendaEntryKind::from_str_name(kind) {
+            Some(AgendaEntryKind::Due) => task.due_date = Some(all_day(day)),
+            Some(AgendaEntryKind::Reminder) => {
+                if let Some(current) = task.reminder_date {
+                    let time = to_local(current).time();
+                    task.reminder_date = Some(from_local(day.and_time(time)));
+                }
+            }
+            _ => return Ok(()),
+        }
+        service.get_service().update_task(task.clone()).await?;
+        Ok(())
+    }
+}
+
+fn entry_widget(entry: &AgendaEntry) -> gtk::Box {
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 3);
+    row.set_margin_start(5);
+    row.set_margin_end(5);
+    row.set_tooltip_text(Some(&format!("{} · {}", entry.task.title, entry.service)));
+    row.add_css_class("caption");
+    let icon = match entry.kind {
+        AgendaEntryKind::Due => None,
+        AgendaEntryKind::Reminder => Some(icon_name::ALARM),
+        AgendaEntryKind::Occurrence => Some(icon_name::ARROW_REPEAT_ALL_REGULAR),
+    };
+    if let Some(icon) = icon {
+        row.append(&gtk::Image::from_icon_name(icon));
+    }
+    let label = gtk::Label::new(Some(&entry.task.title));
+    label.set_halign(gtk::Align::Start);
+    label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    row.append(&label);
+    if entry.task.status.is_finished() {
+        row.add_css_class("dim-label");
+    }
+
+    // Projected occurrences only exist on screen, there is nothing to move.
+    if entry.kind != AgendaEntryKind::Occurrence {
+        let drag_source = gtk::DragSource::new();
+        drag_source.set_actions(gdk::DragAction::MOVE);
+        let payload = format!("{}:{}", entry.kind.as_str_name(), entry.task.id);
+        drag_source
+            .connect_prepare(move |_, _, _| Some(gdk::ContentProvider::for_value(&payload.to_value())));
+        row.add_controller(drag_source);
+    } else {
+        row.add_css_class("dim-label");
+    }
+    row
+}
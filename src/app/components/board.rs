@@ -0,0 +1,210 @@
+use relm4::factory::FactoryVecDeque;
+use relm4::{
+    Component, ComponentParts, ComponentSender, RelmWidgetExt, gtk,
+    gtk::prelude::{BoxExt, OrientableExt, WidgetExt},
+};
+
+use done_core::models::task::Task;
+
+use crate::app::config::preferences::Preferences;
+use crate::app::factories::board_column::{
+    BoardColumnInit, BoardColumnModel, BoardColumnOutput,
+};
+use crate::app::models::board::BoardGrouping;
+
+#[derive(Debug)]
+pub struct BoardModel {
+    tasks: Vec<Task>,
+    /// Key of the sidebar list shown, WIP limits are kept per list.
+    list: String,
+    grouping: BoardGrouping,
+    columns: FactoryVecDeque<BoardColumnModel>,
+}
+
+#[derive(Debug)]
+pub enum BoardInput {
+    AddTask(Task),
+    /// Replaces a task with the version the service saved.
+    ReplaceTask(Task),
+    Clear,
+    SetList(String),
+    SetGrouping(BoardGrouping),
+    SelectGrouping(u32),
+    MoveTask(usize, String, usize),
+    SetLimit(String, u32),
+}
+
+#[derive(Debug)]
+pub enum BoardOutput {
+    UpdateTask(Task),
+    GroupingChanged(BoardGrouping),
+    ColumnFull(String),
+}
+
+#[relm4::component(pub)]
+impl Component for BoardModel {
+    type Input = BoardInput;
+    type Output = BoardOutput;
+    type Init = ();
+    type CommandOutput = ();
+
+    view! {
+        #[root]
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            set_spacing: 10,
+            gtk::Box {
+                set_halign: gtk::Align::End,
+                set_margin_end: 10,
+                gtk::DropDown {
+                    set_model: Some(&gtk::StringList::new(
+                        &BoardGrouping::ALL
+                            .map(|grouping| grouping.name())
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<&str>>(),
+                    )),
+                    #[watch]
+                    set_selected: BoardGrouping::ALL
+                        .iter()
+                        .position(|grouping| *grouping == model.grouping)
+                        .unwrap_or_default() as u32,
+                    connect_selected_notify[sender] => move |dropdown| {
+                        sender.input(BoardInput::SelectGrouping(dropdown.selected()));
+                    },
+                },
+            },
+            gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hexpand: true,
+                set_vscrollbar_policy: gtk::PolicyType::Never,
+                #[local_ref]
+                columns -> gtk::Box {
+                    set_spacing: 15,
+                    set_margin_all: 5,
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = BoardModel {
+            tasks: vec![],
+            list: String::new(),
+            grouping: BoardGrouping::default(),
+            columns: FactoryVecDeque::builder()
+                .launch(gtk::Box::default())
+                .forward(sender.input_sender(), |output| match output {
+                    BoardColumnOutput::Move(from, task_id, to) => {
+                        BoardInput::MoveTask(from, task_id, to)
+                    }
+                    BoardColumnOutput::SetLimit(key, limit) => BoardInput::SetLimit(key, limit),
+                }),
+        };
+
+        let columns = model.columns.widget();
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
+        match message {
+            BoardInput::AddTask(task) => {
+                self.tasks.push(task);
+                self.rebuild();
+            }
+            BoardInput::ReplaceTask(task) => {
+                if let Some(shown) = self.tasks.iter_mut().find(|shown| shown.id == task.id) {
+                    *shown = task;
+                    self.rebuild();
+                }
+            }
+            BoardInput::Clear => {
+                self.tasks.clear();
+                self.rebuild();
+            }
+            BoardInput::SetList(list) => self.list = list,
+            BoardInput::SetGrouping(grouping) => {
+                self.grouping = grouping;
+                self.rebuild();
+            }
+            BoardInput::SelectGrouping(position) => {
+                if let Some(grouping) = BoardGrouping::ALL.get(position as usize) {
+                    if *grouping != self.grouping {
+                        self.grouping = *grouping;
+                        self.rebuild();
+                        sender
+                            .output(BoardOutput::GroupingChanged(*grouping))
+                            .unwrap_or_default();
+                    }
+                }
+            }
+            BoardInput::MoveTask(from, task_id, to) => {
+                if from == to {
+                    return;
+                }
+                let (Some(source), Some(target)) = (self.columns.get(from), self.columns.get(to))
+                else {
+                    return;
+                };
+                if target.full() {
+                    sender
+                        .output(BoardOutput::ColumnFull(target.column.name()))
+                        .unwrap_or_default();
+                    return;
+                }
+                let (source, target) = (source.column.clone(), target.column.clone());
+                if let Some(task) = self.tasks.iter_mut().find(|task| task.id == task_id) {
+                    target.apply(task, &source);
+                    sender
+                        .output(BoardOutput::UpdateTask(task.clone()))
+                        .unwrap_or_default();
+                }
+                self.rebuild();
+            }
+            BoardInput::SetLimit(column, limit) => {
+                let key = limit_key(&self.list, &column);
+                if let Err(err) = Preferences::update(|preferences| {
+                    if limit == 0 {
+                        preferences.wip_limits.remove(&key);
+                    } else {
+                        preferences.wip_limits.insert(key, limit);
+                    }
+                }) {
+                    tracing::error!("{err}");
+                }
+            }
+        }
+    }
+}
+
+impl BoardModel {
+    fn rebuild(&mut self) {
+        let limits = Preferences::load().wip_limits;
+        let mut guard = self.columns.guard();
+        guard.clear();
+        for column in self.grouping.columns(&self.tasks) {
+            let tasks: Vec<Task> = self
+                .tasks
+                .iter()
+                .filter(|task| column.contains(task))
+                .cloned()
+                .collect();
+            let limit = limits
+                .get(&limit_key(&self.list, &column.key()))
+                .copied()
+                .unwrap_or_default();
+            guard.push_back(BoardColumnInit::new(column, tasks, limit));
+        }
+    }
+}
+
+/// Where the WIP limit of a column of a sidebar list is stored.
+fn limit_key(list: &str, column: &str) -> String {
+    format!("{list}/{column}")
+}
@@ -1,25 +1,53 @@
-use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
 use futures::StreamExt;
-use relm4::component::{AsyncComponent, AsyncComponentParts, AsyncComponentSender};
+use relm4::component::{
+    AsyncComponent, AsyncComponentController, AsyncComponentParts, AsyncComponentSender,
+    AsyncController,
+};
 use relm4::factory::AsyncFactoryVecDeque;
-use relm4::gtk::traits::ButtonExt;
+use relm4::gtk::traits::{ButtonExt, ToggleButtonExt};
 use relm4::prelude::DynamicIndex;
 use relm4::{Component, ComponentController, Controller, JoinHandle, RelmWidgetExt, tokio};
 use relm4::{
     adw,
     adw::prelude::NavigationPageExt,
     gtk,
-    gtk::prelude::{BoxExt, OrientableExt, WidgetExt},
+    gtk::gio,
+    gtk::prelude::{ApplicationExt, BoxExt, ListBoxRowExt, OrientableExt, WidgetExt},
+    main_adw_application,
 };
 use relm4_icons::icon_name;
 
-use done_core::models::status::Status;
+use done_core::models::dates::{from_local, local_today};
+use done_core::models::dependency::{Dependency, DependencyError};
+use done_core::models::list::List;
 use done_core::models::task::Task;
+use done_core::models::time_entry::TimeEntry;
+use done_core::models::workload::{Workload, format_estimate};
 use done_core::service::Service;
+use done_core::services::local::database::Database;
 
+use crate::app::components::agenda::{AgendaInput, AgendaModel, AgendaOutput};
+use crate::app::components::board::{BoardInput, BoardModel, BoardOutput};
+use crate::app::components::dependency_dialog::{
+    DependencyDialogComponent, DependencyDialogInput, DependencyDialogOutput,
+};
+use crate::app::components::move_dialog::{
+    MoveDialogComponent, MoveDialogInput, MoveDialogOutput,
+};
+use crate::app::components::my_day::{SuggestionsInput, SuggestionsModel, SuggestionsOutput};
 use crate::app::components::task_input::TaskInputOutput;
-use crate::app::factories::task::{TaskInit, TaskInput, TaskModel, TaskOutput};
+use crate::app::config::info::APP_ID;
+use crate::app::config::preferences::Preferences;
+use crate::app::factories::task::{PomodoroPhase, TaskInit, TaskInput, TaskModel, TaskOutput};
+use crate::app::models::board::{BoardGrouping, ListView, ViewMode};
 use crate::app::models::sidebar_list::SidebarList;
+use crate::app::models::sorting::{ListGrouping, SortOrder, TaskGroup};
 use crate::fl;
 
 use super::task_input::{TaskInputInput, TaskInputModel};
@@ -29,10 +57,70 @@ pub struct ContentModel {
     task_factory: AsyncFactoryVecDeque<TaskModel>,
     task_entry: Controller<TaskInputModel>,
     welcome: Controller<WelcomeComponent>,
+    dependency_dialog: Controller<DependencyDialogComponent>,
+    move_dialog: Controller<MoveDialogComponent>,
+    board: Controller<BoardModel>,
+    agenda: AsyncController<AgendaModel>,
+    suggestions: AsyncController<SuggestionsModel>,
+    view: ListView,
+    /// Tasks of the list view with their list, in the order they're shown.
+    shown: Vec<(Task, List)>,
+    /// Section header shown above each row of the list view, if any.
+    headers: Rc<RefCell<Vec<Option<TaskGroup>>>>,
     state: ContentState,
     service: Service,
+    /// Service of every task shown by a smart list, which mixes services.
+    services: HashMap<String, Service>,
     parent_list: Option<SidebarList>,
     handle: Option<JoinHandle<()>>,
+    /// Shared with the task rows so the whole view uses one connection pool.
+    database: Database,
+    local: LocalTaskData,
+    /// Kept here rather than in the rows so it survives the list reloading.
+    pomodoro: Option<Pomodoro>,
+    /// Bumped whenever a Pomodoro is started or cancelled so phases scheduled
+    /// for an earlier one are ignored.
+    pomodoro_generation: u32,
+    /// Minutes of work planned per day before the workload shows a warning.
+    daily_capacity_minutes: u32,
+}
+
+/// The Pomodoro running on a task.
+#[derive(Debug)]
+struct Pomodoro {
+    task_id: String,
+    title: String,
+    phase: PomodoroPhase,
+}
+
+/// Local data of the task rows, read once for the whole view.
+#[derive(Debug, Default)]
+struct LocalTaskData {
+    dependencies: HashMap<String, Vec<Dependency>>,
+    tracked: HashMap<String, Duration>,
+    timer: Option<TimeEntry>,
+}
+
+impl LocalTaskData {
+    fn load(database: &mut Database) -> Self {
+        let dependencies = database.all_dependencies().unwrap_or_else(|err| {
+            tracing::error!("{err}");
+            HashMap::new()
+        });
+        let tracked = database.tracked_times().unwrap_or_else(|err| {
+            tracing::error!("{err}");
+            HashMap::new()
+        });
+        let timer = database.running_timer().unwrap_or_else(|err| {
+            tracing::error!("{err}");
+            None
+        });
+        Self {
+            dependencies,
+            tracked,
+            timer,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -54,6 +142,20 @@ pub enum ContentInput {
     LoadTasks(SidebarList, Service),
     SetState(ContentState),
     ExpandSubTasks(bool),
+    SetDailyCapacity(u32),
+    ChoosePrerequisite(Task),
+    AddDependency(Task, Task),
+    ChooseList(Task),
+    MoveTask(Task, List),
+    SetBoardView(bool),
+    SetGrouping(BoardGrouping),
+    SetSort(u32),
+    SetListGrouping(u32),
+    ColumnFull(String),
+    ToggleTimer(Task),
+    StartPomodoro(Task),
+    ShowError(String),
+    Reload,
     CollapseSidebar,
     Clean,
 }
@@ -63,9 +165,16 @@ pub enum ContentOutput {
     CollapseSidebar,
 }
 
+#[derive(Debug)]
+pub enum ContentCommand {
+    /// Local midnight passed, My Day starts over.
+    NewDay,
+    PomodoroPhaseFinished(u32),
+}
+
 #[relm4::component(pub async)]
 impl AsyncComponent for ContentModel {
-    type CommandOutput = ();
+    type CommandOutput = ContentCommand;
     type Input = ContentInput;
     type Output = ContentOutput;
     type Init = Option<Service>;
@@ -92,6 +201,79 @@ impl AsyncComponent for ContentModel {
                     set_tooltip: fl!("search"),
                     set_icon_name: icon_name::LOUPE,
                 },
+                pack_end = &gtk::MenuButton {
+                    set_tooltip: fl!("sort-and-group"),
+                    set_icon_name: icon_name::TEXT_SORT_DESCENDING_REGULAR,
+                    #[watch]
+                    set_visible: model.view.mode == ViewMode::List
+                        && model.parent_list.is_some()
+                        && model.parent_list != Some(SidebarList::Agenda),
+                    #[wrap(Some)]
+                    set_popover = &gtk::Popover {
+                        gtk::Box {
+                            set_orientation: gtk::Orientation::Vertical,
+                            set_margin_all: 10,
+                            set_spacing: 5,
+                            gtk::Label {
+                                set_css_classes: &["heading"],
+                                set_halign: gtk::Align::Start,
+                                set_text: fl!("sort-by"),
+                            },
+                            gtk::DropDown {
+                                set_model: Some(&gtk::StringList::new(
+                                    &SortOrder::ALL
+                                        .map(|sort| sort.name())
+                                        .iter()
+                                        .map(String::as_str)
+                                        .collect::<Vec<&str>>(),
+                                )),
+                                #[watch]
+                                set_selected: SortOrder::ALL
+                                    .iter()
+                                    .position(|sort| *sort == model.view.sort)
+                                    .unwrap_or_default() as u32,
+                                connect_selected_notify[sender] => move |dropdown| {
+                                    sender.input(ContentInput::SetSort(dropdown.selected()));
+                                },
+                            },
+                            gtk::Label {
+                                set_css_classes: &["heading"],
+                                set_halign: gtk::Align::Start,
+                                set_margin_top: 5,
+                                set_text: fl!("group-by"),
+                            },
+                            gtk::DropDown {
+                                set_model: Some(&gtk::StringList::new(
+                                    &ListGrouping::ALL
+                                        .map(|group| group.name())
+                                        .iter()
+                                        .map(String::as_str)
+                                        .collect::<Vec<&str>>(),
+                                )),
+                                #[watch]
+                                set_selected: ListGrouping::ALL
+                                    .iter()
+                                    .position(|group| *group == model.view.group)
+                                    .unwrap_or_default() as u32,
+                                connect_selected_notify[sender] => move |dropdown| {
+                                    sender.input(ContentInput::SetListGrouping(dropdown.selected()));
+                                },
+                            },
+                        },
+                    },
+                },
+                pack_end = &gtk::ToggleButton {
+                    set_tooltip: fl!("board-view"),
+                    set_icon_name: icon_name::GRID,
+                    #[watch]
+                    set_visible: model.parent_list.is_some()
+                        && model.parent_list != Some(SidebarList::Agenda),
+                    #[watch]
+                    set_active: model.view.mode == ViewMode::Board,
+                    connect_toggled[sender] => move |toggle| {
+                        sender.input(ContentInput::SetBoardView(toggle.is_active()));
+                    },
+                },
             },
             #[name(overlay)]
             #[wrap(Some)]
@@ -103,7 +285,15 @@ impl AsyncComponent for ContentModel {
                         set_visible: model.parent_list.is_none(),
                         append: model.welcome.widget()
                     },
+                    gtk::Box {
+                        #[watch]
+                        set_visible: model.parent_list == Some(SidebarList::Agenda),
+                        set_hexpand: true,
+                        append: model.agenda.widget()
+                    },
                     adw::Clamp {
+                        #[watch]
+                        set_visible: model.parent_list != Some(SidebarList::Agenda),
                         gtk::Box {
                             #[watch]
                             set_visible: model.parent_list.is_some(),
@@ -198,8 +388,45 @@ impl AsyncComponent for ContentModel {
                                                     #[watch]
                                                     set_text: model.parent_list.as_ref().unwrap().description().as_str()
                                                 },
+                                                gtk::Box {
+                                                    #[watch]
+                                                    set_visible: model.workload().is_some(),
+                                                    set_spacing: 5,
+                                                    set_margin_bottom: 10,
+                                                    set_margin_start: 10,
+                                                    set_margin_end: 10,
+                                                    #[watch]
+                                                    set_class_active: ("warning", model.overloaded()),
+                                                    gtk::Image {
+                                                        #[watch]
+                                                        set_visible: model.overloaded(),
+                                                        set_valign: gtk::Align::Start,
+                                                        set_icon_name: Some(icon_name::WARNING),
+                                                    },
+                                                    gtk::Label {
+                                                        set_css_classes: &["caption"],
+                                                        set_halign: gtk::Align::Start,
+                                                        set_wrap: true,
+                                                        #[watch]
+                                                        set_text: &model.workload_summary(),
+                                                    },
+                                                },
+                                                gtk::Box {
+                                                    #[watch]
+                                                    set_visible: model.parent_list == Some(SidebarList::Today),
+                                                    set_orientation: gtk::Orientation::Vertical,
+                                                    append: model.suggestions.widget()
+                                                },
+                                                gtk::Box {
+                                                    #[watch]
+                                                    set_visible: model.view.mode == ViewMode::Board,
+                                                    set_vexpand: true,
+                                                    append: model.board.widget()
+                                                },
                                                 #[name(task_container)]
                                                 gtk::Stack {
+                                                    #[watch]
+                                                    set_visible: model.view.mode == ViewMode::List,
                                                     set_transition_duration: 250,
                                                     set_transition_type: gtk::StackTransitionType::Crossfade,
                                                     if model.task_factory.is_empty() {
@@ -240,8 +467,9 @@ impl AsyncComponent for ContentModel {
                                                             set_vexpand: true,
                                                             set_hexpand: true,
                                                             #[local_ref]
-                                                            list_box -> adw::PreferencesGroup {
+                                                            list_box -> gtk::ListBox {
                                                                 set_css_classes: &["boxed-list"],
+                                                                set_selection_mode: gtk::SelectionMode::None,
                                                                 set_valign: gtk::Align::Fill,
                                                                 set_margin_all: 5,
                                                             },
@@ -250,6 +478,8 @@ impl AsyncComponent for ContentModel {
                                                 },
                                                 gtk::Box {
                                                     set_margin_all: 5,
+                                                    #[watch]
+                                                    set_visible: !model.parent_list.as_ref().is_some_and(|list| list.read_only()),
                                                     append: model.task_entry.widget()
                                                 }
                                             },
@@ -269,12 +499,17 @@ impl AsyncComponent for ContentModel {
         root: Self::Root,
         sender: AsyncComponentSender<Self>,
     ) -> AsyncComponentParts<Self> {
-        let model = ContentModel {
+        let mut model = ContentModel {
             task_factory: AsyncFactoryVecDeque::builder()
-                .launch(adw::PreferencesGroup::default())
+                .launch(gtk::ListBox::default())
                 .forward(sender.input_sender(), |output| match output {
                     TaskOutput::Remove(index) => ContentInput::RemoveTask(index),
                     TaskOutput::UpdateTask(task) => ContentInput::UpdateTask(task),
+                    TaskOutput::ChoosePrerequisite(task) => ContentInput::ChoosePrerequisite(task),
+                    TaskOutput::ChooseList(task) => ContentInput::ChooseList(task),
+                    TaskOutput::ToggleTimer(task) => ContentInput::ToggleTimer(task),
+                    TaskOutput::StartPomodoro(task) => ContentInput::StartPomodoro(task),
+                    TaskOutput::Error(err) => ContentInput::ShowError(err),
                 }),
             task_entry: TaskInputModel::builder()
                 .launch(SidebarList::default())
@@ -282,13 +517,69 @@ impl AsyncComponent for ContentModel {
                     TaskInputOutput::AddTask(task) => ContentInput::AddTask(task),
                 }),
             welcome: WelcomeComponent::builder().launch(()).detach(),
+            dependency_dialog: DependencyDialogComponent::builder().launch(()).forward(
+                sender.input_sender(),
+                |message| match message {
+                    DependencyDialogOutput::AddDependency(task, prerequisite) => {
+                        ContentInput::AddDependency(task, prerequisite)
+                    }
+                },
+            ),
+            move_dialog: MoveDialogComponent::builder().launch(()).forward(
+                sender.input_sender(),
+                |message| match message {
+                    MoveDialogOutput::MoveTask(task, list) => ContentInput::MoveTask(task, list),
+                },
+            ),
+            board: BoardModel::builder()
+                .launch(())
+                .forward(sender.input_sender(), |message| match message {
+                    BoardOutput::UpdateTask(task) => ContentInput::UpdateTask(task),
+                    BoardOutput::GroupingChanged(grouping) => ContentInput::SetGrouping(grouping),
+                    BoardOutput::ColumnFull(column) => ContentInput::ColumnFull(column),
+                }),
+            agenda: AgendaModel::builder()
+                .launch(())
+                .forward(sender.input_sender(), |message| match message {
+                    AgendaOutput::Error(err) => ContentInput::ShowError(err),
+                }),
+            suggestions: SuggestionsModel::builder().launch(()).forward(
+                sender.input_sender(),
+                |message| match message {
+                    SuggestionsOutput::Planned => ContentInput::Reload,
+                    SuggestionsOutput::Error(err) => ContentInput::ShowError(err),
+                },
+            ),
+            view: ListView::default(),
+            shown: vec![],
+            headers: Rc::new(RefCell::new(vec![])),
             state: ContentState::Unselected,
             service: Service::Smart,
+            services: HashMap::new(),
             parent_list: None,
             handle: None,
+            database: Database::new(APP_ID.to_string()),
+            local: LocalTaskData::default(),
+            pomodoro: None,
+            pomodoro_generation: 0,
+            daily_capacity_minutes: Preferences::load().daily_capacity_minutes,
         };
 
+        if let Err(err) = model.database.reset_my_day() {
+            tracing::error!("{err}");
+        }
+        schedule_new_day(&sender);
+
         let list_box = model.task_factory.widget();
+        let headers = model.headers.clone();
+        list_box.set_header_func(move |row, _| {
+            let group = headers
+                .borrow()
+                .get(row.index() as usize)
+                .cloned()
+                .flatten();
+            row.set_header(group.map(|group| group_header(&group)).as_ref());
+        });
 
         let widgets = view_output!();
 
@@ -311,25 +602,158 @@ impl AsyncComponent for ContentModel {
                     self.task_factory.send(i, TaskInput::ExpandSubTask(expand))
                 }
             }
+            ContentInput::SetDailyCapacity(minutes) => self.daily_capacity_minutes = minutes,
+            ContentInput::ChoosePrerequisite(task) => {
+                let mut candidates: Vec<Task> = self
+                    .task_factory
+                    .iter()
+                    .flatten()
+                    .map(|row| row.task.clone())
+                    .collect();
+                for (_, candidate) in Service::all_tasks().await {
+                    if !candidates.iter().any(|task| task.id == candidate.id) {
+                        candidates.push(candidate);
+                    }
+                }
+                self.dependency_dialog
+                    .sender()
+                    .send(DependencyDialogInput::Open(task, candidates))
+                    .unwrap_or_default();
+            }
+            ContentInput::AddDependency(task, prerequisite) => {
+                let mut database = self.database.clone();
+                let dependency = Dependency {
+                    resolved: prerequisite.status.is_finished(),
+                    ..Dependency::new(task.id.clone(), prerequisite.id, prerequisite.title)
+                };
+                match database
+                    .add_dependency(dependency)
+                    .and_then(|_| database.dependencies(&task.id))
+                {
+                    Ok(dependencies) => self.refresh_dependencies(&task.id, dependencies),
+                    Err(err) => {
+                        let message = match err.downcast_ref::<DependencyError>() {
+                            Some(DependencyError::SelfDependency) => {
+                                fl!("dependency-on-itself").clone()
+                            }
+                            Some(DependencyError::Cycle(title)) => {
+                                fl!("dependency-cycle", title = title.clone()).clone()
+                            }
+                            None => err.to_string(),
+                        };
+                        widgets.overlay.add_toast(adw::Toast::new(&message))
+                    }
+                }
+            }
+            ContentInput::ChooseList(task) => {
+                let mut lists = Service::all_lists().await;
+                lists.retain(|list| !list.read_only());
+                self.move_dialog
+                    .sender()
+                    .send(MoveDialogInput::Open(task, lists))
+                    .unwrap_or_default();
+            }
+            ContentInput::MoveTask(task, list) => {
+                let from = self.task_service(&task.id);
+                match Service::move_task(task, from, &list).await {
+                    Ok(_) => sender.input(ContentInput::Reload),
+                    Err(err) => widgets.overlay.add_toast(adw::Toast::new(&err.to_string())),
+                }
+            }
+            ContentInput::SetBoardView(active) => {
+                let mode = if active {
+                    ViewMode::Board
+                } else {
+                    ViewMode::List
+                };
+                if mode != self.view.mode {
+                    self.view.mode = mode;
+                    self.save_view();
+                    if let Some(list) = self.parent_list.clone() {
+                        sender.input(ContentInput::LoadTasks(list, self.service));
+                    }
+                }
+            }
+            ContentInput::SetGrouping(grouping) => {
+                self.view.grouping = grouping;
+                self.save_view();
+            }
+            ContentInput::SetSort(position) => {
+                if let Some(sort) = SortOrder::ALL.get(position as usize) {
+                    if *sort != self.view.sort {
+                        self.view.sort = *sort;
+                        self.save_view();
+                        let rows = std::mem::take(&mut self.shown);
+                        self.show(rows);
+                    }
+                }
+            }
+            ContentInput::SetListGrouping(position) => {
+                if let Some(group) = ListGrouping::ALL.get(position as usize) {
+                    if *group != self.view.group {
+                        self.view.group = *group;
+                        self.save_view();
+                        let rows = std::mem::take(&mut self.shown);
+                        self.show(rows);
+                    }
+                }
+            }
+            ContentInput::ColumnFull(column) => widgets.overlay.add_toast(adw::Toast::new(
+                fl!("wip-limit-reached", column = column),
+            )),
+            ContentInput::ToggleTimer(task) => {
+                if self.local.timer.as_ref().is_some_and(|entry| entry.task_id == task.id) {
+                    if self.pomodoro.as_ref().is_some_and(|pomodoro| pomodoro.task_id == task.id) {
+                        self.cancel_pomodoro();
+                    }
+                    self.stop_timer(&task.id);
+                } else {
+                    self.start_timer(&task.id);
+                }
+            }
+            ContentInput::StartPomodoro(task) => {
+                self.cancel_pomodoro();
+                self.start_timer(&task.id);
+                self.pomodoro = Some(Pomodoro {
+                    task_id: task.id.clone(),
+                    title: task.title,
+                    phase: PomodoroPhase::Work,
+                });
+                self.refresh_time_tracking(&task.id);
+                schedule_pomodoro_phase(
+                    &sender,
+                    self.pomodoro_generation,
+                    Preferences::load().pomodoro_work_minutes,
+                );
+            }
+            ContentInput::ShowError(err) => widgets.overlay.add_toast(adw::Toast::new(&err)),
+            ContentInput::Reload => {
+                if let Some(list) = self.parent_list.clone() {
+                    sender.input(ContentInput::LoadTasks(list, self.service));
+                }
+            }
             ContentInput::CollapseSidebar => sender
                 .output(ContentOutput::CollapseSidebar)
                 .unwrap_or_default(),
             ContentInput::LoadTask(task) => {
-                if let SidebarList::Custom(parent) = &self.parent_list.as_ref().unwrap() {
-                    let mut guard = self.task_factory.guard();
-                    guard.push_back(TaskInit::new(task, parent.clone()));
-                    self.state = ContentState::TasksLoaded;
+                if let Some(SidebarList::Custom(parent)) = self.parent_list.clone() {
+                    if !task.is_deferred(local_today()) {
+                        self.board.emit(BoardInput::AddTask(task.clone()));
+                        self.insert_task(task, parent);
+                        self.state = ContentState::TasksLoaded;
+                    } else if self.shown.is_empty() {
+                        self.state = ContentState::Empty;
+                    }
                 }
             }
             ContentInput::AddTask(mut task) => {
-                if let SidebarList::Custom(parent) = &self.parent_list.as_ref().unwrap() {
+                if let Some(SidebarList::Custom(parent)) = self.parent_list.clone() {
                     task.parent = parent.id.clone();
                     let mut service = self.service.get_service();
-                    match service.create_task(task.clone()).await {
-                        Ok(_) => {
-                            self.task_factory
-                                .guard()
-                                .push_back(TaskInit::new(task.clone(), parent.clone()));
+                    match service.create_task(task).await {
+                        Ok(task) => {
+                            self.board.emit(BoardInput::AddTask(task.clone()));
+                            self.insert_task(task, parent);
                             self.state = ContentState::TasksLoaded;
                         }
                         Err(err) => {
@@ -339,100 +763,104 @@ impl AsyncComponent for ContentModel {
                 }
             }
             ContentInput::RemoveTask(index) => {
-                let mut guard = self.task_factory.guard();
-                if let Some(task) = guard.get(index.current_index()) {
-                    let mut service = self.service.get_service();
-                    match service
-                        .delete_task(task.task.clone().parent, task.task.clone().id)
-                        .await
-                    {
+                let task = self
+                    .task_factory
+                    .get(index.current_index())
+                    .map(|row| row.task.clone());
+                if let Some(task) = task {
+                    let mut service = self.task_service(&task.id).get_service();
+                    match service.delete_task(task.parent, task.id).await {
                         Ok(_) => {
-                            guard.remove(index.current_index());
+                            self.task_factory.guard().remove(index.current_index());
+                            self.shown.remove(index.current_index());
                         }
                         Err(err) => tracing::error!("An error ocurred: {err}"),
                     }
                 }
+                self.refresh_headers();
             }
             ContentInput::UpdateTask(task) => {
-                let mut service = self.service.get_service();
-                match service.update_task(task).await {
-                    Ok(task) => tracing::info!("Task {} successfully saved.", task.id),
+                if let Some((shown, _)) =
+                    self.shown.iter_mut().find(|(shown, _)| shown.id == task.id)
+                {
+                    *shown = task.clone();
+                }
+                let mut service = self.task_service(&task.id).get_service();
+                match service.update_task(task.clone()).await {
+                    Ok(saved) => {
+                        tracing::info!("Task {} successfully saved.", saved.id);
+                        self.refresh_saved(&saved);
+                        self.update_dependents(&task);
+                        if self.leaves_view(&task) {
+                            sender.input(ContentInput::Reload);
+                        }
+                    }
                     Err(err) => tracing::error!("An error ocurred: {err}"),
                 }
             }
             ContentInput::SelectList(list, service) => {
                 self.state = ContentState::Loading;
+                self.view = Preferences::load()
+                    .list_views
+                    .get(&list.key())
+                    .copied()
+                    .unwrap_or_else(|| match list {
+                        SidebarList::Upcoming => ListView {
+                            sort: SortOrder::StartDate,
+                            ..Default::default()
+                        },
+                        _ => ListView::default(),
+                    });
+                self.board.emit(BoardInput::SetList(list.key()));
+                self.board.emit(BoardInput::SetGrouping(self.view.grouping));
                 if let Some(handle) = &self.handle {
                     handle.abort()
                 }
                 sender.input(ContentInput::LoadTasks(list, service));
             }
             ContentInput::LoadTasks(list, service) => {
-                let mut guard = self.task_factory.guard();
-                guard.clear();
+                let mut rows = vec![];
+                self.board.emit(BoardInput::Clear);
+                self.services.clear();
                 self.service = service;
 
                 let mut service = service.get_service();
-                if let Ok(tasks) = service.read_tasks().await {
+                let blocked = self.database.blocked_tasks().unwrap_or_default();
+                self.local = LocalTaskData::load(&mut self.database);
+                let tasks = if self.service == Service::Smart {
+                    Ok(Service::all_tasks().await)
+                } else {
+                    service.read_tasks().await.map(|tasks| {
+                        tasks
+                            .into_iter()
+                            .map(|task| (self.service, task))
+                            .collect::<Vec<(Service, Task)>>()
+                    })
+                };
+                if let Ok(tasks) = tasks {
                     match &list {
-                        SidebarList::All => {
-                            self.parent_list = Some(SidebarList::All);
-                            for task in tasks {
-                                guard.push_back(TaskInit::new(
-                                    task.clone(),
-                                    service.read_list(task.parent).await.unwrap(),
-                                ));
-                            }
-                            self.state = ContentState::TasksLoaded;
-                        }
-                        SidebarList::Today => {
-                            self.parent_list = Some(SidebarList::Today);
-                            for task in tasks.iter().filter(|task| {
-                                task.today
-                                    || task.due_date.is_some()
-                                        && task.due_date.unwrap().date_naive()
-                                            == Utc::now().date_naive()
-                            }) {
-                                guard.push_back(TaskInit::new(
-                                    task.clone(),
-                                    service.read_list(task.parent.clone()).await.unwrap(),
-                                ));
-                            }
-                            self.state = ContentState::TasksLoaded;
-                        }
-                        SidebarList::Starred => {
-                            self.parent_list = Some(SidebarList::Starred);
-                            for task in tasks.iter().filter(|task| task.favorite) {
-                                guard.push_back(TaskInit::new(
-                                    task.clone(),
-                                    service.read_list(task.parent.clone()).await.unwrap(),
-                                ));
-                            }
-                            self.state = ContentState::TasksLoaded;
-                        }
-                        SidebarList::Next7Days => {
-                            self.parent_list = Some(SidebarList::Next7Days);
-                            for task in tasks.iter().filter(|task: &&Task| {
-                                task.due_date.is_some()
-                                    && is_within_next_7_days(task.due_date.unwrap())
-                            }) {
-                                guard.push_back(TaskInit::new(
-                                    task.clone(),
-                                    service.read_list(task.parent.clone()).await.unwrap(),
-                                ));
-                            }
-                            self.state = ContentState::TasksLoaded;
+                        SidebarList::Agenda => {
+                            self.parent_list = Some(SidebarList::Agenda);
+                            self.agenda.emit(AgendaInput::Reload);
                         }
-                        SidebarList::Done => {
-                            self.parent_list = Some(SidebarList::Done);
-                            for task in tasks
+                        SidebarList::All
+                        | SidebarList::Today
+                        | SidebarList::Starred
+                        | SidebarList::Next7Days
+                        | SidebarList::Upcoming
+                        | SidebarList::Done => {
+                            self.parent_list = Some(list.clone());
+                            for (task_service, task) in tasks
                                 .iter()
-                                .filter(|task: &&Task| task.status == Status::Completed)
+                                .filter(|(_, task)| shows_task(&list, task, &blocked))
                             {
-                                guard.push_back(TaskInit::new(
-                                    task.clone(),
-                                    service.read_list(task.parent.clone()).await.unwrap(),
-                                ));
+                                let parent = read_parent(*task_service, task).await;
+                                self.services.insert(task.id.clone(), *task_service);
+                                self.board.emit(BoardInput::AddTask(task.clone()));
+                                rows.push((task.clone(), parent));
+                            }
+                            if list == SidebarList::Today {
+                                self.suggestions.emit(SuggestionsInput::Reload);
                             }
                             self.state = ContentState::TasksLoaded;
                         }
@@ -462,14 +890,16 @@ impl AsyncComponent for ContentModel {
                                         Err(err) => tracing::error!("{err}"),
                                     }
                                 }));
-                            } else if let Ok(tasks) =
+                            } else if let Ok(mut tasks) =
                                 service.read_tasks_from_list(list_clone.id.clone()).await
                             {
+                                tasks.retain(|task| !task.is_deferred(local_today()));
                                 if tasks.is_empty() {
                                     self.state = ContentState::Empty;
                                 } else {
                                     for task in &tasks {
-                                        guard.push_back(TaskInit::new(
+                                        self.board.emit(BoardInput::AddTask(task.clone()));
+                                        rows.push((
                                             task.clone(),
                                             service.read_list(task.parent.clone()).await.unwrap(),
                                         ));
@@ -483,14 +913,11 @@ impl AsyncComponent for ContentModel {
                     }
                 }
 
-                if guard.is_empty() && self.state != ContentState::Loading {
+                self.show(rows);
+                if self.shown.is_empty() && self.state != ContentState::Loading {
                     self.state = ContentState::Empty;
                 }
 
-                if list.smart() {
-                    self.state = ContentState::Unselected;
-                }
-
                 self.task_entry
                     .sender()
                     .send(TaskInputInput::SetParentList(
@@ -506,10 +933,435 @@ impl AsyncComponent for ContentModel {
         }
         self.update_view(widgets, sender)
     }
+
+    async fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: AsyncComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            ContentCommand::NewDay => {
+                if let Err(err) = self.database.reset_my_day() {
+                    tracing::error!("{err}");
+                }
+                if self.parent_list.as_ref().is_some_and(|list| list.smart()) {
+                    sender.input(ContentInput::Reload);
+                }
+                schedule_new_day(&sender);
+            }
+            ContentCommand::PomodoroPhaseFinished(generation)
+                if generation == self.pomodoro_generation =>
+            {
+                match self.pomodoro.take() {
+                    Some(mut pomodoro) if pomodoro.phase == PomodoroPhase::Work => {
+                        notify_pomodoro(fl!("pomodoro-break-time"), &pomodoro.title);
+                        pomodoro.phase = PomodoroPhase::Break;
+                        let task_id = pomodoro.task_id.clone();
+                        self.pomodoro = Some(pomodoro);
+                        self.stop_timer(&task_id);
+                        schedule_pomodoro_phase(
+                            &sender,
+                            generation,
+                            Preferences::load().pomodoro_break_minutes,
+                        );
+                    }
+                    Some(pomodoro) => {
+                        notify_pomodoro(fl!("pomodoro-break-over"), &pomodoro.title);
+                        self.refresh_time_tracking(&pomodoro.task_id);
+                    }
+                    None => {}
+                }
+            }
+            ContentCommand::PomodoroPhaseFinished(_) => {}
+        }
+        self.update_view(widgets, sender)
+    }
+}
+
+impl ContentModel {
+    /// Replaces the rows of the list view, ordered by the sort and grouping
+    /// of the current list.
+    fn show(&mut self, mut rows: Vec<(Task, List)>) {
+        let view = self.view;
+        rows.sort_by(|(a, a_list), (b, b_list)| {
+            view.group
+                .group(a, a_list)
+                .cmp(&view.group.group(b, b_list))
+                .then_with(|| view.sort.compare(a, b))
+        });
+        let inits: Vec<TaskInit> = rows
+            .iter()
+            .map(|(task, list)| self.task_init(task, list))
+            .collect();
+        {
+            let mut guard = self.task_factory.guard();
+            guard.clear();
+            for init in inits {
+                guard.push_back(init);
+            }
+        }
+        self.shown = rows;
+        self.refresh_headers();
+    }
+
+    /// Adds a row where the sort and grouping of the current list place it.
+    fn insert_task(&mut self, task: Task, list: List) {
+        let view = self.view;
+        let group = view.group.group(&task, &list);
+        let position = self
+            .shown
+            .iter()
+            .position(|(shown, shown_list)| {
+                group
+                    .cmp(&view.group.group(shown, shown_list))
+                    .then_with(|| view.sort.compare(&task, shown))
+                    == Ordering::Less
+            })
+            .unwrap_or(self.shown.len());
+        let init = self.task_init(&task, &list);
+        self.task_factory.guard().insert(position, init);
+        self.shown.insert(position, (task, list));
+        self.refresh_headers();
+    }
+
+    fn task_init(&self, task: &Task, list: &List) -> TaskInit {
+        TaskInit::new(
+            task.clone(),
+            list.clone(),
+            self.local.dependencies.get(&task.id).cloned().unwrap_or_default(),
+            self.local.tracked.get(&task.id).copied().unwrap_or_else(Duration::zero),
+            self.local.timer.clone().filter(|entry| entry.task_id == task.id),
+            self.pomodoro_phase(&task.id),
+            self.database.clone(),
+        )
+    }
+
+    /// Whether an edit moved `task` out of the current view.
+    fn leaves_view(&self, task: &Task) -> bool {
+        let deferred = task.is_deferred(local_today());
+        match &self.parent_list {
+            Some(SidebarList::Today) => !task.today || deferred,
+            Some(SidebarList::Upcoming) => !deferred,
+            Some(SidebarList::Done | SidebarList::Agenda) | None => false,
+            Some(_) => deferred,
+        }
+    }
+
+    /// Puts a header above the first row of every group.
+    fn refresh_headers(&self) {
+        let mut previous = None;
+        *self.headers.borrow_mut() = self
+            .shown
+            .iter()
+            .map(|(task, list)| {
+                let group = self.view.group.group(task, list);
+                let header = if group != previous {
+                    group.clone()
+                } else {
+                    None
+                };
+                previous = group;
+                header
+            })
+            .collect();
+        self.task_factory.widget().invalidate_headers();
+    }
+
+    /// The service a task shown in the current list belongs to.
+    fn task_service(&self, task_id: &str) -> Service {
+        self.services.get(task_id).copied().unwrap_or(self.service)
+    }
+
+    fn save_view(&self) {
+        if let Some(list) = &self.parent_list {
+            let view = self.view;
+            if let Err(err) = Preferences::update(|preferences| {
+                preferences.list_views.insert(list.key(), view);
+            }) {
+                tracing::error!("{err}");
+            }
+        }
+    }
+
+    /// Planned effort of the Today and Next 7 Days views.
+    fn workload(&self) -> Option<Workload> {
+        let days = match self.parent_list {
+            Some(SidebarList::Today) => 1,
+            // Today plus the seven days after it.
+            Some(SidebarList::Next7Days) => 8,
+            _ => return None,
+        };
+        let planned: Vec<(Task, String)> = self
+            .shown
+            .iter()
+            .map(|(task, list)| (task.clone(), list.name.clone()))
+            .collect();
+        Some(Workload::new(&planned, local_today(), days))
+    }
+
+    fn overloaded(&self) -> bool {
+        self.workload().is_some_and(|workload| {
+            !workload
+                .overloaded(self.daily_capacity_minutes)
+                .is_empty()
+        })
+    }
+
+    fn workload_summary(&self) -> String {
+        let Some(workload) = self.workload() else {
+            return String::new();
+        };
+        let mut summary = format!("{}: {}", fl!("planned"), format_estimate(workload.total()));
+        if workload.days.len() > 1 {
+            let days: Vec<String> = workload
+                .days
+                .iter()
+                .filter(|(_, minutes)| *minutes > 0)
+                .map(|(day, minutes)| {
+                    format!("{} {}", day.format("%a %d"), format_estimate(*minutes))
+                })
+                .collect();
+            if !days.is_empty() {
+                summary = format!("{summary} · {}", days.join(", "));
+            }
+        }
+        if !workload.lists.is_empty() {
+            let lists: Vec<String> = workload
+                .lists
+                .iter()
+                .map(|(list, minutes)| format!("{list} {}", format_estimate(*minutes)))
+                .collect();
+            summary = format!("{summary} · {}", lists.join(", "));
+        }
+        let overloaded = workload.overloaded(self.daily_capacity_minutes);
+        if !overloaded.is_empty() {
+            let days: Vec<String> = overloaded
+                .iter()
+                .map(|day| day.format("%a %d").to_string())
+                .collect();
+            summary = format!("{summary}\n{} {}", fl!("over-capacity"), days.join(", "));
+        }
+        summary
+    }
+
+    /// Shows what the service saved, sub-tasks keep the ids it assigned so
+    /// the next save updates them instead of creating them again.
+    fn refresh_saved(&mut self, saved: &Task) {
+        if let Some((shown, _)) = self.shown.iter_mut().find(|(shown, _)| shown.id == saved.id) {
+            *shown = saved.clone();
+        }
+        for (i, row) in self.task_factory.iter().enumerate() {
+            if row.is_some_and(|row| row.task.id == saved.id) {
+                self.task_factory
+                    .send(i, TaskInput::SetSubTasks(saved.sub_tasks.clone()));
+            }
+        }
+        self.board.emit(BoardInput::ReplaceTask(saved.clone()));
+    }
+
+    /// Starts a timer on a task. Only one timer runs at a time, so the row
+    /// of the task whose timer this stops is refreshed too.
+    fn start_timer(&mut self, task_id: &str) {
+        let previous = self.database.running_timer().unwrap_or_else(|err| {
+            tracing::error!("{err}");
+            None
+        });
+        match self.database.start_timer(task_id, "") {
+            Ok(entry) => self.local.timer = Some(entry),
+            Err(err) => {
+                tracing::error!("{err}");
+                return;
+            }
+        }
+        if let Some(previous) = previous.filter(|entry| entry.task_id != task_id) {
+            if self
+                .pomodoro
+                .as_ref()
+                .is_some_and(|pomodoro| pomodoro.task_id == previous.task_id)
+            {
+                self.cancel_pomodoro();
+            }
+            self.refresh_time_tracking(&previous.task_id);
+        }
+        self.refresh_time_tracking(task_id);
+    }
+
+    fn stop_timer(&mut self, task_id: &str) {
+        if let Err(err) = self.database.stop_timer(task_id) {
+            tracing::error!("{err}");
+        }
+        if self.local.timer.as_ref().is_some_and(|entry| entry.task_id == task_id) {
+            self.local.timer = None;
+        }
+        self.refresh_time_tracking(task_id);
+    }
+
+    fn cancel_pomodoro(&mut self) {
+        self.pomodoro_generation += 1;
+        if let Some(pomodoro) = self.pomodoro.take() {
+            self.refresh_time_tracking(&pomodoro.task_id);
+        }
+    }
+
+    fn pomodoro_phase(&self, task_id: &str) -> Option<PomodoroPhase> {
+        self.pomodoro
+            .as_ref()
+            .filter(|pomodoro| pomodoro.task_id == task_id)
+            .map(|pomodoro| pomodoro.phase)
+    }
+
+    /// Shows the tracked time, timer and Pomodoro of a task in its rows.
+    fn refresh_time_tracking(&mut self, task_id: &str) {
+        match self.database.tracked_time(task_id) {
+            Ok(tracked) => {
+                self.local.tracked.insert(task_id.to_string(), tracked);
+            }
+            Err(err) => tracing::error!("{err}"),
+        }
+        let tracked = self
+            .local
+            .tracked
+            .get(task_id)
+            .copied()
+            .unwrap_or_else(Duration::zero);
+        let timer = self
+            .local
+            .timer
+            .clone()
+            .filter(|entry| entry.task_id == task_id);
+        let pomodoro = self.pomodoro_phase(task_id);
+        for (i, row) in self.task_factory.iter().enumerate() {
+            if row.is_some_and(|row| row.task.id == task_id) {
+                self.task_factory.send(
+                    i,
+                    TaskInput::SetTimeTracking(tracked, timer.clone(), pomodoro),
+                );
+            }
+        }
+    }
+
+    fn refresh_dependencies(&mut self, task_id: &str, dependencies: Vec<Dependency>) {
+        self.local
+            .dependencies
+            .insert(task_id.to_string(), dependencies.clone());
+        for (i, row) in self.task_factory.iter().enumerate() {
+            if row.is_some_and(|row| row.task.id == task_id) {
+                self.task_factory
+                    .send(i, TaskInput::SetDependencies(dependencies.clone()));
+            }
+        }
+    }
+
+    /// Propagates a status change to the tasks waiting on `task` and lets the
+    /// user know when one of them is no longer blocked.
+    fn update_dependents(&mut self, task: &Task) {
+        let mut database = self.database.clone();
+        let completed = task.status.is_finished();
+        let changed =
+            match database.set_prerequisite_completed(&task.id, completed, &task.title) {
+                Ok(changed) => changed,
+                Err(err) => {
+                    tracing::error!("{err}");
+                    return;
+                }
+            };
+        if changed.is_empty() {
+            return;
+        }
+        let blocked = database.blocked_tasks().unwrap_or_default();
+        for dependency in changed {
+            if let Ok(dependencies) = database.dependencies(&dependency.task_id) {
+                self.refresh_dependencies(&dependency.task_id, dependencies);
+            }
+            if completed && !blocked.contains(&dependency.task_id) {
+                let notification = gio::Notification::new(fl!("task-unblocked"));
+                notification.set_body(Some(fl!(
+                    "task-unblocked-body",
+                    title = task.title.clone()
+                )));
+                main_adw_application()
+                    .send_notification(Some(&dependency.task_id), &notification);
+            }
+        }
+    }
+}
+
+fn group_header(group: &TaskGroup) -> gtk::Label {
+    let label = gtk::Label::new(Some(&group.name));
+    label.set_halign(gtk::Align::Start);
+    label.set_margin_all(10);
+    label.add_css_class("heading");
+    if group.warning {
+        label.add_css_class("error");
+    }
+    label
+}
+
+/// Whether the smart `list` shows `task`, deferred tasks only show up in
+/// Upcoming and Done.
+fn shows_task(list: &SidebarList, task: &Task, blocked: &HashSet<String>) -> bool {
+    let deferred = task.is_deferred(local_today());
+    match list {
+        SidebarList::All => !deferred,
+        SidebarList::Today => {
+            task.today && !deferred && !blocked.contains(&task.id) && !task.status.is_set_aside()
+        }
+        SidebarList::Starred => task.favorite && !deferred,
+        SidebarList::Next7Days => {
+            !deferred
+                && !blocked.contains(&task.id)
+                && !task.status.is_set_aside()
+                && task.due_date.is_some_and(is_within_next_7_days)
+        }
+        SidebarList::Upcoming => deferred && !task.status.is_finished(),
+        SidebarList::Done => task.status.is_finished(),
+        SidebarList::Agenda | SidebarList::Custom(_) => false,
+    }
+}
+
+async fn read_parent(service: Service, task: &Task) -> List {
+    service
+        .get_service()
+        .read_list(task.parent.clone())
+        .await
+        .unwrap_or_else(|err| {
+            tracing::error!("{err}");
+            List::default()
+        })
+}
+
+/// Wakes the view up at the next local midnight.
+fn schedule_new_day(sender: &AsyncComponentSender<ContentModel>) {
+    let tomorrow = local_today().succ_opt().unwrap_or_default();
+    let midnight = from_local(tomorrow.and_time(NaiveTime::MIN));
+    let delay = (midnight - Utc::now()).to_std().unwrap_or_default();
+    sender.oneshot_command(async move {
+        tokio::time::sleep(delay).await;
+        ContentCommand::NewDay
+    });
+}
+
+fn schedule_pomodoro_phase(
+    sender: &AsyncComponentSender<ContentModel>,
+    generation: u32,
+    minutes: u32,
+) {
+    sender.oneshot_command(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(minutes as u64 * 60)).await;
+        ContentCommand::PomodoroPhaseFinished(generation)
+    });
+}
+
+fn notify_pomodoro(title: &str, body: &str) {
+    let notification = gio::Notification::new(title);
+    notification.set_body(Some(body));
+    main_adw_application().send_notification(Some("pomodoro"), &notification);
 }
 
 fn is_within_next_7_days(date: DateTime<Utc>) -> bool {
-    let now = Utc::now();
-    let next_7_days = now + chrono::Duration::days(7);
-    date >= now && date <= next_7_days
+    let today = local_today();
+    let due = date.date_naive();
+    due >= today && due <= today + chrono::Duration::days(7)
 }
@@ -0,0 +1,140 @@
+use gtk::prelude::{BoxExt, ButtonExt, ListModelExt, StaticType, WidgetExt};
+use relm4::{
+    Component, ComponentParts, ComponentSender, RelmWidgetExt, adw,
+    gtk::{
+        self,
+        traits::{GtkWindowExt, OrientableExt},
+    },
+};
+use relm4_icons::icon_name;
+
+use done_core::models::task::Task;
+
+use crate::fl;
+
+#[derive(Debug)]
+pub struct DependencyDialogComponent {
+    task: Option<Task>,
+    candidates: Vec<Task>,
+    titles: gtk::StringList,
+}
+
+#[derive(Debug)]
+pub enum DependencyDialogInput {
+    Open(Task, Vec<Task>),
+    Select(u32),
+}
+
+#[derive(Debug)]
+pub enum DependencyDialogOutput {
+    AddDependency(Task, Task),
+}
+
+#[relm4::component(pub)]
+impl Component for DependencyDialogComponent {
+    type Input = DependencyDialogInput;
+    type Output = DependencyDialogOutput;
+    type Init = ();
+    type CommandOutput = ();
+
+    view! {
+        #[root]
+        adw::Window {
+            set_hide_on_close: true,
+            set_default_width: 320,
+            set_resizable: false,
+            set_modal: true,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+
+                adw::HeaderBar {
+                    set_show_end_title_buttons: true,
+                    set_css_classes: &["flat"],
+                    set_title_widget: Some(&gtk::Box::default())
+                },
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 20,
+                    set_spacing: 10,
+                    gtk::Image {
+                        set_icon_size: gtk::IconSize::Large,
+                        set_icon_name: Some(icon_name::HORIZONTAL_ARROWS),
+                    },
+                    gtk::Label {
+                        set_css_classes: &["title-4"],
+                        #[watch]
+                        set_label: &model.task.as_ref().map(|task| task.title.clone()).unwrap_or_default(),
+                    },
+                    gtk::Label {
+                        set_label: fl!("choose-prerequisite"),
+                    },
+                    #[name = "candidates"]
+                    gtk::DropDown {
+                        set_model: Some(&model.titles),
+                        set_enable_search: true,
+                        set_expression: Some(gtk::PropertyExpression::new(
+                            gtk::StringObject::static_type(),
+                            None::<gtk::Expression>,
+                            "string",
+                        )),
+                    },
+                    gtk::Button {
+                        set_css_classes: &["suggested-action"],
+                        set_label: fl!("add-prerequisite"),
+                        #[watch]
+                        set_sensitive: !model.candidates.is_empty(),
+                        connect_clicked[sender, candidates] => move |_| {
+                            sender.input(DependencyDialogInput::Select(candidates.selected()));
+                        },
+                    },
+                }
+            }
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = DependencyDialogComponent {
+            task: None,
+            candidates: vec![],
+            titles: gtk::StringList::new(&[]),
+        };
+
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            DependencyDialogInput::Open(task, candidates) => {
+                self.candidates = candidates
+                    .into_iter()
+                    .filter(|candidate| candidate.id != task.id)
+                    .collect();
+                let titles: Vec<&str> = self
+                    .candidates
+                    .iter()
+                    .map(|candidate| candidate.title.as_str())
+                    .collect();
+                self.titles.splice(0, self.titles.n_items(), &titles);
+                self.task = Some(task);
+                root.present();
+            }
+            DependencyDialogInput::Select(position) => {
+                if let (Some(task), Some(prerequisite)) = (
+                    self.task.take(),
+                    self.candidates.get(position as usize).cloned(),
+                ) {
+                    sender
+                        .output(DependencyDialogOutput::AddDependency(task, prerequisite))
+                        .unwrap_or_default();
+                }
+                root.close();
+            }
+        }
+    }
+}
@@ -16,6 +16,15 @@ pub struct ListDialogComponent {
     pub name: gtk::EntryBuffer,
     pub mode: ListDialogMode,
     pub label: String,
+    /// Whether the dialog names a group of lists instead of a list.
+    pub group: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ListDialogInit {
+    /// The current name when renaming, `None` when adding.
+    pub name: Option<String>,
+    pub group: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -39,7 +48,7 @@ pub enum ListDialogOutput {
 impl Component for ListDialogComponent {
     type Input = ListDialogInput;
     type Output = ListDialogOutput;
-    type Init = Option<String>;
+    type Init = ListDialogInit;
     type CommandOutput = ();
 
     view! {
@@ -71,9 +80,11 @@ impl Component for ListDialogComponent {
                     },
                     gtk::Label {
                         set_css_classes: &["title-4"],
-                        set_label: match model.mode {
-                            ListDialogMode::New => "You're about to add a list.",
-                            ListDialogMode::Edit => "You're about to rename this list."
+                        set_label: match (&model.mode, model.group) {
+                            (ListDialogMode::New, false) => "You're about to add a list.",
+                            (ListDialogMode::Edit, false) => "You're about to rename this list.",
+                            (ListDialogMode::New, true) => "You're about to add a group.",
+                            (ListDialogMode::Edit, true) => "You're about to rename this group.",
                         },
                     },
                     gtk::Label {
@@ -81,7 +92,11 @@ impl Component for ListDialogComponent {
                     },
                     #[name = "new_list_entry"]
                     gtk::Entry {
-                        set_placeholder_text: Some(fl!("list-name")),
+                        set_placeholder_text: Some(if model.group {
+                            fl!("group-name").as_str()
+                        } else {
+                            fl!("list-name").as_str()
+                        }),
                         set_buffer: &model.name,
                         connect_activate => ListDialogInput::HandleEntry,
                     },
@@ -100,17 +115,23 @@ impl Component for ListDialogComponent {
         root: &Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let model = if let Some(name) = init {
+        let model = if let Some(name) = init.name {
             ListDialogComponent {
                 name: gtk::EntryBuffer::new(Some(name)),
                 mode: ListDialogMode::Edit,
                 label: fl!("rename").clone(),
+                group: init.group,
             }
         } else {
             ListDialogComponent {
                 name: gtk::EntryBuffer::new(Some("")),
                 mode: ListDialogMode::New,
-                label: fl!("add-list").clone(),
+                label: if init.group {
+                    fl!("add-group").clone()
+                } else {
+                    fl!("add-list").clone()
+                },
+                group: init.group,
             }
         };
 
@@ -17,13 +17,22 @@ use relm4::{
 };
 use relm4_icons::icon_name;
 
-use done_core::{models::list::List, service::Service};
+use done_core::{
+    models::{list::List, list_group::ListGroup},
+    service::Service,
+};
 
 use crate::{
     app::{
         AboutAction, PreferencesAction, QuitAction, ShortcutsAction,
-        components::{list_dialog::ListDialogOutput, services::ServicesOutput},
-        factories::task_list::{TaskListFactoryInit, TaskListFactoryModel, TaskListFactoryOutput},
+        components::{
+            list_dialog::{ListDialogInit, ListDialogOutput},
+            services::ServicesOutput,
+        },
+        factories::{
+            list_group::{ListGroupFactoryInit, ListGroupFactoryModel, ListGroupFactoryOutput},
+            task_list::{TaskListFactoryInit, TaskListFactoryModel, TaskListFactoryOutput},
+        },
         models::sidebar_list::SidebarList,
     },
     fl,
@@ -38,7 +47,11 @@ pub struct ListSidebarModel {
     service: Service,
     state: ListSidebarStatus,
     task_list_factory: AsyncFactoryVecDeque<TaskListFactoryModel>,
+    list_group_factory: AsyncFactoryVecDeque<ListGroupFactoryModel>,
     list_entry: Controller<ListDialogComponent>,
+    group_entry: Controller<ListDialogComponent>,
+    /// Whether the service can sort lists into groups.
+    groups_support: bool,
     services_sidebar_controller: AsyncController<ServicesModel>,
     handle: Option<JoinHandle<()>>,
 }
@@ -49,6 +62,13 @@ pub enum ListSidebarInput {
     OpenNewTaskListDialog,
     LoadTaskList(List),
     AddTaskListToSidebar(String),
+    OpenNewGroupDialog,
+    AddGroupToSidebar(String),
+    /// Moves the list with the id into the group, or out of its group.
+    MoveList(String, Option<String>),
+    DeleteGroup(DynamicIndex),
+    /// A list inside a group was deleted.
+    ListDeleted,
     ServiceSelected(Service),
     ServiceDisabled(Service),
     SelectList(SidebarList),
@@ -103,6 +123,15 @@ impl SimpleAsyncComponent for ListSidebarModel {
                     set_valign: gtk::Align::Center,
                     connect_clicked => ListSidebarInput::OpenNewTaskListDialog
                 },
+                pack_start = &gtk::Button {
+                    #[watch]
+                    set_visible: model.groups_support,
+                    set_tooltip: fl!("add-new-group"),
+                    set_icon_name: icon_name::FOLDER_ADD_REGULAR,
+                    set_css_classes: &["flat", "image-button"],
+                    set_valign: gtk::Align::Center,
+                    connect_clicked => ListSidebarInput::OpenNewGroupDialog
+                },
                 pack_end = &gtk::MenuButton {
                     set_tooltip: fl!("menu"),
                     set_valign: gtk::Align::Center,
@@ -121,8 +150,9 @@ impl SimpleAsyncComponent for ListSidebarModel {
                     set_margin_bottom: 5,
                     gtk::Label {
                         set_css_classes: &["heading"],
+                        set_justify: gtk::Justification::Center,
                         #[watch]
-                        set_text: &model.service.to_string()
+                        set_text: &model.service.label()
                     }
                 },
                 append = match model.state {
@@ -168,9 +198,14 @@ impl SimpleAsyncComponent for ListSidebarModel {
                     ListSidebarStatus::Loaded => {
                         gtk::ScrolledWindow {
                             set_vexpand: true,
+                            #[name(lists_box)]
                             gtk::Box {
                                 set_orientation: gtk::Orientation::Vertical,
                                 #[local_ref]
+                                list_group_widget -> gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+                                },
+                                #[local_ref]
                                 task_list_widget -> gtk::ListBox {
                                     set_margin_all: 10,
                                     set_css_classes: &["boxed-list"],
@@ -204,15 +239,34 @@ impl SimpleAsyncComponent for ListSidebarModel {
                         ListSidebarInput::DeleteTaskList(index)
                     }
                 }),
-            list_entry: ListDialogComponent::builder().launch(None).forward(
-                sender.input_sender(),
-                |message| match message {
+            list_group_factory: AsyncFactoryVecDeque::builder()
+                .launch(gtk::Box::default())
+                .forward(sender.input_sender(), |output| match output {
+                    ListGroupFactoryOutput::SelectList(list) => ListSidebarInput::SelectList(list),
+                    ListGroupFactoryOutput::MoveList(list, group) => {
+                        ListSidebarInput::MoveList(list, group)
+                    }
+                    ListGroupFactoryOutput::ListDeleted => ListSidebarInput::ListDeleted,
+                    ListGroupFactoryOutput::Deleted(index) => ListSidebarInput::DeleteGroup(index),
+                }),
+            list_entry: ListDialogComponent::builder()
+                .launch(ListDialogInit::default())
+                .forward(sender.input_sender(), |message| match message {
                     ListDialogOutput::AddTaskListToSidebar(name) => {
                         ListSidebarInput::AddTaskListToSidebar(name)
                     }
                     ListDialogOutput::RenameList(_) => todo!(),
-                },
-            ),
+                }),
+            group_entry: ListDialogComponent::builder()
+                .launch(ListDialogInit {
+                    name: None,
+                    group: true,
+                })
+                .forward(sender.input_sender(), |message| match message {
+                    ListDialogOutput::AddTaskListToSidebar(name)
+                    | ListDialogOutput::RenameList(name) => ListSidebarInput::AddGroupToSidebar(name),
+                }),
+            groups_support: false,
             services_sidebar_controller: ServicesModel::builder().launch(()).forward(
                 sender.input_sender(),
                 |message| match message {
@@ -228,7 +282,21 @@ impl SimpleAsyncComponent for ListSidebarModel {
         };
         sender.input(ListSidebarInput::LoadTaskLists);
         let task_list_widget = model.task_list_factory.widget();
+        let list_group_widget = model.list_group_factory.widget();
         let widgets = view_output!();
+
+        // Dropping a list anywhere but on a group takes it out of its group.
+        let drop_target =
+            gtk::DropTarget::new(gtk::glib::Type::STRING, gtk::gdk::DragAction::MOVE);
+        let drop_sender = sender.clone();
+        drop_target.connect_drop(move |_, value, _, _| match value.get::<String>() {
+            Ok(list) => {
+                drop_sender.input(ListSidebarInput::MoveList(list, None));
+                true
+            }
+            Err(_) => false,
+        });
+        widgets.lists_box.add_controller(drop_target);
         AsyncComponentParts { model, widgets }
     }
 
@@ -263,6 +331,50 @@ impl SimpleAsyncComponent for ListSidebarModel {
                 let list_entry = self.list_entry.widget();
                 list_entry.present();
             }
+            ListSidebarInput::OpenNewGroupDialog => {
+                let group_entry = self.group_entry.widget();
+                group_entry.present();
+            }
+            ListSidebarInput::AddGroupToSidebar(name) => {
+                let position = self.list_group_factory.len() as i32;
+                match self
+                    .service
+                    .get_service()
+                    .create_list_group(ListGroup::new(&name, position, self.service))
+                    .await
+                {
+                    Ok(group) => {
+                        self.list_group_factory
+                            .guard()
+                            .push_back(ListGroupFactoryInit::new(group, vec![]));
+                        self.state = ListSidebarStatus::Loaded;
+                    }
+                    Err(e) => {
+                        tracing::error!("Error while creating list group: {}", e);
+                    }
+                }
+            }
+            ListSidebarInput::MoveList(id, group) => {
+                let mut service = self.service.get_service();
+                match service.read_list(id).await {
+                    Ok(mut list) if list.group != group => {
+                        list.group = group;
+                        match service.update_list(list).await {
+                            Ok(_) => sender.input(ListSidebarInput::LoadTaskLists),
+                            Err(err) => tracing::error!("{err}"),
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => tracing::error!("{err}"),
+                }
+            }
+            ListSidebarInput::DeleteGroup(_) => {
+                // Its lists are now outside of any group.
+                sender.input(ListSidebarInput::LoadTaskLists);
+            }
+            ListSidebarInput::ListDeleted => sender
+                .output(ListSidebarOutput::CleanContent)
+                .unwrap_or_default(),
             ListSidebarInput::ServiceSelected(service) => {
                 self.service = service;
                 if let Some(handle) = &self.handle {
@@ -295,8 +407,11 @@ impl SimpleAsyncComponent for ListSidebarModel {
             ListSidebarInput::LoadTaskLists => {
                 let mut guard = self.task_list_factory.guard();
                 guard.clear();
+                let mut group_guard = self.list_group_factory.guard();
+                group_guard.clear();
 
                 let mut service = self.service.get_service();
+                self.groups_support = service.list_groups_support();
                 if service.stream_support() {
                     let sender_clone = sender.clone();
                     self.handle = Some(tokio::spawn(async move {
@@ -323,14 +438,30 @@ impl SimpleAsyncComponent for ListSidebarModel {
                             guard.push_back(TaskListFactoryInit::new(Service::Smart, smart_list));
                         }
                     } else {
-                        for list in service.read_lists().await.unwrap() {
+                        let groups = service.read_list_groups().await.unwrap_or_else(|err| {
+                            tracing::error!("{err}");
+                            vec![]
+                        });
+                        let (grouped, lists): (Vec<List>, Vec<List>) =
+                            service.read_lists().await.unwrap().into_iter().partition(|list| {
+                                groups.iter().any(|group| list.group.as_ref() == Some(&group.id))
+                            });
+                        for group in groups {
+                            let lists = grouped
+                                .iter()
+                                .filter(|list| list.group.as_ref() == Some(&group.id))
+                                .cloned()
+                                .collect();
+                            group_guard.push_back(ListGroupFactoryInit::new(group, lists));
+                        }
+                        for list in lists {
                             guard.push_back(TaskListFactoryInit::new(
                                 self.service,
                                 SidebarList::Custom(list),
                             ));
                         }
                     }
-                    if guard.is_empty() {
+                    if guard.is_empty() && group_guard.is_empty() {
                         self.state = ListSidebarStatus::Empty;
                     } else {
                         self.state = ListSidebarStatus::Loaded;
@@ -345,7 +476,7 @@ impl SimpleAsyncComponent for ListSidebarModel {
                 sender
                     .output(ListSidebarOutput::CleanContent)
                     .unwrap_or_default();
-                if self.task_list_factory.is_empty() {
+                if self.task_list_factory.is_empty() && self.list_group_factory.is_empty() {
                     self.state = ListSidebarStatus::Empty;
                 }
             }
@@ -1,8 +1,13 @@
 pub mod about_dialog;
+pub mod agenda;
+pub mod board;
 pub mod content;
 pub mod delete;
+pub mod dependency_dialog;
 pub mod list_dialog;
 pub mod list_sidebar;
+pub mod move_dialog;
+pub mod my_day;
 pub mod preferences;
 pub mod services;
 pub mod task_input;
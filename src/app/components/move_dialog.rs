@@ -0,0 +1,141 @@
+use gtk::prelude::{BoxExt, ButtonExt, ListModelExt, StaticType, WidgetExt};
+use relm4::{
+    Component, ComponentParts, ComponentSender, RelmWidgetExt, adw,
+    gtk::{
+        self,
+        traits::{GtkWindowExt, OrientableExt},
+    },
+};
+use relm4_icons::icon_name;
+
+use done_core::models::{list::List, task::Task};
+
+use crate::fl;
+
+#[derive(Debug)]
+pub struct MoveDialogComponent {
+    task: Option<Task>,
+    lists: Vec<List>,
+    names: gtk::StringList,
+}
+
+#[derive(Debug)]
+pub enum MoveDialogInput {
+    Open(Task, Vec<List>),
+    Select(u32),
+}
+
+#[derive(Debug)]
+pub enum MoveDialogOutput {
+    MoveTask(Task, List),
+}
+
+#[relm4::component(pub)]
+impl Component for MoveDialogComponent {
+    type Input = MoveDialogInput;
+    type Output = MoveDialogOutput;
+    type Init = ();
+    type CommandOutput = ();
+
+    view! {
+        #[root]
+        adw::Window {
+            set_hide_on_close: true,
+            set_default_width: 320,
+            set_resizable: false,
+            set_modal: true,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+
+                adw::HeaderBar {
+                    set_show_end_title_buttons: true,
+                    set_css_classes: &["flat"],
+                    set_title_widget: Some(&gtk::Box::default())
+                },
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 20,
+                    set_spacing: 10,
+                    gtk::Image {
+                        set_icon_size: gtk::IconSize::Large,
+                        set_icon_name: Some(icon_name::SEND),
+                    },
+                    gtk::Label {
+                        set_css_classes: &["title-4"],
+                        #[watch]
+                        set_label: &model.task.as_ref().map(|task| task.title.clone()).unwrap_or_default(),
+                    },
+                    gtk::Label {
+                        set_label: fl!("choose-destination"),
+                    },
+                    #[name = "destinations"]
+                    gtk::DropDown {
+                        set_model: Some(&model.names),
+                        set_enable_search: true,
+                        set_expression: Some(gtk::PropertyExpression::new(
+                            gtk::StringObject::static_type(),
+                            None::<gtk::Expression>,
+                            "string",
+                        )),
+                    },
+                    gtk::Button {
+                        set_css_classes: &["suggested-action"],
+                        set_label: fl!("move-task"),
+                        #[watch]
+                        set_sensitive: !model.lists.is_empty(),
+                        connect_clicked[sender, destinations] => move |_| {
+                            sender.input(MoveDialogInput::Select(destinations.selected()));
+                        },
+                    },
+                }
+            }
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: &Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = MoveDialogComponent {
+            task: None,
+            lists: vec![],
+            names: gtk::StringList::new(&[]),
+        };
+
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            MoveDialogInput::Open(task, lists) => {
+                self.lists = lists
+                    .into_iter()
+                    .filter(|list| list.id != task.parent)
+                    .collect();
+                let names: Vec<String> = self
+                    .lists
+                    .iter()
+                    .map(|list| format!("{} — {}", list.name, list.service.label().replace('\n', " · ")))
+                    .collect();
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                self.names.splice(0, self.names.n_items(), &names);
+                self.task = Some(task);
+                root.present();
+            }
+            MoveDialogInput::Select(position) => {
+                if let (Some(task), Some(list)) = (
+                    self.task.take(),
+                    self.lists.get(position as usize).cloned(),
+                ) {
+                    sender
+                        .output(MoveDialogOutput::MoveTask(task, list))
+                        .unwrap_or_default();
+                }
+                root.close();
+            }
+        }
+    }
+}
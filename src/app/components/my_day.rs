@@ -0,0 +1,185 @@
+use relm4::component::{AsyncComponent, AsyncComponentParts, AsyncComponentSender};
+use relm4::gtk::traits::ButtonExt;
+use relm4::{
+    RelmWidgetExt, adw,
+    adw::prelude::{ActionRowExt, PreferencesRowExt},
+    gtk,
+    gtk::prelude::{BoxExt, OrientableExt, WidgetExt},
+};
+use relm4_icons::icon_name;
+
+use done_core::models::my_day::{Suggestion, SuggestionReason, suggestions};
+use done_core::service::Service;
+use done_core::services::local::database::Database;
+use done_core::models::dates::local_today;
+
+use crate::app::config::info::APP_ID;
+use crate::fl;
+
+pub struct SuggestionsModel {
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug)]
+pub enum SuggestionsInput {
+    Reload,
+    Plan(String),
+    PlanAll,
+}
+
+#[derive(Debug)]
+pub enum SuggestionsOutput {
+    Planned,
+    Error(String),
+}
+
+#[relm4::component(pub async)]
+impl AsyncComponent for SuggestionsModel {
+    type CommandOutput = ();
+    type Input = SuggestionsInput;
+    type Output = SuggestionsOutput;
+    type Init = ();
+
+    view! {
+        #[root]
+        gtk::Expander {
+            #[watch]
+            set_visible: !model.suggestions.is_empty(),
+            set_expanded: true,
+            set_margin_start: 10,
+            set_margin_end: 10,
+            set_margin_bottom: 10,
+            #[wrap(Some)]
+            set_label_widget = &gtk::Box {
+                set_spacing: 10,
+                gtk::Label {
+                    set_css_classes: &["heading"],
+                    #[watch]
+                    set_text: &format!("{} ({})", fl!("suggestions"), model.suggestions.len()),
+                },
+                gtk::Button {
+                    set_css_classes: &["flat"],
+                    set_label: fl!("add-all"),
+                    connect_clicked => SuggestionsInput::PlanAll,
+                },
+            },
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_margin_top: 5,
+                #[name(list)]
+                gtk::ListBox {
+                    set_css_classes: &["boxed-list"],
+                    set_selection_mode: gtk::SelectionMode::None,
+                },
+            },
+        }
+    }
+
+    async fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: AsyncComponentSender<Self>,
+    ) -> AsyncComponentParts<Self> {
+        let model = SuggestionsModel {
+            suggestions: vec![],
+        };
+
+        let widgets = view_output!();
+
+        AsyncComponentParts { model, widgets }
+    }
+
+    async fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: AsyncComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            SuggestionsInput::Reload => {}
+            SuggestionsInput::Plan(task_id) => {
+                let planned: Vec<Suggestion> = self
+                    .suggestions
+                    .iter()
+                    .filter(|suggestion| suggestion.task.id == task_id)
+                    .cloned()
+                    .collect();
+                plan(planned, &sender).await;
+            }
+            SuggestionsInput::PlanAll => plan(self.suggestions.clone(), &sender).await,
+        }
+        self.suggestions = load().await;
+        self.fill_list(&widgets.list, &sender);
+        self.update_view(widgets, sender)
+    }
+}
+
+impl SuggestionsModel {
+    fn fill_list(&self, list: &gtk::ListBox, sender: &AsyncComponentSender<Self>) {
+        while let Some(child) = list.first_child() {
+            list.remove(&child);
+        }
+        for suggestion in &self.suggestions {
+            let row = adw::ActionRow::new();
+            row.set_title(&suggestion.task.title);
+            row.set_subtitle(&format!(
+                "{} · {}",
+                reason_name(suggestion.reason),
+                suggestion.service
+            ));
+            if suggestion.reason == SuggestionReason::Overdue {
+                row.add_css_class("error");
+            }
+            let button = gtk::Button::from_icon_name(icon_name::PLUS);
+            button.set_css_classes(&["flat", "circular"]);
+            button.set_valign(gtk::Align::Center);
+            button.set_tooltip(fl!("add-to-my-day"));
+            {
+                let sender = sender.clone();
+                let task_id = suggestion.task.id.clone();
+                button.connect_clicked(move |_| {
+                    sender.input(SuggestionsInput::Plan(task_id.clone()));
+                });
+            }
+            row.add_suffix(&button);
+            list.append(&row);
+        }
+    }
+}
+
+async fn load() -> Vec<Suggestion> {
+    let leftovers = Database::new(APP_ID.to_string())
+        .my_day_leftovers()
+        .unwrap_or_else(|err| {
+            tracing::error!("{err}");
+            Default::default()
+        });
+    suggestions(&Service::all_tasks().await, &leftovers, local_today())
+}
+
+async fn plan(planned: Vec<Suggestion>, sender: &AsyncComponentSender<SuggestionsModel>) {
+    for Suggestion {
+        service, mut task, ..
+    } in planned
+    {
+        task.today = true;
+        if let Err(err) = service.get_service().update_task(task).await {
+            tracing::error!("{err}");
+            sender
+                .output(SuggestionsOutput::Error(err.to_string()))
+                .unwrap_or_default();
+        }
+    }
+    sender
+        .output(SuggestionsOutput::Planned)
+        .unwrap_or_default();
+}
+
+fn reason_name(reason: SuggestionReason) -> String {
+    match reason {
+        SuggestionReason::Overdue => fl!("overdue").to_string(),
+        SuggestionReason::DueToday => fl!("due-today").to_string(),
+        SuggestionReason::Leftover => fl!("from-yesterday").to_string(),
+    }
+}
@@ -1,18 +1,30 @@
-use anyhow::Result;
-use libset::Config;
 use relm4::{
 	adw,
 	adw::prelude::{
-		ActionRowExt, AdwWindowExt, BoxExt, GtkWindowExt, OrientableExt,
-		PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt, WidgetExt,
+		ActionRowExt, AdwDialogExt, AdwWindowExt, AlertDialogExt, BoxExt,
+		ButtonExt, GtkWindowExt, OrientableExt, PreferencesGroupExt,
+		PreferencesPageExt, PreferencesRowExt, WidgetExt,
 	},
-	adw::traits::ComboRowExt,
+	adw::traits::{ComboRowExt, EntryRowExt, SpinRowExt},
 	component::{AsyncComponent, AsyncComponentParts},
-	gtk, AsyncComponentSender,
+	gtk,
+	gtk::prelude::{EditableExt, FileExt, StaticType},
+	AsyncComponentSender,
 };
 use relm4_icons::icon_name;
 
+use done_core::models::dates::{set_time_zone, time_zone_names};
+use done_core::models::login::{Login, LoginPrompt, LoginPrompter};
+use done_core::models::time_entry::{
+	time_report, time_report_csv, TimeReportGrouping,
+};
 use done_core::service::Service;
+use done_core::services::microsoft::oauth::{
+	set_oauth_settings, Authority, OAuthSettings, SignInFlow,
+};
+use done_core::services::local::database::{
+	integrity::IntegrityReport, Database,
+};
 
 use crate::app::config::preferences::Preferences;
 use crate::app::config::{appearance::ColorScheme, info::APP_ID};
@@ -21,6 +33,23 @@ use crate::fl;
 #[derive(Debug)]
 pub struct PreferencesComponentModel {
 	pub preferences: Preferences,
+	integrity_report: Option<IntegrityReport>,
+	account_rows: Vec<adw::ActionRow>,
+	oauth: OAuthSettings,
+	/// Whether sign-in is limited to the tenant below.
+	single_tenant: bool,
+	tenant: String,
+	/// Shows the code of a device code sign-in until it finishes.
+	login_dialog: Option<adw::AlertDialog>,
+	/// Bumped on every change of the daily capacity, only the last one of a
+	/// burst gets saved.
+	capacity_generation: u32,
+}
+
+#[derive(Debug)]
+pub enum PreferencesComponentCommand {
+	LoginFinished(Service, Result<Login, String>),
+	SaveDailyCapacity(u32, u32),
 }
 
 #[derive(Debug)]
@@ -28,18 +57,38 @@ pub enum PreferencesComponentInput {
 	SetColorScheme(ColorScheme),
 	ExpandSubTasks,
 	MicrosoftLogin,
-	MicrosoftLogout,
+	/// Signs in to the account again, or to a new one.
+	MicrosoftRelogin(Service),
+	MicrosoftLogout(Service),
+	RefreshAccounts,
+	SetClientId(String),
+	SetAuthority(u32),
+	SetTenant(String),
+	SetScopes(String),
+	SetSignInFlow(u32),
+	ShowLoginPrompt(LoginPrompt),
+	ResetOAuth,
+	CheckDatabase,
+	RepairDatabase,
+	SetPomodoroWork(u32),
+	SetPomodoroBreak(u32),
+	SetDailyCapacity(u32),
+	SetTimeZone(Option<String>),
+	ExportTimeReport(TimeReportGrouping),
 }
 
 #[derive(Debug)]
 pub enum PreferencesComponentOutput {
+	ServiceEnabled(Service),
 	ServiceDisabled(Service),
 	ExpandSubTasks(bool),
+	DailyCapacityChanged(u32),
+	DatabaseRepaired,
 }
 
 #[relm4::component(pub async)]
 impl AsyncComponent for PreferencesComponentModel {
-	type CommandOutput = ();
+	type CommandOutput = PreferencesComponentCommand;
 	type Input = PreferencesComponentInput;
 	type Output = PreferencesComponentOutput;
 	type Init = ();
@@ -97,22 +146,203 @@ impl AsyncComponent for PreferencesComponentModel {
 									connect_active_notify => PreferencesComponentInput::ExpandSubTasks
 								}
 							},
+							#[name = "services_group"]
 							add = &adw::PreferencesGroup {
 								set_title: fl!("services"),
-								adw::SwitchRow {
-									set_title: "Microsoft To Do",
-									set_subtitle: fl!("msft-todo-description"),
+								set_description: Some(fl!("msft-todo-description")),
+								#[wrap(Some)]
+								set_header_suffix = &gtk::Button {
+									set_valign: gtk::Align::Center,
+									set_css_classes: &["flat"],
+									set_tooltip_text: Some(fl!("add-microsoft-account")),
+									set_icon_name: icon_name::PLUS,
+									connect_clicked => PreferencesComponentInput::MicrosoftLogin,
+								},
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("microsoft-sign-in"),
+								set_description: Some(fl!("microsoft-sign-in-desc")),
+								#[wrap(Some)]
+								set_header_suffix = &gtk::Button {
+									set_valign: gtk::Align::Center,
+									set_css_classes: &["flat"],
+									set_label: fl!("reset"),
+									#[watch]
+									set_sensitive: model.preferences.microsoft_oauth.is_some(),
+									connect_clicked => PreferencesComponentInput::ResetOAuth,
+								},
+								#[name = "client_id_row"]
+								adw::EntryRow {
+									set_title: fl!("client-id"),
+									set_text: &model.oauth.client_id,
+									set_show_apply_button: true,
+									connect_apply[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetClientId(row.text().to_string()));
+									}
+								},
+								#[name = "authority_row"]
+								adw::ComboRow {
+									set_title: fl!("account-type"),
+									set_model: Some(&gtk::StringList::new(&[
+										fl!("personal-accounts"),
+										fl!("work-accounts"),
+										fl!("any-accounts"),
+										fl!("single-tenant"),
+									])),
+									set_selected: authority_position(&model.oauth.authority),
+									connect_selected_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetAuthority(row.selected()));
+									}
+								},
+								#[name = "tenant_row"]
+								adw::EntryRow {
+									set_title: fl!("tenant"),
+									set_text: &model.tenant,
+									set_show_apply_button: true,
+									#[watch]
+									set_visible: model.single_tenant,
+									connect_apply[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetTenant(row.text().to_string()));
+									}
+								},
+								#[name = "flow_row"]
+								adw::ComboRow {
+									set_title: fl!("sign-in-method"),
+									set_model: Some(&gtk::StringList::new(&[
+										fl!("sign-in-automatic"),
+										fl!("sign-in-link"),
+										fl!("sign-in-loopback"),
+										fl!("sign-in-device-code"),
+									])),
+									set_selected: SignInFlow::ALL
+										.iter()
+										.position(|flow| *flow == model.oauth.flow)
+										.unwrap_or_default() as u32,
+									connect_selected_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetSignInFlow(row.selected()));
+									}
+								},
+								#[name = "scopes_row"]
+								adw::EntryRow {
+									set_title: fl!("scopes"),
+									set_text: &model.oauth.scopes.join(" "),
+									set_show_apply_button: true,
+									connect_apply[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetScopes(row.text().to_string()));
+									}
+								},
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("time-tracking"),
+								adw::SpinRow {
+									set_title: fl!("pomodoro-work"),
+									set_subtitle: fl!("pomodoro-work-desc"),
 									add_prefix = &gtk::Image {
-										set_resource: Some(Service::Microsoft.icon())
+										set_icon_name: Some(icon_name::ALARM),
 									},
-									set_active: Service::Microsoft.get_service().available(),
-									connect_active_notify[sender] => move |switch| {
-										if switch.is_active() {
-											sender.input_sender().send(PreferencesComponentInput::MicrosoftLogin).unwrap();
-										} else {
-											sender.input_sender().send(PreferencesComponentInput::MicrosoftLogout).unwrap();
-										}
+									set_adjustment: Some(&gtk::Adjustment::new(
+										model.preferences.pomodoro_work_minutes as f64, 1.0, 180.0, 1.0, 5.0, 0.0
+									)),
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetPomodoroWork(row.value() as u32));
+									}
+								},
+								adw::SpinRow {
+									set_title: fl!("pomodoro-break"),
+									set_subtitle: fl!("pomodoro-break-desc"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::ALARM),
+									},
+									set_adjustment: Some(&gtk::Adjustment::new(
+										model.preferences.pomodoro_break_minutes as f64, 1.0, 60.0, 1.0, 5.0, 0.0
+									)),
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetPomodoroBreak(row.value() as u32));
+									}
+								},
+								adw::ActionRow {
+									set_title: fl!("time-report"),
+									set_subtitle: fl!("time-report-desc"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::CLIPBOARD),
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("by-list"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::ExportTimeReport(TimeReportGrouping::List),
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("by-tag"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::ExportTimeReport(TimeReportGrouping::Tag),
+									},
+								}
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("planning"),
+								adw::SpinRow {
+									set_title: fl!("daily-capacity"),
+									set_subtitle: fl!("daily-capacity-desc"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::WORK_WEEK),
+									},
+									set_adjustment: Some(&gtk::Adjustment::new(
+										model.preferences.daily_capacity_minutes as f64, 15.0, 1440.0, 15.0, 60.0, 0.0
+									)),
+									connect_value_notify[sender] => move |row| {
+										sender.input(PreferencesComponentInput::SetDailyCapacity(row.value() as u32));
 									}
+								},
+								adw::ComboRow {
+									set_title: fl!("time-zone"),
+									set_subtitle: fl!("time-zone-desc"),
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::CLOCK),
+									},
+									set_enable_search: true,
+									set_expression: Some(&gtk::PropertyExpression::new(
+										gtk::StringObject::static_type(),
+										None::<gtk::Expression>,
+										"string",
+									)),
+									set_model: Some(&time_zones),
+									set_selected: model.preferences.time_zone.as_deref()
+										.and_then(|name| time_zone_names().position(|zone| zone == name))
+										.map_or(0, |position| position as u32 + 1),
+									connect_selected_notify[sender] => move |row| {
+										let zone = row.selected().checked_sub(1)
+											.and_then(|position| time_zone_names().nth(position as usize))
+											.map(str::to_string);
+										sender.input(PreferencesComponentInput::SetTimeZone(zone));
+									}
+								},
+							},
+							add = &adw::PreferencesGroup {
+								set_title: fl!("maintenance"),
+								adw::ActionRow {
+									set_title: fl!("database-integrity"),
+									#[watch]
+									set_subtitle: &match &model.integrity_report {
+										Some(report) if report.is_clean() => fl!("database-clean").clone(),
+										Some(report) => fl!("database-issues", count = report.issue_count()).clone(),
+										None => fl!("database-integrity-desc").clone(),
+									},
+									add_prefix = &gtk::Image {
+										set_icon_name: Some(icon_name::CHECK_ROUND_OUTLINE2),
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("check"),
+										set_valign: gtk::Align::Center,
+										connect_clicked => PreferencesComponentInput::CheckDatabase,
+									},
+									add_suffix = &gtk::Button {
+										set_label: fl!("repair"),
+										set_css_classes: &["destructive-action"],
+										set_valign: gtk::Align::Center,
+										#[watch]
+										set_visible: model.integrity_report.as_ref().is_some_and(|report| report.repairable()),
+										connect_clicked => PreferencesComponentInput::RepairDatabase,
+									},
 								}
 							}
 						}
@@ -127,16 +357,27 @@ impl AsyncComponent for PreferencesComponentModel {
 		root: Self::Root,
 		sender: AsyncComponentSender<Self>,
 	) -> AsyncComponentParts<Self> {
-		let preferences = if let Ok(config) = Config::new(APP_ID, 1, None) {
-			config.get_json("preferences").unwrap_or(Preferences::new())
-		} else {
-			Preferences::new()
+		let mut model = Self {
+			preferences: Preferences::load(),
+			integrity_report: None,
+			account_rows: vec![],
+			oauth: OAuthSettings::default(),
+			single_tenant: false,
+			tenant: String::new(),
+			login_dialog: None,
+			capacity_generation: 0,
 		};
+		model.load_oauth();
 
-		let model = Self { preferences };
+		let time_zones = gtk::StringList::new(&[fl!("system-time-zone")]);
+		for zone in time_zone_names() {
+			time_zones.append(zone);
+		}
 
 		let widgets = view_output!();
 
+		model.refresh_accounts(&widgets.services_group, &sender);
+
 		AsyncComponentParts { model, widgets }
 	}
 
@@ -145,37 +386,24 @@ impl AsyncComponent for PreferencesComponentModel {
 		widgets: &mut Self::Widgets,
 		message: Self::Input,
 		sender: AsyncComponentSender<Self>,
-		_root: &Self::Root,
+		root: &Self::Root,
 	) {
 		match message {
 			PreferencesComponentInput::SetColorScheme(color_scheme) => {
-				match color_scheme {
-					ColorScheme::Dark => {
-						adw::StyleManager::default()
-							.set_color_scheme(adw::ColorScheme::ForceDark);
-						self.preferences.color_scheme = ColorScheme::Dark;
-					},
-					ColorScheme::Light => {
-						adw::StyleManager::default()
-							.set_color_scheme(adw::ColorScheme::ForceLight);
-						self.preferences.color_scheme = ColorScheme::Light;
-					},
-					ColorScheme::Default => {
-						adw::StyleManager::default()
-							.set_color_scheme(adw::ColorScheme::Default);
-						self.preferences.color_scheme = ColorScheme::Default;
-					},
-				}
-
-				if let Err(err) = update_preferences(&self.preferences) {
-					tracing::error!("{err}")
-				}
+				adw::StyleManager::default().set_color_scheme(match color_scheme {
+					ColorScheme::Dark => adw::ColorScheme::ForceDark,
+					ColorScheme::Light => adw::ColorScheme::ForceLight,
+					ColorScheme::Default => adw::ColorScheme::Default,
+				});
+				self.update_preferences(|preferences| {
+					preferences.color_scheme = color_scheme
+				});
 			},
 			PreferencesComponentInput::ExpandSubTasks => {
-				self.preferences.expand_subtasks = !self.preferences.expand_subtasks;
-				if let Err(err) = update_preferences(&self.preferences) {
-					tracing::error!("{err}")
-				}
+				let expand = !self.preferences.expand_subtasks;
+				self.update_preferences(|preferences| {
+					preferences.expand_subtasks = expand
+				});
 				sender
 					.output(PreferencesComponentOutput::ExpandSubTasks(
 						self.preferences.expand_subtasks,
@@ -183,33 +411,345 @@ impl AsyncComponent for PreferencesComponentModel {
 					.unwrap();
 			},
 			PreferencesComponentInput::MicrosoftLogin => {
-				let service = Service::Microsoft.get_service();
-				match service.login() {
-					Ok(_) => println!("Login started"),
-					Err(err) => eprintln!("{err}"),
-				};
+				sender.input(PreferencesComponentInput::MicrosoftRelogin(
+					Service::new_microsoft_account(),
+				));
+			},
+			PreferencesComponentInput::MicrosoftRelogin(service) => {
+				let input = sender.input_sender().clone();
+				let prompt: LoginPrompter = Box::new(move |prompt| {
+					input
+						.send(PreferencesComponentInput::ShowLoginPrompt(prompt))
+						.unwrap_or_default();
+				});
+				sender.oneshot_command(async move {
+					let login = service
+						.get_service()
+						.login(prompt)
+						.await
+						.map_err(|err| format!("{err:#}"));
+					PreferencesComponentCommand::LoginFinished(service, login)
+				});
+			},
+			PreferencesComponentInput::ShowLoginPrompt(prompt) => match prompt {
+				LoginPrompt::DeviceCode {
+					user_code,
+					verification_uri,
+					message,
+				} => {
+					let dialog =
+						adw::AlertDialog::new(Some(fl!("sign-in").as_str()), Some(&message));
+					dialog.add_responses(&[
+						("close", fl!("close").as_str()),
+						("open", fl!("copy-code-and-open").as_str()),
+					]);
+					dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+					dialog.set_close_response("close");
+					let window = root.clone();
+					dialog.connect_response(Some("open"), move |_, _| {
+						window.clipboard().set_text(&user_code);
+						gtk::UriLauncher::new(&verification_uri).launch(
+							Some(&window),
+							None::<&gtk::gio::Cancellable>,
+							|result| {
+								if let Err(err) = result {
+									tracing::error!("{err}");
+								}
+							},
+						);
+					});
+					dialog.present(Some(root));
+					self.login_dialog = Some(dialog);
+				},
 			},
-			PreferencesComponentInput::MicrosoftLogout => {
-				let service = Service::Microsoft.get_service();
-				match service.logout() {
+			PreferencesComponentInput::MicrosoftLogout(service) => {
+				match service.get_service().logout() {
 					Ok(_) => {
 						println!("Logout completed");
 						sender
-							.output(PreferencesComponentOutput::ServiceDisabled(
-								Service::Microsoft,
-							))
+							.output(PreferencesComponentOutput::ServiceDisabled(service))
 							.unwrap();
+						self.refresh_accounts(&widgets.services_group, &sender);
 					},
 					Err(err) => eprintln!("{err}"),
 				};
 			},
+			PreferencesComponentInput::RefreshAccounts => {
+				self.refresh_accounts(&widgets.services_group, &sender);
+			},
+			PreferencesComponentInput::SetClientId(client_id) => {
+				self.oauth.client_id = client_id.trim().to_string();
+				self.save_oauth();
+			},
+			PreferencesComponentInput::SetAuthority(position) => {
+				self.single_tenant = position as usize >= Authority::ALL.len();
+				if let Some(authority) = Authority::ALL.get(position as usize) {
+					self.oauth.authority = authority.to_string();
+					self.save_oauth();
+				} else if !self.tenant.is_empty() {
+					self.oauth.authority = self.tenant.clone();
+					self.save_oauth();
+				}
+			},
+			PreferencesComponentInput::SetTenant(tenant) => {
+				self.tenant = tenant.trim().to_string();
+				if !self.tenant.is_empty() {
+					self.oauth.authority = self.tenant.clone();
+					self.save_oauth();
+				}
+			},
+			PreferencesComponentInput::SetScopes(scopes) => {
+				self.oauth.scopes =
+					scopes.split_whitespace().map(str::to_string).collect();
+				self.save_oauth();
+			},
+			PreferencesComponentInput::SetSignInFlow(position) => {
+				if let Some(flow) = SignInFlow::ALL.get(position as usize) {
+					self.oauth.flow = *flow;
+					self.save_oauth();
+				}
+			},
+			PreferencesComponentInput::ResetOAuth => {
+				set_oauth_settings(None);
+				self.update_preferences(|preferences| {
+					preferences.microsoft_oauth = None
+				});
+				self.load_oauth();
+				widgets.client_id_row.set_text(&self.oauth.client_id);
+				widgets.tenant_row.set_text(&self.tenant);
+				widgets.scopes_row.set_text(&self.oauth.scopes.join(" "));
+				widgets
+					.authority_row
+					.set_selected(authority_position(&self.oauth.authority));
+				widgets.flow_row.set_selected(0);
+			},
+			PreferencesComponentInput::CheckDatabase => {
+				let mut database = Database::new(APP_ID.to_string());
+				match database.check_integrity() {
+					Ok(report) => {
+						tracing::info!("{report}");
+						self.integrity_report = Some(report);
+					},
+					Err(err) => {
+						tracing::error!("{err}");
+						widgets.overlay.add_toast(adw::Toast::new(&err.to_string()));
+					},
+				}
+			},
+			PreferencesComponentInput::RepairDatabase => {
+				if let Some(report) = self.integrity_report.take() {
+					let mut database = Database::new(APP_ID.to_string());
+					match database.repair(
+						&report,
+						fl!("recovered-list"),
+						fl!("recovered-list-description"),
+					) {
+						Ok(summary) => {
+							widgets.overlay.add_toast(adw::Toast::new(fl!(
+								"database-repaired",
+								tasks = summary.reattached_tasks,
+								sub_tasks = summary.removed_sub_tasks,
+								fields = summary.reset_fields
+							)));
+							sender
+								.output(PreferencesComponentOutput::DatabaseRepaired)
+								.unwrap_or_default();
+						},
+						Err(err) => {
+							tracing::error!("{err}");
+							widgets.overlay.add_toast(adw::Toast::new(&err.to_string()));
+						},
+					}
+				}
+			},
+			PreferencesComponentInput::SetPomodoroWork(minutes) => {
+				self.update_preferences(|preferences| preferences.pomodoro_work_minutes = minutes);
+			},
+			PreferencesComponentInput::SetPomodoroBreak(minutes) => {
+				self.update_preferences(|preferences| preferences.pomodoro_break_minutes = minutes);
+			},
+			PreferencesComponentInput::SetDailyCapacity(minutes) => {
+				self.capacity_generation += 1;
+				let generation = self.capacity_generation;
+				sender.oneshot_command(async move {
+					relm4::tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+					PreferencesComponentCommand::SaveDailyCapacity(generation, minutes)
+				});
+			},
+			PreferencesComponentInput::SetTimeZone(zone) => {
+				set_time_zone(zone.as_deref());
+				self.update_preferences(|preferences| preferences.time_zone = zone);
+			},
+			PreferencesComponentInput::ExportTimeReport(grouping) => {
+				let entries = match Database::new(APP_ID.to_string()).all_time_entries() {
+					Ok(entries) => entries,
+					Err(err) => {
+						tracing::error!("{err}");
+						widgets.overlay.add_toast(adw::Toast::new(&err.to_string()));
+						return;
+					},
+				};
+				let tasks: Vec<_> = Service::all_tasks()
+					.await
+					.into_iter()
+					.map(|(_, task)| task)
+					.collect();
+				let lists = Service::all_lists().await;
+				let csv = time_report_csv(&time_report(&entries, &tasks, &lists, grouping));
+
+				let dialog = gtk::FileDialog::builder()
+					.title(fl!("time-report").as_str())
+					.initial_name("time-report.csv")
+					.build();
+				if let Ok(file) = dialog.save_future(Some(root)).await {
+					if let Some(path) = file.path() {
+						match std::fs::write(&path, csv) {
+							Ok(_) => widgets.overlay.add_toast(adw::Toast::new(fl!(
+								"time-report-saved",
+								path = path.display().to_string()
+							))),
+							Err(err) => {
+								tracing::error!("{err}");
+								widgets.overlay.add_toast(adw::Toast::new(&err.to_string()));
+							},
+						}
+					}
+				}
+			},
+		}
+		self.update_view(widgets, sender);
+	}
+
+	async fn update_cmd_with_view(
+		&mut self,
+		widgets: &mut Self::Widgets,
+		message: Self::CommandOutput,
+		sender: AsyncComponentSender<Self>,
+		_root: &Self::Root,
+	) {
+		match message {
+			PreferencesComponentCommand::LoginFinished(service, login) => {
+				if let Some(dialog) = self.login_dialog.take() {
+					dialog.close();
+				}
+				match login {
+					Ok(Login::Completed) => {
+						tracing::info!("Login completed");
+						sender
+							.output(PreferencesComponentOutput::ServiceEnabled(service))
+							.unwrap_or_default();
+					},
+					Ok(Login::Redirect) => tracing::info!("Login started"),
+					Err(err) => {
+						tracing::error!("{err}");
+						widgets.overlay.add_toast(adw::Toast::new(&err));
+					},
+				}
+			},
+			PreferencesComponentCommand::SaveDailyCapacity(generation, minutes) => {
+				if generation == self.capacity_generation {
+					self.update_preferences(|preferences| {
+						preferences.daily_capacity_minutes = minutes
+					});
+					sender
+						.output(PreferencesComponentOutput::DailyCapacityChanged(minutes))
+						.unwrap_or_default();
+				}
+			},
 		}
 		self.update_view(widgets, sender);
 	}
 }
 
-fn update_preferences(preferences: &Preferences) -> Result<()> {
-	Config::new(APP_ID, 1, None)?
-		.set_json::<Preferences>("preferences", preferences.to_owned())?;
-	Ok(())
+impl PreferencesComponentModel {
+	fn load_oauth(&mut self) {
+		self.oauth = self.preferences.microsoft_oauth.clone().unwrap_or_default();
+		self.single_tenant = self.oauth.authority.parse::<Authority>().is_err();
+		self.tenant = if self.single_tenant {
+			self.oauth.authority.clone()
+		} else {
+			String::new()
+		};
+	}
+
+	/// Saves the OAuth settings, accounts signed in from now on use them.
+	fn save_oauth(&mut self) {
+		if let Err(err) = self.oauth.validate() {
+			tracing::warn!("{err}");
+			return;
+		}
+		let settings = Some(self.oauth.clone())
+			.filter(|settings| *settings != OAuthSettings::default());
+		set_oauth_settings(settings.clone());
+		self.update_preferences(|preferences| {
+			preferences.microsoft_oauth = settings
+		});
+	}
+
+	/// Saves a change on top of the preferences on disk, which the content
+	/// view and the board also write to.
+	fn update_preferences(&mut self, change: impl FnOnce(&mut Preferences)) {
+		match Preferences::update(change) {
+			Ok(preferences) => self.preferences = preferences,
+			Err(err) => tracing::error!("{err}"),
+		}
+	}
+
+	fn refresh_accounts(
+		&mut self,
+		group: &adw::PreferencesGroup,
+		sender: &AsyncComponentSender<Self>,
+	) {
+		for row in self.account_rows.drain(..) {
+			group.remove(&row);
+		}
+		for service in Service::accounts() {
+			let row = adw::ActionRow::builder()
+				.title(service.label().replace('\n', " — "))
+				.subtitle(if service.needs_login() {
+					fl!("needs-sign-in").as_str()
+				} else if service.get_service().available() {
+					fl!("signed-in").as_str()
+				} else {
+					fl!("signed-out").as_str()
+				})
+				.build();
+			row.add_prefix(&gtk::Image::from_resource(service.icon()));
+			if service.needs_login() {
+				let login = gtk::Button::builder()
+					.label(fl!("sign-in").as_str())
+					.css_classes(["suggested-action"])
+					.valign(gtk::Align::Center)
+					.build();
+				let input = sender.input_sender().clone();
+				login.connect_clicked(move |_| {
+					input
+						.send(PreferencesComponentInput::MicrosoftRelogin(service))
+						.unwrap_or_default();
+				});
+				row.add_suffix(&login);
+			}
+			let logout = gtk::Button::builder()
+				.label(fl!("sign-out").as_str())
+				.valign(gtk::Align::Center)
+				.build();
+			let input = sender.input_sender().clone();
+			logout.connect_clicked(move |_| {
+				input
+					.send(PreferencesComponentInput::MicrosoftLogout(service))
+					.unwrap_or_default();
+			});
+			row.add_suffix(&logout);
+			group.add(&row);
+			self.account_rows.push(row);
+		}
+	}
+}
+
+/// Position of the authority in the account type row, tenants come last.
+fn authority_position(authority: &str) -> u32 {
+	Authority::ALL
+		.iter()
+		.position(|known| authority.parse::<Authority>().ok() == Some(*known))
+		.unwrap_or(Authority::ALL.len()) as u32
 }
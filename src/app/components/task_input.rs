@@ -94,6 +94,7 @@ impl Component for TaskInputModel {
 				if !self.task.title.is_empty() {
 					if let SidebarList::Custom(list) = &self.parent_list {
 						self.task.parent = list.id.clone();
+						self.task.capture_links();
 						sender
 							.output(TaskInputOutput::AddTask(self.task.clone()))
 							.unwrap_or_default();
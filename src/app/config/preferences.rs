@@ -1,11 +1,50 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use libset::Config;
 use serde::{Deserialize, Serialize};
 
-use super::appearance::ColorScheme;
+use done_core::services::microsoft::oauth::OAuthSettings;
+
+use crate::app::models::board::ListView;
+
+use super::{appearance::ColorScheme, info::APP_ID};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Preferences {
     pub color_scheme: ColorScheme,
     pub expand_subtasks: bool,
+    /// View settings keyed by [`SidebarList::key`](crate::app::models::sidebar_list::SidebarList::key).
+    #[serde(default)]
+    pub list_views: HashMap<String, ListView>,
+    /// Work in progress limits keyed by board column.
+    #[serde(default)]
+    pub wip_limits: HashMap<String, u32>,
+    #[serde(default = "default_pomodoro_work_minutes")]
+    pub pomodoro_work_minutes: u32,
+    #[serde(default = "default_pomodoro_break_minutes")]
+    pub pomodoro_break_minutes: u32,
+    /// Minutes of planned effort a day can hold before it is flagged.
+    #[serde(default = "default_daily_capacity_minutes")]
+    pub daily_capacity_minutes: u32,
+    /// IANA name of the zone dates are shown in, `None` follows the system.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// OAuth client Microsoft accounts sign in with, `None` uses Done's own.
+    #[serde(default)]
+    pub microsoft_oauth: Option<OAuthSettings>,
+}
+
+fn default_pomodoro_work_minutes() -> u32 {
+    25
+}
+
+fn default_pomodoro_break_minutes() -> u32 {
+    5
+}
+
+fn default_daily_capacity_minutes() -> u32 {
+    480
 }
 
 impl Preferences {
@@ -13,6 +52,35 @@ impl Preferences {
         Self {
             color_scheme: ColorScheme::Default,
             expand_subtasks: false,
+            list_views: HashMap::new(),
+            wip_limits: HashMap::new(),
+            pomodoro_work_minutes: default_pomodoro_work_minutes(),
+            pomodoro_break_minutes: default_pomodoro_break_minutes(),
+            daily_capacity_minutes: default_daily_capacity_minutes(),
+            time_zone: None,
+            microsoft_oauth: None,
+        }
+    }
+
+    pub fn load() -> Self {
+        if let Ok(config) = Config::new(APP_ID, 1, None) {
+            config.get_json("preferences").unwrap_or(Preferences::new())
+        } else {
+            Preferences::new()
         }
     }
+
+    /// Applies `change` to the preferences currently on disk and saves them,
+    /// so settings written elsewhere since they were loaded are kept.
+    pub fn update(change: impl FnOnce(&mut Preferences)) -> Result<Preferences> {
+        let mut preferences = Self::load();
+        change(&mut preferences);
+        preferences.save()?;
+        Ok(preferences)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        Config::new(APP_ID, 1, None)?.set_json::<Preferences>("preferences", self.to_owned())?;
+        Ok(())
+    }
 }
@@ -1,7 +1,9 @@
 use anyhow::Result;
 use libset::{Config, FileType};
 
+use done_core::models::dates::set_time_zone;
 use done_core::service::Services;
+use done_core::services::microsoft::oauth::set_oauth_settings;
 
 use super::{info::APP_ID, preferences::Preferences};
 
@@ -9,6 +11,9 @@ pub(crate) fn init() -> Result<()> {
 	migrate_old_database()?;
 	ensure_app_config_exists()?;
 	Services::init(APP_ID);
+	let preferences = Preferences::load();
+	set_time_zone(preferences.time_zone.as_deref());
+	set_oauth_settings(preferences.microsoft_oauth);
 	Ok(())
 }
 
@@ -0,0 +1,101 @@
+use relm4::factory::{DynamicIndex, FactoryComponent, FactoryView};
+use relm4::{
+	gtk,
+	gtk::gdk,
+	gtk::prelude::{BoxExt, OrientableExt, ToValue, WidgetExt},
+	FactorySender, RelmWidgetExt,
+};
+use relm4_icons::icon_name;
+
+use done_core::models::priority::Priority;
+use done_core::models::task::Task;
+
+use crate::app::models::dates::format_date;
+use crate::fl;
+
+#[derive(Debug)]
+pub struct BoardCardModel {
+	pub task: Task,
+	pub column: usize,
+}
+
+#[derive(Debug, derive_new::new)]
+pub struct BoardCardInit {
+	pub task: Task,
+	pub column: usize,
+}
+
+#[relm4::factory(pub)]
+impl FactoryComponent for BoardCardModel {
+	type ParentWidget = gtk::Box;
+	type Input = ();
+	type Output = ();
+	type Init = BoardCardInit;
+	type CommandOutput = ();
+
+	view! {
+		#[root]
+		gtk::Box {
+			set_orientation: gtk::Orientation::Vertical,
+			set_css_classes: &["card"],
+			set_class_active: ("dim-label", self.task.status.is_finished()),
+			set_spacing: 5,
+			gtk::Box {
+				set_margin_all: 10,
+				set_spacing: 5,
+				gtk::Label {
+					set_hexpand: true,
+					set_halign: gtk::Align::Start,
+					set_wrap: true,
+					set_wrap_mode: gtk::pango::WrapMode::WordChar,
+					set_text: self.task.title.as_str(),
+				},
+				gtk::Image {
+					set_visible: self.task.priority == Priority::High,
+					set_icon_name: Some(icon_name::FLAG_FILLED),
+				},
+				gtk::Image {
+					set_visible: self.task.favorite,
+					set_icon_name: Some(icon_name::STAR_FILLED_ROUNDED),
+				},
+			},
+			gtk::Label {
+				set_visible: self.task.due_date.is_some(),
+				set_css_classes: &["caption", "dim-label"],
+				set_halign: gtk::Align::Start,
+				set_margin_start: 10,
+				set_margin_bottom: 10,
+				set_text: &self.task.due_date
+					.map(|date| fl!("due-on", date = format_date(date)).clone())
+					.unwrap_or_default(),
+			},
+		}
+	}
+
+	fn init_model(init: Self::Init, _index: &DynamicIndex, _sender: FactorySender<Self>) -> Self {
+		Self {
+			task: init.task,
+			column: init.column,
+		}
+	}
+
+	fn init_widgets(
+		&mut self,
+		_index: &DynamicIndex,
+		root: &Self::Root,
+		_returned_widget: &<Self::ParentWidget as FactoryView>::ReturnedWidget,
+		_sender: FactorySender<Self>,
+	) -> Self::Widgets {
+		let widgets = view_output!();
+
+		let drag_source = gtk::DragSource::new();
+		drag_source.set_actions(gdk::DragAction::MOVE);
+		let payload = format!("{}:{}", self.column, self.task.id);
+		drag_source.connect_prepare(move |_, _, _| {
+			Some(gdk::ContentProvider::for_value(&payload.to_value()))
+		});
+		root.add_controller(drag_source);
+
+		widgets
+	}
+}
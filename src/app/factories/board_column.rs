@@ -0,0 +1,203 @@
+use relm4::factory::{DynamicIndex, FactoryComponent, FactoryVecDeque, FactoryView};
+use relm4::{
+	gtk,
+	gtk::gdk,
+	gtk::prelude::{BoxExt, ButtonExt, OrientableExt, StaticType, WidgetExt},
+	FactorySender, RelmWidgetExt,
+};
+use relm4_icons::icon_name;
+
+use done_core::models::task::Task;
+
+use crate::app::models::board::BoardColumn;
+use crate::fl;
+
+use super::board_card::{BoardCardInit, BoardCardModel};
+
+#[derive(Debug)]
+pub struct BoardColumnModel {
+	pub column: BoardColumn,
+	pub count: usize,
+	/// Maximum number of cards the column should hold, `0` means no limit.
+	pub limit: u32,
+	index: DynamicIndex,
+	cards: FactoryVecDeque<BoardCardModel>,
+}
+
+#[derive(Debug, derive_new::new)]
+pub struct BoardColumnInit {
+	pub column: BoardColumn,
+	pub tasks: Vec<Task>,
+	pub limit: u32,
+}
+
+#[derive(Debug)]
+pub enum BoardColumnInput {
+	Drop(String),
+	SetLimit(u32),
+}
+
+#[derive(Debug)]
+pub enum BoardColumnOutput {
+	/// A card was dropped: source column, task id and target column.
+	Move(usize, String, usize),
+	SetLimit(String, u32),
+}
+
+impl BoardColumnModel {
+	pub fn over_limit(&self) -> bool {
+		self.limit > 0 && self.count as u32 > self.limit
+	}
+
+	pub fn full(&self) -> bool {
+		self.limit > 0 && self.count as u32 >= self.limit
+	}
+}
+
+#[relm4::factory(pub)]
+impl FactoryComponent for BoardColumnModel {
+	type ParentWidget = gtk::Box;
+	type Input = BoardColumnInput;
+	type Output = BoardColumnOutput;
+	type Init = BoardColumnInit;
+	type CommandOutput = ();
+
+	view! {
+		#[root]
+		gtk::Box {
+			set_orientation: gtk::Orientation::Vertical,
+			set_width_request: 260,
+			set_spacing: 10,
+			gtk::Box {
+				set_spacing: 5,
+				gtk::Label {
+					set_css_classes: &["heading"],
+					set_hexpand: true,
+					set_halign: gtk::Align::Start,
+					set_text: &self.column.name(),
+				},
+				gtk::Label {
+					set_css_classes: &["caption", "numeric"],
+					#[watch]
+					set_class_active: ("error", self.over_limit()),
+					#[watch]
+					set_text: &if self.limit > 0 {
+						format!("{} / {}", self.count, self.limit)
+					} else {
+						self.count.to_string()
+					},
+				},
+				gtk::MenuButton {
+					set_css_classes: &["flat", "circular"],
+					set_icon_name: icon_name::SIZE_VERTICALLY,
+					set_tooltip: fl!("wip-limit"),
+					#[wrap(Some)]
+					set_popover = &gtk::Popover {
+						gtk::Box {
+							set_orientation: gtk::Orientation::Vertical,
+							set_margin_all: 10,
+							set_spacing: 10,
+							gtk::Label {
+								set_css_classes: &["heading"],
+								set_text: fl!("wip-limit"),
+							},
+							gtk::Label {
+								set_css_classes: &["caption", "dim-label"],
+								set_text: fl!("wip-limit-desc"),
+							},
+							gtk::SpinButton {
+								set_adjustment: &gtk::Adjustment::new(
+									self.limit as f64, 0.0, 99.0, 1.0, 1.0, 0.0
+								),
+								set_numeric: true,
+								connect_value_changed[sender] => move |spin| {
+									sender.input(BoardColumnInput::SetLimit(spin.value() as u32))
+								},
+							},
+						}
+					}
+				},
+			},
+			gtk::ScrolledWindow {
+				set_vexpand: true,
+				set_hscrollbar_policy: gtk::PolicyType::Never,
+				#[local_ref]
+				cards -> gtk::Box {
+					set_orientation: gtk::Orientation::Vertical,
+					set_spacing: 10,
+					set_margin_all: 5,
+				}
+			}
+		}
+	}
+
+	fn init_model(init: Self::Init, index: &DynamicIndex, _sender: FactorySender<Self>) -> Self {
+		let mut cards = FactoryVecDeque::builder()
+			.launch(gtk::Box::default())
+			.detach();
+		{
+			let mut guard = cards.guard();
+			for task in init.tasks.iter().cloned() {
+				guard.push_back(BoardCardInit::new(task, index.current_index()));
+			}
+		}
+		Self {
+			column: init.column,
+			count: init.tasks.len(),
+			limit: init.limit,
+			index: index.clone(),
+			cards,
+		}
+	}
+
+	fn init_widgets(
+		&mut self,
+		_index: &DynamicIndex,
+		root: &Self::Root,
+		_returned_widget: &<Self::ParentWidget as FactoryView>::ReturnedWidget,
+		sender: FactorySender<Self>,
+	) -> Self::Widgets {
+		let cards = self.cards.widget();
+		let widgets = view_output!();
+
+		let drop_target =
+			gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+		{
+			let sender = sender.clone();
+			drop_target.connect_drop(move |_, value, _, _| match value.get::<String>() {
+				Ok(payload) => {
+					sender.input(BoardColumnInput::Drop(payload));
+					true
+				},
+				Err(_) => false,
+			});
+		}
+		root.add_controller(drop_target);
+
+		widgets
+	}
+
+	fn update(&mut self, message: Self::Input, sender: FactorySender<Self>) {
+		match message {
+			BoardColumnInput::Drop(payload) => {
+				if let Some((from, task_id)) = payload.split_once(':') {
+					if let Ok(from) = from.parse::<usize>() {
+						sender
+							.output(BoardColumnOutput::Move(
+								from,
+								task_id.to_string(),
+								self.index.current_index(),
+							))
+							.unwrap_or_default();
+					}
+				}
+			},
+			BoardColumnInput::SetLimit(limit) => {
+				self.limit = limit;
+				sender
+					.output(BoardColumnOutput::SetLimit(self.column.key(), limit))
+					.unwrap_or_default();
+			},
+		}
+	}
+}
@@ -0,0 +1,264 @@
+use relm4::actions::{ActionGroupName, RelmAction, RelmActionGroup};
+use relm4::factory::{
+	AsyncFactoryComponent, AsyncFactoryVecDeque, DynamicIndex, FactoryView,
+};
+use relm4::gtk::prelude::{BoxExt, OrientableExt, WidgetExt};
+use relm4::gtk::traits::GtkWindowExt;
+use relm4::{
+	gtk, AsyncFactorySender, Component, ComponentController, Controller,
+};
+use relm4_icons::icon_name;
+
+use done_core::models::{list::List, list_group::ListGroup};
+
+use crate::app::components::delete::{
+	DeleteComponent, DeleteInit, DeleteOutput,
+};
+use crate::app::components::list_dialog::{
+	ListDialogComponent, ListDialogInit, ListDialogOutput,
+};
+use crate::app::factories::task_list::{
+	TaskListFactoryInit, TaskListFactoryModel, TaskListFactoryOutput,
+};
+use crate::app::models::sidebar_list::SidebarList;
+
+pub struct ListGroupFactoryModel {
+	pub group: ListGroup,
+	pub index: DynamicIndex,
+	pub lists: AsyncFactoryVecDeque<TaskListFactoryModel>,
+	pub rename: Controller<ListDialogComponent>,
+	pub delete: Controller<DeleteComponent>,
+}
+
+#[derive(Debug, derive_new::new)]
+pub struct ListGroupFactoryInit {
+	pub group: ListGroup,
+	pub lists: Vec<List>,
+}
+
+#[derive(Debug)]
+pub enum ListGroupFactoryInput {
+	SetExpanded(bool),
+	Rename(String),
+	Delete,
+	SelectList(SidebarList),
+	RemoveList(DynamicIndex),
+}
+
+#[derive(Debug)]
+pub enum ListGroupFactoryOutput {
+	SelectList(SidebarList),
+	/// A list was dropped on a group, `None` takes it out of its group.
+	MoveList(String, Option<String>),
+	ListDeleted,
+	Deleted(DynamicIndex),
+}
+
+relm4::new_action_group!(pub(super) ListGroupActionGroup, "group");
+relm4::new_stateless_action!(RenameGroupAction, ListGroupActionGroup, "rename");
+relm4::new_stateless_action!(DeleteGroupAction, ListGroupActionGroup, "delete");
+
+#[relm4::factory(pub async)]
+impl AsyncFactoryComponent for ListGroupFactoryModel {
+	type ParentWidget = gtk::Box;
+	type CommandOutput = ();
+	type Input = ListGroupFactoryInput;
+	type Output = ListGroupFactoryOutput;
+	type Init = ListGroupFactoryInit;
+	type Widgets = ListGroupWidgets;
+
+	menu! {
+		group_menu: {
+			section! {
+				"Rename" => RenameGroupAction,
+				"Delete" => DeleteGroupAction,
+			}
+		}
+	}
+
+	view! {
+		#[root]
+		gtk::Expander {
+			set_margin_start: 10,
+			set_margin_end: 10,
+			set_margin_top: 5,
+			set_expanded: self.group.expanded,
+			connect_expanded_notify[sender] => move |expander| {
+				sender.input(ListGroupFactoryInput::SetExpanded(expander.is_expanded()))
+			},
+			#[wrap(Some)]
+			set_label_widget = &gtk::Box {
+				set_orientation: gtk::Orientation::Horizontal,
+				set_spacing: 5,
+				gtk::Image {
+					set_icon_name: Some(icon_name::FOLDER_REGULAR),
+				},
+				gtk::Label {
+					set_hexpand: true,
+					set_halign: gtk::Align::Start,
+					set_css_classes: &["heading"],
+					#[watch]
+					set_text: self.group.name.as_str(),
+				},
+				#[name(group_actions)]
+				gtk::MenuButton {
+					set_icon_name: icon_name::MORE_VERTICAL_REGULAR,
+					set_css_classes: &["flat", "image-button"],
+					set_valign: gtk::Align::Center,
+					set_menu_model: Some(&group_menu),
+				},
+			},
+		}
+	}
+
+	async fn init_model(
+		init: Self::Init,
+		index: &DynamicIndex,
+		sender: AsyncFactorySender<Self>,
+	) -> Self {
+		let mut lists = AsyncFactoryVecDeque::builder()
+			.launch(gtk::ListBox::default())
+			.forward(sender.input_sender(), |output| match output {
+				TaskListFactoryOutput::Select(list) => {
+					ListGroupFactoryInput::SelectList(list)
+				},
+				TaskListFactoryOutput::DeleteTaskList(index) => {
+					ListGroupFactoryInput::RemoveList(index)
+				},
+			});
+		{
+			let mut guard = lists.guard();
+			for list in init.lists {
+				guard.push_back(TaskListFactoryInit::new(
+					list.service,
+					SidebarList::Custom(list),
+				));
+			}
+		}
+		let rename = ListDialogComponent::builder()
+			.launch(ListDialogInit {
+				name: Some(init.group.name.clone()),
+				group: true,
+			})
+			.forward(sender.input_sender(), |message| match message {
+				ListDialogOutput::AddTaskListToSidebar(name)
+				| ListDialogOutput::RenameList(name) => {
+					ListGroupFactoryInput::Rename(name)
+				},
+			});
+		let delete = DeleteComponent::builder()
+			.launch(DeleteInit {
+				warning: "You're about to delete this group".into(),
+				delete_warning: "Its lists will be kept outside of any group.".into(),
+			})
+			.forward(sender.input_sender(), |message| match message {
+				DeleteOutput::Delete => ListGroupFactoryInput::Delete,
+			});
+		Self {
+			group: init.group,
+			index: index.clone(),
+			lists,
+			rename,
+			delete,
+		}
+	}
+
+	fn init_widgets(
+		&mut self,
+		_index: &DynamicIndex,
+		root: &Self::Root,
+		_returned_widget: &<Self::ParentWidget as FactoryView>::ReturnedWidget,
+		sender: AsyncFactorySender<Self>,
+	) -> Self::Widgets {
+		let widgets = view_output!();
+
+		let lists_widget = self.lists.widget();
+		lists_widget.set_margin_top(5);
+		lists_widget.add_css_class("boxed-list");
+		root.set_child(Some(lists_widget));
+
+		let mut actions = RelmActionGroup::<ListGroupActionGroup>::new();
+		let rename_action = {
+			let rename_widget = self.rename.widget().clone();
+			RelmAction::<RenameGroupAction>::new_stateless(move |_| {
+				rename_widget.present()
+			})
+		};
+		let delete_action = {
+			let delete_widget = self.delete.widget().clone();
+			RelmAction::<DeleteGroupAction>::new_stateless(move |_| {
+				delete_widget.present()
+			})
+		};
+		actions.add_action(rename_action);
+		actions.add_action(delete_action);
+		widgets.group_actions.insert_action_group(
+			ListGroupActionGroup::NAME,
+			Some(&actions.into_action_group()),
+		);
+
+		let drop_target = gtk::DropTarget::new(
+			gtk::glib::Type::STRING,
+			gtk::gdk::DragAction::MOVE,
+		);
+		let group = self.group.id.clone();
+		drop_target.connect_drop(move |_, value, _, _| match value.get::<String>() {
+			Ok(list) => {
+				sender
+					.output(ListGroupFactoryOutput::MoveList(list, Some(group.clone())))
+					.unwrap_or_default();
+				true
+			},
+			Err(_) => false,
+		});
+		root.add_controller(drop_target);
+
+		widgets
+	}
+
+	async fn update(
+		&mut self,
+		message: Self::Input,
+		sender: AsyncFactorySender<Self>,
+	) {
+		match message {
+			ListGroupFactoryInput::SetExpanded(expanded) => {
+				if self.group.expanded != expanded {
+					self.group.expanded = expanded;
+					let mut service = self.group.service.get_service();
+					if let Err(err) = service.update_list_group(self.group.clone()).await
+					{
+						tracing::error!("{err}");
+					}
+				}
+			},
+			ListGroupFactoryInput::Rename(name) => {
+				let mut renamed = self.group.clone();
+				renamed.name = name;
+				let mut service = self.group.service.get_service();
+				match service.update_list_group(renamed.clone()).await {
+					Ok(_) => self.group = renamed,
+					Err(err) => tracing::error!("{err}"),
+				}
+			},
+			ListGroupFactoryInput::Delete => {
+				let mut service = self.group.service.get_service();
+				match service.delete_list_group(self.group.id.clone()).await {
+					Ok(_) => sender
+						.output(ListGroupFactoryOutput::Deleted(self.index.clone()))
+						.unwrap_or_default(),
+					Err(err) => tracing::error!("{err}"),
+				}
+			},
+			ListGroupFactoryInput::SelectList(list) => sender
+				.output(ListGroupFactoryOutput::SelectList(list))
+				.unwrap_or_default(),
+			ListGroupFactoryInput::RemoveList(index) => {
+				self.lists.guard().remove(index.current_index());
+				sender
+					.output(ListGroupFactoryOutput::ListDeleted)
+					.unwrap_or_default();
+			},
+		}
+	}
+}
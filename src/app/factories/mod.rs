@@ -1,3 +1,6 @@
+pub mod board_card;
+pub mod board_column;
+pub mod list_group;
 pub mod service;
 pub mod sub_tasks;
 pub mod task;
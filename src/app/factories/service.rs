@@ -35,7 +35,7 @@ impl AsyncFactoryComponent for ServiceFactoryModel {
         #[root]
         gtk::ToggleButton {
             set_hexpand: true,
-            set_tooltip: &self.service.to_string(),
+            set_tooltip: &self.service.label(),
             set_css_classes: &["card"],
             gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
@@ -50,7 +50,7 @@ impl AsyncFactoryComponent for ServiceFactoryModel {
                     set_justify: gtk::Justification::Center,
                     set_wrap: true,
                     set_css_classes: &["caption"],
-                    set_text: &self.service.to_string(),
+                    set_text: &self.service.label(),
                 }
             },
             connect_clicked[sender, index] => move |_| {
@@ -1,17 +1,24 @@
+use std::str::FromStr;
+
 use adw::traits::{EntryRowExt, PreferencesRowExt};
+use chrono::{DateTime, Datelike, Utc};
 use gtk::traits::{ButtonExt, CheckButtonExt, ListBoxRowExt, WidgetExt};
 use relm4::RelmWidgetExt;
+use relm4::gtk::prelude::{BoxExt, OrientableExt, TextBufferExt, TextViewExt};
 use relm4::gtk::traits::EditableExt;
 use relm4::{
     FactorySender, adw,
-    factory::FactoryView,
+    factory::{FactoryVecDeque, FactoryView},
     gtk::{self, prelude::WidgetExt},
     prelude::{DynamicIndex, FactoryComponent},
 };
 use relm4_icons::icon_name;
 
+use done_core::models::dates::local_today;
 use done_core::models::status::Status;
+use done_core::models::task::Task;
 
+use crate::app::models::dates::format_date;
 use crate::fl;
 
 use super::{
@@ -29,41 +36,138 @@ impl FactoryComponent for SubTaskModel {
 
     view! {
         #[root]
-        adw::EntryRow {
-            set_title: "Sub task",
-            set_enable_emoji_completion: true,
-            set_show_apply_button: true,
-            set_text: self.sub_task.title.as_str(),
-            add_prefix = &gtk::CheckButton {
-                set_active: self.sub_task.status == Status::Completed,
-                connect_toggled[sender, index] => move |checkbox| {
-                    sender.input(SubTaskInput::SetStatus(index.clone(), checkbox.is_active()));
-                }
-            },
-            add_suffix = &gtk::Button {
-                set_valign: gtk::Align::Center,
-                set_icon_name: icon_name::X_CIRCULAR,
-                set_css_classes: &["error", "circular"],
-                set_tooltip: fl!("remove-sub-task"),
-                connect_clicked[sender, index] => move |_| {
-                    sender.input(SubTaskInput::Remove(index.clone()));
-                }
-            },
-            connect_activate[sender, index] => move |entry| {
-                let buffer = entry.text().to_string();
-                sender.input(SubTaskInput::ModifyTitle(index.clone(), buffer));
-            },
-            connect_apply[sender, index] => move |entry| {
-                let buffer = entry.text().to_string();
-                sender.input(SubTaskInput::ModifyTitle(index.clone(), buffer));
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            adw::EntryRow {
+                #[watch]
+                set_title: &self.sub_task.due_date
+                    .map(|date| fl!("due-on", date = format_date(date)).clone())
+                    .unwrap_or(fl!("sub-task").clone()),
+                set_enable_emoji_completion: true,
+                set_show_apply_button: true,
+                set_text: self.sub_task.title.as_str(),
+                add_prefix = &gtk::CheckButton {
+                    set_active: self.sub_task.status == Status::Completed,
+                    connect_toggled[sender, index] => move |checkbox| {
+                        sender.input(SubTaskInput::SetStatus(index.clone(), checkbox.is_active()));
+                    }
+                },
+                add_suffix = &gtk::MenuButton {
+                    set_valign: gtk::Align::Center,
+                    set_icon_name: icon_name::WORK_WEEK,
+                    set_css_classes: &["flat", "circular"],
+                    set_tooltip: fl!("details"),
+                    #[wrap(Some)]
+                    set_popover = &gtk::Popover {
+                        gtk::Box {
+                            set_orientation: gtk::Orientation::Vertical,
+                            set_margin_all: 10,
+                            set_spacing: 10,
+                            gtk::Label {
+                                set_css_classes: &["heading"],
+                                set_halign: gtk::Align::Start,
+                                set_text: fl!("due-date"),
+                            },
+                            gtk::Calendar {
+                                add_css_class: "card",
+                                set_day: self.sub_task.due_date.map(|date| date.date_naive()).unwrap_or_else(local_today).day() as i32,
+                                set_month: self.sub_task.due_date.map(|date| date.date_naive()).unwrap_or_else(local_today).month() as i32 - 1,
+                                set_year: self.sub_task.due_date.map(|date| date.date_naive()).unwrap_or_else(local_today).year(),
+                                connect_day_selected[sender, index] => move |calendar| {
+                                    if let Ok(date) = calendar.date().format("%Y-%m-%dT%H:%M:%SZ") {
+                                        if let Ok(date) = DateTime::<Utc>::from_str(date.as_str()) {
+                                            sender.input(SubTaskInput::SetDueDate(index.clone(), Some(date)))
+                                        }
+                                    }
+                                }
+                            },
+                            gtk::Button {
+                                set_label: fl!("none"),
+                                set_tooltip: fl!("unset"),
+                                connect_clicked[sender, index] => move |_| {
+                                    sender.input(SubTaskInput::SetDueDate(index.clone(), None));
+                                }
+                            },
+                            gtk::Label {
+                                set_css_classes: &["heading"],
+                                set_halign: gtk::Align::Start,
+                                set_text: fl!("notes"),
+                            },
+                            gtk::TextView {
+                                set_css_classes: &["card"],
+                                set_top_margin: 10,
+                                set_bottom_margin: 10,
+                                set_left_margin: 10,
+                                set_right_margin: 10,
+                                set_height_request: 80,
+                                set_buffer: Some(&self.notes_buffer),
+                            }
+                        }
+                    }
+                },
+                add_suffix = &gtk::Button {
+                    set_valign: gtk::Align::Center,
+                    set_icon_name: icon_name::PLUS,
+                    set_css_classes: &["flat", "circular"],
+                    set_tooltip: fl!("add-sub-task"),
+                    connect_clicked[sender, index] => move |_| {
+                        sender.input(SubTaskInput::AddChild(index.clone()));
+                    }
+                },
+                add_suffix = &gtk::Button {
+                    set_valign: gtk::Align::Center,
+                    set_icon_name: icon_name::X_CIRCULAR,
+                    set_css_classes: &["error", "circular"],
+                    set_tooltip: fl!("remove-sub-task"),
+                    connect_clicked[sender, index] => move |_| {
+                        sender.input(SubTaskInput::Remove(index.clone()));
+                    }
+                },
+                connect_activate[sender, index] => move |entry| {
+                    let buffer = entry.text().to_string();
+                    sender.input(SubTaskInput::ModifyTitle(index.clone(), buffer));
+                },
+                connect_apply[sender, index] => move |entry| {
+                    let buffer = entry.text().to_string();
+                    sender.input(SubTaskInput::ModifyTitle(index.clone(), buffer));
+                },
             },
+            #[local_ref]
+            children -> adw::PreferencesGroup {
+                set_margin_start: 20,
+                #[watch]
+                set_visible: !self.sub_task.sub_tasks.is_empty(),
+            }
         }
     }
 
-    fn init_model(init: Self::Init, index: &DynamicIndex, _sender: FactorySender<Self>) -> Self {
+    fn init_model(init: Self::Init, index: &DynamicIndex, sender: FactorySender<Self>) -> Self {
+        let notes_buffer = gtk::TextBuffer::default();
+        notes_buffer.set_text(&init.sub_task.notes);
+        {
+            let sender = sender.clone();
+            let index = index.clone();
+            notes_buffer.connect_changed(move |_| sender.input(SubTaskInput::SetNotes(index.clone())));
+        }
+
+        let mut children = FactoryVecDeque::builder()
+            .launch(adw::PreferencesGroup::default())
+            .forward(sender.input_sender(), |output| match output {
+                SubTaskOutput::Update(index, sub_task) => SubTaskInput::UpdateChild(index, sub_task),
+                SubTaskOutput::Remove(index) => SubTaskInput::RemoveChild(index),
+            });
+        {
+            let mut guard = children.guard();
+            for child in init.sub_task.sub_tasks.iter().cloned() {
+                guard.push_back(SubTaskInit::new(child));
+            }
+        }
+
         Self {
             sub_task: init.sub_task,
             index: index.clone(),
+            children,
+            notes_buffer,
         }
     }
 
@@ -74,31 +178,72 @@ impl FactoryComponent for SubTaskModel {
         _returned_widget: &<Self::ParentWidget as FactoryView>::ReturnedWidget,
         sender: FactorySender<Self>,
     ) -> Self::Widgets {
+        let children = self.children.widget();
         let widgets = view_output!();
         widgets
     }
 
     fn update(&mut self, message: Self::Input, sender: FactorySender<Self>) {
-        match message {
+        let index = match message {
             SubTaskInput::SetStatus(index, completed) => {
                 if completed {
                     self.sub_task.status = Status::Completed;
                 } else {
                     self.sub_task.status = Status::NotStarted;
                 }
-                sender
-                    .output(SubTaskOutput::Update(index, self.sub_task.clone()))
-                    .unwrap_or_default()
+                index
             }
             SubTaskInput::ModifyTitle(index, title) => {
                 self.sub_task.title = title;
+                index
+            }
+            SubTaskInput::SetDueDate(index, due_date) => {
+                self.sub_task.due_date = due_date;
+                index
+            }
+            SubTaskInput::SetNotes(index) => {
+                self.sub_task.notes = self
+                    .notes_buffer
+                    .text(
+                        &self.notes_buffer.start_iter(),
+                        &self.notes_buffer.end_iter(),
+                        false,
+                    )
+                    .to_string();
+                index
+            }
+            SubTaskInput::AddChild(index) => {
+                let child = Task::new(String::new(), self.sub_task.id.clone());
+                self.children
+                    .guard()
+                    .push_back(SubTaskInit::new(child.clone()));
+                self.sub_task.sub_tasks.push(child);
+                index
+            }
+            SubTaskInput::UpdateChild(child_index, child) => {
+                if let Some(sub_task) = self
+                    .sub_task
+                    .sub_tasks
+                    .get_mut(child_index.current_index())
+                {
+                    *sub_task = child;
+                }
+                self.index.clone()
+            }
+            SubTaskInput::RemoveChild(child_index) => {
+                self.sub_task.sub_tasks.remove(child_index.current_index());
+                self.children.guard().remove(child_index.current_index());
+                self.index.clone()
+            }
+            SubTaskInput::Remove(index) => {
                 sender
-                    .output(SubTaskOutput::Update(index, self.sub_task.clone()))
-                    .unwrap_or_default()
+                    .output(SubTaskOutput::Remove(index))
+                    .unwrap_or_default();
+                return;
             }
-            SubTaskInput::Remove(index) => sender
-                .output(SubTaskOutput::Remove(index))
-                .unwrap_or_default(),
-        }
+        };
+        sender
+            .output(SubTaskOutput::Update(index, self.sub_task.clone()))
+            .unwrap_or_default()
     }
 }
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use relm4::prelude::DynamicIndex;
 
 use done_core::models::task::Task;
@@ -6,6 +7,11 @@ use done_core::models::task::Task;
 pub enum SubTaskInput {
 	SetStatus(DynamicIndex, bool),
 	ModifyTitle(DynamicIndex, String),
+	SetDueDate(DynamicIndex, Option<DateTime<Utc>>),
+	SetNotes(DynamicIndex),
+	AddChild(DynamicIndex),
+	UpdateChild(DynamicIndex, Task),
+	RemoveChild(DynamicIndex),
 	Remove(DynamicIndex),
 }
 
@@ -1,3 +1,5 @@
+use relm4::factory::FactoryVecDeque;
+use relm4::gtk;
 use relm4::prelude::DynamicIndex;
 
 use done_core::models::task::Task;
@@ -6,6 +8,8 @@ use done_core::models::task::Task;
 pub struct SubTaskModel {
 	pub sub_task: Task,
 	pub index: DynamicIndex,
+	pub children: FactoryVecDeque<SubTaskModel>,
+	pub notes_buffer: gtk::TextBuffer,
 }
 
 #[derive(derive_new::new)]
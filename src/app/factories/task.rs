@@ -1,12 +1,11 @@
-use std::str::FromStr;
-
 use adw::prelude::{
-	ActionRowExt, BoxExt, ExpanderRowExt, OrientableExt, PreferencesGroupExt,
+	ActionRowExt, BoxExt, ComboRowExt, ExpanderRowExt, OrientableExt, PreferencesGroupExt,
 	TextBufferExt, TextViewExt, ToggleButtonExt,
 };
-use adw::traits::{EntryRowExt, PreferencesRowExt};
-use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
-use libset::Config;
+use adw::traits::{EntryRowExt, PreferencesRowExt, SpinRowExt};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Timelike};
+use relm4::gtk::gio;
+use relm4::gtk::prelude::{FileExt, GtkApplicationExt};
 use relm4::factory::{AsyncFactoryComponent, FactoryVecDeque};
 use relm4::factory::{AsyncFactorySender, DynamicIndex, FactoryView};
 use relm4::{
@@ -14,18 +13,27 @@ use relm4::{
 	gtk::prelude::{
 		ButtonExt, CheckButtonExt, EditableExt, ListBoxRowExt, WidgetExt,
 	},
-	RelmWidgetExt,
+	main_adw_application, tokio, RelmWidgetExt,
 };
 use relm4_icons::icon_name;
 
+use done_core::models::attachment::{format_size, Attachment};
+use done_core::models::dates::{all_day, from_local, local_today, to_local};
+use done_core::models::dependency::Dependency;
+use done_core::models::linked_resource::LinkedResource;
 use done_core::models::list::List;
 use done_core::models::priority::Priority;
 use done_core::models::recurrence::Day;
 use done_core::models::status::Status;
 use done_core::models::task::Task;
+use done_core::models::time_entry::{format_duration, TimeEntry};
+use done_core::models::workload::format_estimate;
+use done_core::services::local::database::Database;
 
-use crate::app::config::info::APP_ID;
 use crate::app::config::preferences::Preferences;
+use crate::app::models::board::status_name;
+use crate::app::models::dates::format_date;
+use crate::app::models::markdown::markdown_to_pango;
 use crate::fl;
 
 use super::sub_tasks::messages::SubTaskOutput;
@@ -37,25 +45,47 @@ pub struct TaskModel {
 	pub sub_tasks: FactoryVecDeque<SubTaskModel>,
 	pub parent_list: List,
 	pub index: DynamicIndex,
+	pub dependencies: Vec<Dependency>,
+	pub tracked: Duration,
+	pub timer: Option<TimeEntry>,
+	pomodoro: Option<PomodoroPhase>,
 	notes_buffer: gtk::TextBuffer,
+	/// Shows the rendered Markdown instead of the editor.
+	notes_preview: bool,
+	attachments: Vec<Attachment>,
+	attachment_rows: Vec<adw::ActionRow>,
 	preferences: Preferences,
+	database: Database,
 }
 
+/// A task row along with its local data, which the content view loads once
+/// for all of its rows.
 #[derive(derive_new::new)]
 pub struct TaskInit {
 	pub task: Task,
 	pub parent_list: List,
+	pub dependencies: Vec<Dependency>,
+	pub tracked: Duration,
+	pub timer: Option<TimeEntry>,
+	pub pomodoro: Option<PomodoroPhase>,
+	/// Shared with the other rows so they reuse one connection pool.
+	pub database: Database,
 }
 
 #[derive(Debug)]
 pub enum TaskInput {
 	SetCompleted(bool),
+	SetStatus(u32),
+	SetEstimate(u32),
 	ModifyTitle(String),
 	Favorite,
+	ToggleToday,
 	SetNotes,
+	SetNotesPreview(bool),
 	SetPriority(i32),
-	SetDueDate(Option<DateTime<Utc>>),
-	SetReminderDate(Option<DateTime<Utc>>),
+	SetDueDate(Option<NaiveDate>),
+	SetStartDate(Option<NaiveDate>),
+	SetReminderDate(Option<NaiveDate>),
 	SetReminderHour(u32),
 	SetReminderMinute(u32),
 	SetDayInRecurrence((bool, Day)),
@@ -64,18 +94,55 @@ pub enum TaskInput {
 	RemoveSubTask(DynamicIndex),
 	ExpandSubTask(bool),
 	CreateSubTask,
+	ChoosePrerequisite,
+	ChooseList,
+	SetDependencies(Vec<Dependency>),
+	/// Replaces the sub-tasks with the ones the service saved.
+	SetSubTasks(Vec<Task>),
+	/// Shows the time tracking state the content view keeps for the task.
+	SetTimeTracking(Duration, Option<TimeEntry>, Option<PomodoroPhase>),
+	ClearDependencies,
+	ToggleTimer,
+	StartPomodoro,
+	CaptureLinks,
+	OpenLink(LinkedResource),
+	ChooseAttachment,
+	OpenAttachment(Attachment),
+	RemoveAttachment(Attachment),
 }
 
 #[derive(Debug)]
 pub enum TaskOutput {
 	Remove(DynamicIndex),
 	UpdateTask(Task),
+	ChoosePrerequisite(Task),
+	ChooseList(Task),
+	ToggleTimer(Task),
+	StartPomodoro(Task),
+	Error(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroPhase {
+	Work,
+	Break,
+}
+
+#[derive(Debug)]
+pub enum TaskCommand {
+	AttachmentsLoaded(Vec<Attachment>),
+	AttachmentAdded(Attachment),
+	AttachmentRemoved(String),
+	AttachmentReady(std::path::PathBuf),
+	/// An attachment request failed, the error has already been logged.
+	AttachmentFailed(String),
 }
 
 #[derive(Debug)]
 pub enum DateType {
 	Reminder,
 	DueDate,
+	StartDate,
 }
 
 #[derive(Debug)]
@@ -87,8 +154,8 @@ pub enum DateDay {
 
 #[relm4::factory(pub async)]
 impl AsyncFactoryComponent for TaskModel {
-	type ParentWidget = adw::PreferencesGroup;
-	type CommandOutput = ();
+	type ParentWidget = gtk::ListBox;
+	type CommandOutput = TaskCommand;
 	type Input = TaskInput;
 	type Output = TaskOutput;
 	type Init = TaskInit;
@@ -99,17 +166,15 @@ impl AsyncFactoryComponent for TaskModel {
 			#[watch]
 			set_title: self.task.title.as_str(),
 			#[watch]
-			set_subtitle: &if let Some(reminder_date) = self.task.reminder_date {
-				format!("Reminder: {}", reminder_date.format("%m/%d/%Y %H:%M"))
-			} else if let Some(due_date) = self.task.due_date {
-				format!("Due: {}", due_date.format("%m/%d/%Y"))
-			}  else {
-				format!("Sub tasks: {}", self.task.sub_tasks.len())
-			},
+			set_subtitle: &self.subtitle(),
 			#[watch]
 			set_expanded: self.preferences.expand_subtasks && !self.task.sub_tasks.is_empty(),
 			#[watch]
 			set_enable_expansion: !self.task.sub_tasks.is_empty(),
+			#[watch]
+			set_class_active: ("dim-label", self.task.status.is_set_aside()),
+			#[watch]
+			set_class_active: ("overdue", self.task.is_overdue(local_today())),
 			#[name(check_button)]
 			add_prefix = &gtk::CheckButton {
 				set_tooltip: fl!("completed-tooltip"),
@@ -130,6 +195,73 @@ impl AsyncFactoryComponent for TaskModel {
 					sender.output(TaskOutput::Remove(index.clone())).unwrap()
 				}
 			},
+			#[name(status)]
+			add_suffix = &gtk::Label {
+				set_css_classes: &["caption", "accent"],
+				set_valign: gtk::Align::Center,
+				#[watch]
+				set_visible: !matches!(self.task.status, Status::NotStarted | Status::Completed),
+				#[watch]
+				set_text: &status_name(self.task.status),
+			},
+			#[name(timer)]
+			add_suffix = &gtk::Button {
+				set_css_classes: &["flat", "circular"],
+				set_valign: gtk::Align::Center,
+				#[watch]
+				set_icon_name: if self.timer.is_some() {
+					icon_name::STOP
+				} else {
+					icon_name::PLAY
+				},
+				#[watch]
+				set_tooltip: &if self.timer.is_some() {
+					fl!("stop-timer").to_string()
+				} else {
+					fl!("start-timer").to_string()
+				},
+				connect_clicked => TaskInput::ToggleTimer,
+			},
+			#[name(my_day)]
+			add_suffix = &gtk::ToggleButton {
+				set_css_classes: &["flat", "circular"],
+				set_icon_name: icon_name::IMAGE_ADJUST_BRIGHTNESS,
+				set_valign: gtk::Align::Center,
+				#[watch]
+				set_active: self.task.today,
+				#[watch]
+				set_tooltip: &if self.task.today {
+					fl!("remove-from-my-day").to_string()
+				} else {
+					fl!("add-to-my-day").to_string()
+				},
+				connect_clicked => TaskInput::ToggleToday,
+			},
+			#[name(links)]
+			add_suffix = &gtk::Box {
+				set_spacing: 5,
+				set_valign: gtk::Align::Center,
+				#[watch]
+				set_visible: !self.task.linked_resources.is_empty(),
+			},
+			#[name(attachment_indicator)]
+			add_suffix = &gtk::Image {
+				set_icon_name: Some(icon_name::MAIL_ATTACHMENT),
+				set_valign: gtk::Align::Center,
+				set_tooltip: fl!("attachments"),
+				#[watch]
+				set_visible: self.task.has_attachments,
+			},
+			#[name(blocked)]
+			add_suffix = &gtk::Label {
+				set_css_classes: &["caption", "warning"],
+				set_valign: gtk::Align::Center,
+				set_text: fl!("blocked"),
+				#[watch]
+				set_visible: self.is_blocked(),
+				#[watch]
+				set_tooltip: &self.blockers(),
+			},
 			#[name(sub_tasks_button)]
 			add_suffix = &gtk::MenuButton {
 				add_css_class: "accent",
@@ -139,6 +271,7 @@ impl AsyncFactoryComponent for TaskModel {
 				set_tooltip: fl!("details"),
 				#[wrap(Some)]
 				set_popover = &gtk::Popover {
+					connect_closed => TaskInput::CaptureLinks,
 					adw::PreferencesGroup {
 						set_margin_all: 10,
 						set_title: fl!("details"),
@@ -161,6 +294,40 @@ impl AsyncFactoryComponent for TaskModel {
 								sender.input(TaskInput::ModifyTitle(buffer));
 							},
 						},
+						#[name(status_row)]
+						add = &adw::ComboRow {
+							set_title: fl!("status"),
+							set_subtitle: fl!("status-desc"),
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::CHECK_ROUND_OUTLINE2)
+							},
+							set_model: Some(&gtk::StringList::new(
+								&Status::ALL
+									.map(status_name)
+									.iter()
+									.map(String::as_str)
+									.collect::<Vec<&str>>(),
+							)),
+							#[watch]
+							set_selected: status_position(self.task.status),
+							connect_selected_notify[sender] => move |combo_row| {
+								sender.input(TaskInput::SetStatus(combo_row.selected()));
+							},
+						},
+						#[name(estimate)]
+						add = &adw::SpinRow {
+							set_title: fl!("estimate"),
+							set_subtitle: fl!("estimate-desc"),
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::WORK_WEEK)
+							},
+							set_adjustment: Some(&gtk::Adjustment::new(
+								self.task.estimate.unwrap_or_default() as f64, 0.0, 1440.0, 15.0, 60.0, 0.0
+							)),
+							connect_value_notify[sender] => move |row| {
+								sender.input(TaskInput::SetEstimate(row.value() as u32));
+							},
+						},
 						#[name(favorite)]
 						add = &adw::ActionRow {
 							set_title: fl!("favorite"),
@@ -233,7 +400,7 @@ impl AsyncFactoryComponent for TaskModel {
 						add = &adw::ActionRow {
 							set_title: fl!("reminder"),
 							#[watch]
-							set_subtitle: &self.task.reminder_date.map(|date| date.format("%m/%d/%Y %H:%M").to_string()).unwrap_or(fl!("no-date-set").to_string()),
+							set_subtitle: &self.task.reminder_date.map(|date| to_local(date).format("%m/%d/%Y %H:%M").to_string()).unwrap_or(fl!("no-date-set").to_string()),
 							add_prefix = &gtk::Image {
 								set_icon_name: Some(icon_name::ALARM)
 							},
@@ -249,14 +416,12 @@ impl AsyncFactoryComponent for TaskModel {
 										gtk::Calendar {
 											set_margin_all: 10,
 											add_css_class: "card",
-											set_day: self.task.reminder_date.unwrap_or(Utc::now()).day() as i32,
-											set_month: self.task.reminder_date.unwrap_or(Utc::now()).month() as i32 - 1,
-											set_year: self.task.reminder_date.unwrap_or(Utc::now()).year(),
+											set_day: self.reminder_day().day() as i32,
+											set_month: self.reminder_day().month() as i32 - 1,
+											set_year: self.reminder_day().year(),
 											connect_day_selected[sender] => move |calendar| {
-												if let Ok(date) = calendar.date().format("%Y-%m-%dT%H:%M:%SZ") {
-													if let Ok(date) = DateTime::<Utc>::from_str(date.to_string().as_str()) {
-														sender.input(TaskInput::SetReminderDate(Some(date)))
-													}
+												if let Some(date) = calendar_day(calendar) {
+													sender.input(TaskInput::SetReminderDate(Some(date)))
 												}
 											}
 										},
@@ -300,7 +465,7 @@ impl AsyncFactoryComponent for TaskModel {
 										set_halign: gtk::Align::Center,
 										gtk::SpinButton {
 											set_adjustment: &gtk::Adjustment::new(
-												self.task.reminder_date.unwrap_or_default().time().hour() as f64, 0.0, 23.0, 1.0, 1.0, 0.0
+												self.reminder_time().hour() as f64, 0.0, 23.0, 1.0, 1.0, 0.0
 											),
 											set_orientation: gtk::Orientation::Horizontal,
 											set_wrap: true,
@@ -318,7 +483,7 @@ impl AsyncFactoryComponent for TaskModel {
 										},
 										gtk::SpinButton {
 											set_adjustment: &gtk::Adjustment::new(
-												self.task.reminder_date.unwrap_or_default().time().minute() as f64, 0.0, 59.0, 1.0, 1.0, 0.0
+												self.reminder_time().minute() as f64, 0.0, 59.0, 1.0, 1.0, 0.0
 											),
 											set_orientation: gtk::Orientation::Horizontal,
 											set_wrap: true,
@@ -425,15 +590,12 @@ impl AsyncFactoryComponent for TaskModel {
 										gtk::Calendar {
 											set_margin_all: 10,
 											add_css_class: "card",
-											set_day: self.task.due_date.unwrap_or(Utc::now()).day() as i32,
-											set_month: self.task.due_date.unwrap_or(Utc::now()).month() as i32 - 1,
-											set_year: self.task.due_date.unwrap_or(Utc::now()).year(),
+											set_day: self.due_day().day() as i32,
+											set_month: self.due_day().month() as i32 - 1,
+											set_year: self.due_day().year(),
 											connect_day_selected[sender] => move |calendar| {
-												if let Ok(date) = calendar.date().format("%Y-%m-%dT%H:%M:%SZ") {
-													println!("{date}");
-													if let Ok(date) = DateTime::<Utc>::from_str(date.as_str()) {
-															sender.input(TaskInput::SetDueDate(Some(date)))
-													}
+												if let Some(date) = calendar_day(calendar) {
+													sender.input(TaskInput::SetDueDate(Some(date)))
 												}
 											}
 										},
@@ -472,20 +634,194 @@ impl AsyncFactoryComponent for TaskModel {
 								}
 							},
 						},
+						add = &adw::ActionRow {
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::CALENDAR_CLOCK_REGULAR)
+							},
+							set_title: fl!("start-date"),
+							#[watch]
+							set_subtitle: &self.task.start_date.map(|date| date.format("%m/%d/%Y").to_string()).unwrap_or(fl!("start-date-desc").to_string()),
+							add_suffix = &gtk::MenuButton {
+								set_tooltip: fl!("start-date"),
+								set_icon_name: icon_name::WORK_WEEK,
+								set_valign: gtk::Align::Center,
+								#[wrap(Some)]
+								set_popover = &gtk::Popover {
+									gtk::Box {
+										set_orientation: gtk::Orientation::Vertical,
+										#[name(start_date_calendar)]
+										gtk::Calendar {
+											set_margin_all: 10,
+											add_css_class: "card",
+											set_day: self.start_day().day() as i32,
+											set_month: self.start_day().month() as i32 - 1,
+											set_year: self.start_day().year(),
+											connect_day_selected[sender] => move |calendar| {
+												if let Some(date) = calendar_day(calendar) {
+													sender.input(TaskInput::SetStartDate(Some(date)))
+												}
+											}
+										},
+										gtk::Box {
+											set_margin_all: 10,
+											set_margin_bottom: 5,
+											set_margin_top: 5,
+											set_spacing: 10,
+											gtk::Button {
+												set_hexpand: true,
+												set_label: fl!("today"),
+												set_tooltip: fl!("set-day-today"),
+												connect_clicked => TaskInput::SetDate(DateType::StartDate, DateDay::Today)
+											},
+											gtk::Button {
+												set_hexpand: true,
+												set_label: fl!("tomorrow"),
+												set_tooltip: fl!("set-day-tomorrow"),
+												connect_clicked => TaskInput::SetDate(DateType::StartDate, DateDay::Tomorrow)
+											}
+										},
+										gtk::Button {
+											set_margin_all:10,
+											set_margin_top: 5,
+											set_label: fl!("none"),
+											set_tooltip: fl!("unset"),
+											connect_clicked => TaskInput::SetDate(DateType::StartDate, DateDay::None)
+										}
+									}
+								}
+							},
+						},
+						#[name(time_tracking)]
+						add = &adw::ActionRow {
+							set_title: fl!("time-tracking"),
+							#[watch]
+							set_subtitle: &match self.pomodoro {
+								Some(PomodoroPhase::Work) => fl!("pomodoro-working").to_string(),
+								Some(PomodoroPhase::Break) => fl!("pomodoro-break-time").to_string(),
+								None => format_duration(self.tracked),
+							},
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::ALARM)
+							},
+							add_suffix = &gtk::Button {
+								set_icon_name: icon_name::TIMER,
+								set_css_classes: &["flat", "circular"],
+								set_tooltip: fl!("start-pomodoro"),
+								set_valign: gtk::Align::Center,
+								#[watch]
+								set_sensitive: self.pomodoro.is_none(),
+								connect_clicked => TaskInput::StartPomodoro,
+							},
+						},
+						#[name(dependencies)]
+						add = &adw::ActionRow {
+							set_title: fl!("blocked-by"),
+							#[watch]
+							set_subtitle: &if self.is_blocked() {
+								self.blockers()
+							} else {
+								fl!("no-prerequisites").to_string()
+							},
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::HORIZONTAL_ARROWS)
+							},
+							add_suffix = &gtk::Button {
+								set_icon_name: icon_name::PLUS,
+								set_css_classes: &["flat", "circular"],
+								set_tooltip: fl!("add-prerequisite"),
+								set_valign: gtk::Align::Center,
+								connect_clicked => TaskInput::ChoosePrerequisite,
+							},
+							add_suffix = &gtk::Button {
+								set_icon_name: icon_name::X_CIRCULAR,
+								set_css_classes: &["flat", "circular"],
+								set_tooltip: fl!("clear-prerequisites"),
+								set_valign: gtk::Align::Center,
+								#[watch]
+								set_visible: !self.dependencies.is_empty(),
+								connect_clicked => TaskInput::ClearDependencies,
+							},
+						},
+						#[name(attachments_row)]
+						add = &adw::ExpanderRow {
+							set_title: fl!("attachments"),
+							#[watch]
+							set_subtitle: &if self.attachments.is_empty() {
+								fl!("no-attachments").to_string()
+							} else {
+								self.attachments.len().to_string()
+							},
+							#[watch]
+							set_enable_expansion: !self.attachments.is_empty(),
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::MAIL_ATTACHMENT)
+							},
+							add_suffix = &gtk::Button {
+								set_icon_name: icon_name::PLUS,
+								set_css_classes: &["flat", "circular"],
+								set_tooltip: fl!("attach-file"),
+								set_valign: gtk::Align::Center,
+								connect_clicked => TaskInput::ChooseAttachment,
+							},
+						},
+						add = &adw::ActionRow {
+							set_title: fl!("list"),
+							set_subtitle: &format!(
+								"{} — {}",
+								self.parent_list.name,
+								self.parent_list.service.label().replace('\n', " · ")
+							),
+							add_prefix = &gtk::Image {
+								set_icon_name: Some(icon_name::SEND)
+							},
+							add_suffix = &gtk::Button {
+								set_label: fl!("move-to"),
+								set_css_classes: &["flat"],
+								set_valign: gtk::Align::Center,
+								connect_clicked => TaskInput::ChooseList,
+							},
+						},
 						#[name(notes)]
 						add = &gtk::ListBoxRow {
 							set_activatable: false,
 							set_margin_top: 10,
 							adw::PreferencesGroup {
 								set_description: Some(fl!("notes")),
-								add = &gtk::TextView {
-									set_css_classes: &["card"],
-									set_top_margin: 10,
-									set_bottom_margin: 10,
-									set_left_margin: 10,
-									set_right_margin: 10,
-									set_height_request: 100,
-									set_buffer: Some(&self.notes_buffer),
+								#[wrap(Some)]
+								set_header_suffix = &gtk::ToggleButton {
+									set_css_classes: &["flat"],
+									set_icon_name: icon_name::EYE_OPEN,
+									set_tooltip: fl!("preview-markdown"),
+									set_valign: gtk::Align::Center,
+									set_active: self.notes_preview,
+									connect_toggled[sender] => move |button| {
+										sender.input(TaskInput::SetNotesPreview(button.is_active()));
+									}
+								},
+								add = &gtk::Stack {
+									set_transition_type: gtk::StackTransitionType::Crossfade,
+									if self.notes_preview {
+										gtk::Label {
+											set_css_classes: &["card", "notes-preview"],
+											set_height_request: 100,
+											set_xalign: 0.0,
+											set_yalign: 0.0,
+											set_wrap: true,
+											set_selectable: true,
+											#[watch]
+											set_markup: &markdown_to_pango(&self.task.notes),
+										}
+									} else {
+										gtk::TextView {
+											set_css_classes: &["card"],
+											set_top_margin: 10,
+											set_bottom_margin: 10,
+											set_left_margin: 10,
+											set_right_margin: 10,
+											set_height_request: 100,
+											set_buffer: Some(&self.notes_buffer),
+										}
+									}
 								}
 							}
 						},
@@ -531,14 +867,37 @@ impl AsyncFactoryComponent for TaskModel {
 				}),
 			parent_list: init.parent_list,
 			index: index.clone(),
+			dependencies: init.dependencies,
+			tracked: init.tracked,
+			timer: init.timer,
+			pomodoro: init.pomodoro,
+			notes_preview: !init.task.notes.is_empty(),
 			notes_buffer,
-			preferences: if let Ok(config) = Config::new(APP_ID, 1, None) {
-				config.get_json("preferences").unwrap_or(Preferences::new())
-			} else {
-				Preferences::new()
-			},
+			attachments: vec![],
+			attachment_rows: vec![],
+			preferences: Preferences::load(),
+			database: init.database,
 		};
 
+		if model.task.has_attachments {
+			let service = model.parent_list.service;
+			let list_id = model.parent_list.id.clone();
+			let task_id = model.task.id.clone();
+			sender.oneshot_command(async move {
+				match service
+					.get_service()
+					.read_attachments(list_id, task_id)
+					.await
+				{
+					Ok(attachments) => TaskCommand::AttachmentsLoaded(attachments),
+					Err(err) => {
+						tracing::error!("Failed to read attachments: {err:#}");
+						TaskCommand::AttachmentFailed(err.to_string())
+					},
+				}
+			});
+		}
+
 		model
 			.notes_buffer
 			.connect_changed(move |_| sender.input(TaskInput::SetNotes));
@@ -561,6 +920,7 @@ impl AsyncFactoryComponent for TaskModel {
 	) -> Self::Widgets {
 		let sub_tasks = self.sub_tasks.widget();
 		let widgets = view_output!();
+		self.refresh_links(&widgets, &sender);
 		widgets
 	}
 
@@ -583,22 +943,50 @@ impl AsyncFactoryComponent for TaskModel {
 					)
 					.to_string();
 			},
+			TaskInput::SetNotesPreview(preview) => {
+				self.notes_preview = preview;
+				self.update_view(widgets, sender);
+				return;
+			},
 			TaskInput::ExpandSubTask(expand) => {
 				self.preferences.expand_subtasks = expand
 			},
-			TaskInput::SetPriority(priority) => {
-				self.task.priority = priority.into();
+			TaskInput::SetPriority(priority) => match priority.try_into() {
+				Ok(priority) => self.task.priority = priority,
+				Err(err) => tracing::error!("{err}"),
 			},
 			TaskInput::SetCompleted(toggled) => {
-				self.task.status = if toggled {
+				self.task.set_status(if toggled {
 					Status::Completed
 				} else {
 					Status::NotStarted
-				};
+				});
+			},
+			TaskInput::SetStatus(position) => {
+				match Status::ALL.get(position as usize) {
+					Some(status) if *status != self.task.status => {
+						self.task.set_status(*status)
+					},
+					_ => {
+						self.update_view(widgets, sender);
+						return;
+					},
+				}
+			},
+			TaskInput::SetEstimate(minutes) => {
+				let estimate = (minutes > 0).then_some(minutes);
+				if estimate == self.task.estimate {
+					self.update_view(widgets, sender);
+					return;
+				}
+				self.task.estimate = estimate;
 			},
 			TaskInput::Favorite => {
 				self.task.favorite = !self.task.favorite;
 			},
+			TaskInput::ToggleToday => {
+				self.task.today = !self.task.today;
+			},
 			TaskInput::ModifyTitle(title) => {
 				if title != self.task.title {
 					self.task.title = title;
@@ -606,73 +994,58 @@ impl AsyncFactoryComponent for TaskModel {
 			},
 			TaskInput::SetDate(calendar, date) => {
 				let date = match date {
-					DateDay::Today => Some(Utc::now()),
-					DateDay::Tomorrow => {
-						let date =
-							Utc::now().checked_add_signed(Duration::days(1)).unwrap();
-						Some(date)
-					},
+					DateDay::Today => Some(local_today()),
+					DateDay::Tomorrow => local_today().succ_opt(),
 					DateDay::None => None,
 				};
 				match calendar {
 					DateType::Reminder => {
 						sender.input(TaskInput::SetReminderDate(date));
 						if let Some(date) = date {
-							self.task.reminder_date = Some(date);
 							widgets.reminder_calendar.set_year(date.year());
 							widgets.reminder_calendar.set_month(date.month() as i32 - 1);
 							widgets.reminder_calendar.set_day(date.day() as i32);
-						} else {
-							self.task.reminder_date = None;
 						}
 					},
 					DateType::DueDate => {
 						sender.input(TaskInput::SetDueDate(date));
 						if let Some(date) = date {
-							self.task.due_date = Some(date);
 							widgets.due_date_calendar.set_year(date.year());
 							widgets.due_date_calendar.set_month(date.month() as i32 - 1);
 							widgets.due_date_calendar.set_day(date.day() as i32);
-						} else {
-							self.task.due_date = None;
+						}
+					},
+					DateType::StartDate => {
+						sender.input(TaskInput::SetStartDate(date));
+						if let Some(date) = date {
+							widgets.start_date_calendar.set_year(date.year());
+							widgets.start_date_calendar.set_month(date.month() as i32 - 1);
+							widgets.start_date_calendar.set_day(date.day() as i32);
 						}
 					},
 				}
 			},
 			TaskInput::SetDueDate(due_date) => {
-				if let Some(date) = due_date {
-					self.task.due_date = Some(date);
-				} else {
-					self.task.due_date = None;
-				}
+				self.task.due_date = due_date.map(all_day);
+			},
+			TaskInput::SetStartDate(start_date) => {
+				self.task.start_date = start_date.map(all_day);
 			},
 			TaskInput::SetReminderDate(reminder_date) => {
-				if let Some(date) = reminder_date {
-					self.task.reminder_date = Some(date);
-				} else {
-					self.task.reminder_date = None;
-				}
+				let time = self.reminder_time();
+				self.task.reminder_date =
+					reminder_date.map(|date| from_local(date.and_time(time)));
 			},
 			TaskInput::SetReminderHour(hour) => {
-				if let Some(date) = self.task.reminder_date {
-					if let Some(new_date) = date.with_hour(hour) {
-						self.task.reminder_date = Some(new_date);
-					}
-				} else {
-					let now = Utc::now().with_hour(hour).unwrap();
-					let now = now.with_minute(0).unwrap();
-					self.task.reminder_date = Some(now);
+				let reminder = self.reminder_day().and_time(self.reminder_time());
+				if let Some(reminder) = reminder.with_hour(hour) {
+					self.task.reminder_date = Some(from_local(reminder));
 				}
 			},
 			TaskInput::SetReminderMinute(minute) => {
-				if let Some(date) = self.task.reminder_date {
-					if let Some(new_date) = date.with_minute(minute) {
-						self.task.reminder_date = Some(new_date);
-					}
-				} else {
-					let now = Utc::now().with_hour(0).unwrap();
-					let now = now.with_minute(minute).unwrap();
-					self.task.reminder_date = Some(now);
+				let reminder = self.reminder_day().and_time(self.reminder_time());
+				if let Some(reminder) = reminder.with_minute(minute) {
+					self.task.reminder_date = Some(from_local(reminder));
 				}
 			},
 			TaskInput::SetDayInRecurrence((active, day)) => match day {
@@ -685,13 +1058,12 @@ impl AsyncFactoryComponent for TaskModel {
 				Day::Sunday => self.task.recurrence.sunday = active,
 			},
 			TaskInput::CreateSubTask => {
-				let index = self.sub_tasks.guard().push_back(SubTaskInit {
-					sub_task: Task::default(),
-				});
-				self
-					.task
+				let sub_task = Task::new(String::new(), self.task.id.clone());
+				let index = self
 					.sub_tasks
-					.insert(index.current_index(), Task::default());
+					.guard()
+					.push_back(SubTaskInit::new(sub_task.clone()));
+				self.task.sub_tasks.insert(index.current_index(), sub_task);
 			},
 			TaskInput::UpdateSubTask(index, sub_task) => {
 				self
@@ -713,6 +1085,154 @@ impl AsyncFactoryComponent for TaskModel {
 					.remove(index.current_index())
 					.unwrap();
 			},
+			TaskInput::ChoosePrerequisite => {
+				sender
+					.output_sender()
+					.send(TaskOutput::ChoosePrerequisite(self.task.clone()))
+					.unwrap_or_default();
+				return;
+			},
+			TaskInput::ChooseList => {
+				sender
+					.output_sender()
+					.send(TaskOutput::ChooseList(self.task.clone()))
+					.unwrap_or_default();
+				return;
+			},
+			TaskInput::SetDependencies(dependencies) => {
+				self.dependencies = dependencies;
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::SetSubTasks(sub_tasks) => {
+				let mut guard = self.sub_tasks.guard();
+				guard.clear();
+				for sub_task in &sub_tasks {
+					guard.push_back(SubTaskInit::new(sub_task.clone()));
+				}
+				drop(guard);
+				self.task.sub_tasks = sub_tasks;
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::ClearDependencies => {
+				for dependency in &self.dependencies {
+					if let Err(err) = self
+						.database
+						.remove_dependency(&self.task.id, &dependency.prerequisite_id)
+					{
+						tracing::error!("{err}");
+					}
+				}
+				self.dependencies.clear();
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::ToggleTimer => {
+				sender
+					.output_sender()
+					.send(TaskOutput::ToggleTimer(self.task.clone()))
+					.unwrap_or_default();
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::StartPomodoro => {
+				sender
+					.output_sender()
+					.send(TaskOutput::StartPomodoro(self.task.clone()))
+					.unwrap_or_default();
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::SetTimeTracking(tracked, timer, pomodoro) => {
+				self.tracked = tracked;
+				self.timer = timer;
+				self.pomodoro = pomodoro;
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::CaptureLinks => {
+				if !self.task.capture_links() {
+					self.update_view(widgets, sender);
+					return;
+				}
+				self.refresh_links(widgets, &sender);
+			},
+			TaskInput::OpenLink(link) => {
+				if let Err(err) = link.open() {
+					tracing::error!("Failed to open {}: {err}", link.web_url);
+				}
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::ChooseAttachment => {
+				let dialog = gtk::FileDialog::builder()
+					.title(fl!("attach-file"))
+					.modal(true)
+					.build();
+				let window = main_adw_application().active_window();
+				if let Ok(file) = dialog.open_future(window.as_ref()).await {
+					if let Some(path) = file.path() {
+						let service = self.parent_list.service;
+						let list_id = self.parent_list.id.clone();
+						let task_id = self.task.id.clone();
+						sender.oneshot_command(async move {
+							match service
+								.get_service()
+								.add_attachment(list_id, task_id, path)
+								.await
+							{
+								Ok(attachment) => TaskCommand::AttachmentAdded(attachment),
+								Err(err) => {
+									tracing::error!("Failed to attach file: {err:#}");
+									TaskCommand::AttachmentFailed(err.to_string())
+								},
+							}
+						});
+					}
+				}
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::OpenAttachment(attachment) => {
+				let service = self.parent_list.service;
+				let list_id = self.parent_list.id.clone();
+				sender.oneshot_command(async move {
+					match service
+						.get_service()
+						.open_attachment(list_id, attachment)
+						.await
+					{
+						Ok(path) => TaskCommand::AttachmentReady(path),
+						Err(err) => {
+							tracing::error!("Failed to open attachment: {err:#}");
+							TaskCommand::AttachmentFailed(err.to_string())
+						},
+					}
+				});
+				self.update_view(widgets, sender);
+				return;
+			},
+			TaskInput::RemoveAttachment(attachment) => {
+				let service = self.parent_list.service;
+				let list_id = self.parent_list.id.clone();
+				let id = attachment.id.clone();
+				sender.oneshot_command(async move {
+					match service
+						.get_service()
+						.remove_attachment(list_id, attachment)
+						.await
+					{
+						Ok(()) => TaskCommand::AttachmentRemoved(id),
+						Err(err) => {
+							tracing::error!("Failed to remove attachment: {err:#}");
+							TaskCommand::AttachmentFailed(err.to_string())
+						},
+					}
+				});
+				self.update_view(widgets, sender);
+				return;
+			},
 		}
 		sender
 			.output_sender()
@@ -720,4 +1240,201 @@ impl AsyncFactoryComponent for TaskModel {
 			.unwrap_or_default();
 		self.update_view(widgets, sender);
 	}
+
+	async fn update_cmd_with_view(
+		&mut self,
+		widgets: &mut Self::Widgets,
+		message: Self::CommandOutput,
+		sender: AsyncFactorySender<Self>,
+	) {
+		match message {
+			TaskCommand::AttachmentsLoaded(attachments) => {
+				self.attachments = attachments;
+				self.refresh_attachments(widgets, &sender);
+			},
+			TaskCommand::AttachmentAdded(attachment) => {
+				self.attachments.push(attachment);
+				self.refresh_attachments(widgets, &sender);
+			},
+			TaskCommand::AttachmentRemoved(id) => {
+				self.attachments.retain(|attachment| attachment.id != id);
+				self.refresh_attachments(widgets, &sender);
+			},
+			TaskCommand::AttachmentReady(path) => {
+				let launcher =
+					gtk::FileLauncher::new(Some(&gio::File::for_path(&path)));
+				let window = main_adw_application().active_window();
+				if let Err(err) = launcher.launch_future(window.as_ref()).await {
+					tracing::error!("Failed to open {}: {err}", path.display());
+				}
+			},
+			TaskCommand::AttachmentFailed(err) => {
+				sender
+					.output_sender()
+					.send(TaskOutput::Error(err))
+					.unwrap_or_default();
+			},
+		}
+		self.update_view(widgets, sender);
+	}
+}
+
+impl TaskModel {
+	/// Day of the reminder in the user's zone, today if there is none.
+	fn reminder_day(&self) -> NaiveDate {
+		self
+			.task
+			.reminder_date
+			.map(|date| to_local(date).date_naive())
+			.unwrap_or_else(local_today)
+	}
+
+	/// Time of the reminder in the user's zone, midnight if there is none.
+	fn reminder_time(&self) -> NaiveTime {
+		self
+			.task
+			.reminder_date
+			.map(|date| to_local(date).time())
+			.unwrap_or_default()
+	}
+
+	fn due_day(&self) -> NaiveDate {
+		self
+			.task
+			.due_date
+			.map(|date| date.date_naive())
+			.unwrap_or_else(local_today)
+	}
+
+	fn start_day(&self) -> NaiveDate {
+		self
+			.task
+			.start_date
+			.map(|date| date.date_naive())
+			.unwrap_or_else(local_today)
+	}
+
+	fn subtitle(&self) -> String {
+		let subtitle = if let Some(start_date) =
+			self.task.start_date.filter(|_| self.task.is_deferred(local_today()))
+		{
+			fl!("starts-on", date = format_date(start_date)).clone()
+		} else if let Some(reminder_date) = self.task.reminder_date {
+			format!(
+				"Reminder: {}",
+				to_local(reminder_date).format("%m/%d/%Y %H:%M")
+			)
+		} else if let Some(due_date) = self.task.due_date {
+			format!("Due: {}", due_date.format("%m/%d/%Y"))
+		} else {
+			format!("Sub tasks: {}", self.task.sub_tasks.len())
+		};
+		let subtitle = match self.task.estimate {
+			Some(estimate) => format!("{subtitle} · ~{}", format_estimate(estimate)),
+			None => subtitle,
+		};
+		if self.tracked > Duration::zero() {
+			format!("{subtitle} · {}", format_duration(self.tracked))
+		} else {
+			subtitle
+		}
+	}
+
+	/// Rebuilds the chips of the task's links.
+	fn refresh_links(
+		&self,
+		widgets: &TaskWidgets,
+		sender: &AsyncFactorySender<Self>,
+	) {
+		while let Some(chip) = widgets.links.first_child() {
+			widgets.links.remove(&chip);
+		}
+		for link in &self.task.linked_resources {
+			let chip = gtk::Button::builder()
+				.label(link.display_name.as_str())
+				.tooltip_text(link.web_url.as_str())
+				.css_classes(["pill", "caption", "link-chip"])
+				.valign(gtk::Align::Center)
+				.build();
+			let sender = sender.clone();
+			let link = link.clone();
+			chip.connect_clicked(move |_| {
+				sender.input(TaskInput::OpenLink(link.clone()))
+			});
+			widgets.links.append(&chip);
+		}
+	}
+
+	/// Rebuilds the rows of the attachments expander.
+	fn refresh_attachments(
+		&mut self,
+		widgets: &mut TaskWidgets,
+		sender: &AsyncFactorySender<Self>,
+	) {
+		for row in self.attachment_rows.drain(..) {
+			widgets.attachments_row.remove(&row);
+		}
+		for attachment in &self.attachments {
+			let row = adw::ActionRow::builder()
+				.title(attachment.name.as_str())
+				.subtitle(format_size(attachment.size))
+				.activatable(true)
+				.build();
+			let remove = gtk::Button::builder()
+				.icon_name(icon_name::X_CIRCULAR)
+				.css_classes(["flat", "circular"])
+				.tooltip_text(fl!("remove-attachment"))
+				.valign(gtk::Align::Center)
+				.build();
+			{
+				let sender = sender.clone();
+				let attachment = attachment.clone();
+				row.connect_activated(move |_| {
+					sender.input(TaskInput::OpenAttachment(attachment.clone()))
+				});
+			}
+			{
+				let sender = sender.clone();
+				let attachment = attachment.clone();
+				remove.connect_clicked(move |_| {
+					sender.input(TaskInput::RemoveAttachment(attachment.clone()))
+				});
+			}
+			row.add_suffix(&remove);
+			widgets.attachments_row.add_row(&row);
+			self.attachment_rows.push(row);
+		}
+		self.task.has_attachments = !self.attachments.is_empty();
+	}
+
+	fn is_blocked(&self) -> bool {
+		self.dependencies.iter().any(|dependency| !dependency.resolved)
+	}
+
+	fn blockers(&self) -> String {
+		self
+			.dependencies
+			.iter()
+			.filter(|dependency| !dependency.resolved)
+			.map(|dependency| dependency.prerequisite_title.as_str())
+			.collect::<Vec<&str>>()
+			.join(", ")
+	}
+}
+
+fn status_position(status: Status) -> u32 {
+	Status::ALL
+		.iter()
+		.position(|candidate| *candidate == status)
+		.unwrap_or_default() as u32
+}
+
+/// The calendar day selected in a [`gtk::Calendar`].
+fn calendar_day(calendar: &gtk::Calendar) -> Option<NaiveDate> {
+	let date = calendar.date();
+	NaiveDate::from_ymd_opt(
+		date.year(),
+		date.month() as u32,
+		date.day_of_month() as u32,
+	)
 }
@@ -1,12 +1,13 @@
 use relm4::actions::{ActionGroupName, RelmAction, RelmActionGroup};
 use relm4::factory::AsyncFactoryComponent;
 use relm4::factory::{DynamicIndex, FactoryView};
-use relm4::gtk::prelude::{ListBoxRowExt, WidgetExt};
+use relm4::gtk::prelude::{ListBoxRowExt, ToValue, WidgetExt};
 use relm4::gtk::traits::{BoxExt, GtkWindowExt};
 use relm4::{
 	gtk, AsyncFactorySender, Component, ComponentController, Controller,
 	RelmWidgetExt,
 };
+use relm4_icons::icon_name;
 
 use done_core::service::Service;
 
@@ -14,7 +15,7 @@ use crate::app::components::delete::{
 	DeleteComponent, DeleteInit, DeleteOutput,
 };
 use crate::app::components::list_dialog::{
-	ListDialogComponent, ListDialogOutput,
+	ListDialogComponent, ListDialogInit, ListDialogOutput,
 };
 use crate::app::models::sidebar_list::SidebarList;
 use crate::fl;
@@ -97,6 +98,8 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 						set_label: self.list.icon().unwrap_or_default(),
 						#[watch]
 						set_visible: !self.list.smart(),
+						#[watch]
+						set_sensitive: self.list.can_rename(),
 						set_css_classes: &["flat", "image-button"],
 						set_valign: gtk::Align::Center,
 						#[wrap(Some)]
@@ -118,11 +121,24 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 						set_text: self.list.name().as_str(),
 						set_margin_all: 5,
 					},
+					gtk::Image {
+						#[watch]
+						set_visible: self.list.shared(),
+						set_icon_name: Some(icon_name::SHARE),
+						set_tooltip: fl!("shared-list"),
+					},
+					gtk::Image {
+						#[watch]
+						set_visible: self.list.read_only(),
+						set_icon_name: Some(icon_name::PADLOCK2),
+						set_tooltip: fl!("read-only-list"),
+					},
 					#[name(list_actions)]
 					gtk::MenuButton {
 						#[watch]
-						set_visible: !self.list.smart(),
-						set_icon_name: "view-more-symbolic",
+						set_visible: !self.list.smart()
+							&& (self.list.can_rename() || self.list.can_delete()),
+						set_icon_name: icon_name::MORE_VERTICAL_REGULAR,
 						set_css_classes: &["flat", "image-button"],
 						set_valign: gtk::Align::Center,
 						set_menu_model: Some(&primary_menu),
@@ -138,7 +154,10 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 		sender: AsyncFactorySender<Self>,
 	) -> Self {
 		let rename = ListDialogComponent::builder()
-			.launch(Some(init.list.name()))
+			.launch(ListDialogInit {
+				name: Some(init.list.name()),
+				..Default::default()
+			})
 			.forward(sender.input_sender(), |message| match message {
 				ListDialogOutput::AddTaskListToSidebar(_) => {
 					TaskListFactoryInput::Select
@@ -189,6 +208,8 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 			})
 		};
 
+		rename_action.set_enabled(self.list.can_rename());
+		delete_action.set_enabled(self.list.can_delete());
 		actions.add_action(rename_action);
 		actions.add_action(delete_action);
 
@@ -197,6 +218,17 @@ impl AsyncFactoryComponent for TaskListFactoryModel {
 			Some(&actions.into_action_group()),
 		);
 
+		// Lists are dragged by id into groups, see `ListGroupFactoryModel`.
+		if let SidebarList::Custom(list) = &self.list {
+			let id = list.id.clone();
+			let drag = gtk::DragSource::new();
+			drag.set_actions(gtk::gdk::DragAction::MOVE);
+			drag.connect_prepare(move |_, _, _| {
+				Some(gtk::gdk::ContentProvider::for_value(&id.to_value()))
+			});
+			root.add_controller(drag);
+		}
+
 		widgets
 	}
 
@@ -17,6 +17,7 @@ use relm4::{
 };
 use relm4_icons::icon_name;
 use std::str::FromStr;
+use std::time::Duration;
 
 use done_core::service::Service;
 
@@ -24,7 +25,7 @@ use crate::{
     app::{
         components::{
             content::ContentOutput, list_sidebar::ListSidebarOutput,
-            preferences::PreferencesComponentOutput,
+            preferences::{PreferencesComponentInput, PreferencesComponentOutput},
         },
         config::{info::PROFILE, setup},
     },
@@ -46,6 +47,10 @@ pub mod config;
 pub mod factories;
 pub mod models;
 
+/// How often tokens are checked, they're refreshed a few minutes before
+/// they expire.
+const TOKEN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 new_action_group!(pub(super) WindowActionGroup, "win");
 new_stateless_action!(pub(super) ShortcutsAction, WindowActionGroup, "show-help-overlay");
 new_stateless_action!(AboutAction, WindowActionGroup, "about");
@@ -58,6 +63,8 @@ pub struct Done {
     about_dialog: Controller<AboutDialog>,
     preferences: AsyncController<PreferencesComponentModel>,
     startup_failed: bool,
+    /// Accounts the user was already asked to sign in to again.
+    login_prompts: Vec<Service>,
 }
 
 #[derive(Debug)]
@@ -66,8 +73,12 @@ pub enum AppInput {
     ListSelected(SidebarList, Service),
     ReloadSidebar(Service),
     ExpandSubTasks(bool),
+    SetDailyCapacity(u32),
+    DatabaseRepaired,
     CollapseSidebar,
     CleanContent,
+    LoginRequired(Service),
+    SignInAgain(Service),
     Refresh,
     Quit,
 }
@@ -172,14 +183,18 @@ impl AsyncComponent for Done {
                     }
                 }
             } else {
-                #[name(outter_view)]
-                adw::OverlaySplitView {
-                    set_enable_show_gesture: true,
-                    set_sidebar_width_fraction: 0.40,
-                    #[wrap(Some)]
-                    set_sidebar = model.task_list_sidebar_controller.widget(),
+                #[name(toasts)]
+                adw::ToastOverlay {
+                    #[name(outter_view)]
                     #[wrap(Some)]
-                    set_content = model.content_controller.widget(),
+                    set_child = &adw::OverlaySplitView {
+                        set_enable_show_gesture: true,
+                        set_sidebar_width_fraction: 0.40,
+                        #[wrap(Some)]
+                        set_sidebar = model.task_list_sidebar_controller.widget(),
+                        #[wrap(Some)]
+                        set_content = model.content_controller.widget(),
+                    }
                 }
             }
         }
@@ -224,14 +239,15 @@ impl AsyncComponent for Done {
             let bytes = files[0].uri();
             let uri = reqwest::Url::from_str(bytes.to_string().as_str()).unwrap();
             let captured_sender = captured_sender.clone();
+            let Some(service) = Service::from_redirect(&uri) else {
+                tracing::error!("The redirect is not meant for an account: {uri}");
+                return;
+            };
             relm4::tokio::spawn(async move {
-                let response = Service::Microsoft
-                    .get_service()
-                    .handle_uri_params(uri)
-                    .await;
+                let response = service.get_service().handle_uri_params(uri).await;
                 match response {
                     Ok(_) => {
-                        captured_sender.input(AppInput::ReloadSidebar(Service::Microsoft));
+                        captured_sender.input(AppInput::ReloadSidebar(service));
                         tracing::info!("Token stored");
                     }
                     Err(err) => tracing::error!("An error ocurred: {}", err),
@@ -265,19 +281,25 @@ impl AsyncComponent for Done {
             preferences: PreferencesComponentModel::builder().launch(()).forward(
                 sender.input_sender(),
                 move |message| match message {
-                    PreferencesComponentOutput::ServiceDisabled(service) => {
+                    PreferencesComponentOutput::ServiceEnabled(service)
+                    | PreferencesComponentOutput::ServiceDisabled(service) => {
                         AppInput::ReloadSidebar(service)
                     }
                     PreferencesComponentOutput::ExpandSubTasks(expand) => {
                         AppInput::ExpandSubTasks(expand)
                     }
+                    PreferencesComponentOutput::DailyCapacityChanged(minutes) => {
+                        AppInput::SetDailyCapacity(minutes)
+                    }
+                    PreferencesComponentOutput::DatabaseRepaired => AppInput::DatabaseRepaired,
                 },
             ),
             startup_failed: false,
+            login_prompts: vec![],
         };
 
         match setup::init_services() {
-            Ok(_) => (),
+            Ok(_) => refresh_tokens_periodically(sender.clone()),
             Err(_) => model.startup_failed = true,
         };
 
@@ -346,6 +368,21 @@ impl AsyncComponent for Done {
                 .sender()
                 .send(ContentInput::ExpandSubTasks(expand))
                 .unwrap(),
+            AppInput::SetDailyCapacity(minutes) => self
+                .content_controller
+                .sender()
+                .send(ContentInput::SetDailyCapacity(minutes))
+                .unwrap_or_default(),
+            AppInput::DatabaseRepaired => {
+                self.task_list_sidebar_controller
+                    .sender()
+                    .send(ListSidebarInput::ReloadSidebar(Service::Computer))
+                    .unwrap_or_default();
+                self.content_controller
+                    .sender()
+                    .send(ContentInput::Reload)
+                    .unwrap_or_default();
+            }
             AppInput::CollapseSidebar => {
                 let collapsed = widgets.outter_view.shows_sidebar();
                 widgets.outter_view.set_show_sidebar(!collapsed);
@@ -367,12 +404,59 @@ impl AsyncComponent for Done {
                     .send(ContentInput::ServiceDisabled(service))
                     .unwrap_or_default();
             }
-            AppInput::ReloadSidebar(service) => self
-                .task_list_sidebar_controller
-                .sender()
-                .send(ListSidebarInput::ReloadSidebar(service))
-                .unwrap_or_default(),
+            AppInput::LoginRequired(service) => {
+                if !self.login_prompts.contains(&service) {
+                    self.login_prompts.push(service);
+                    let toast = adw::Toast::builder()
+                        .title(
+                            fl!(
+                                "login-required",
+                                account = service.label().replace('\n', " — ")
+                            )
+                            .as_str(),
+                        )
+                        .button_label(fl!("sign-in").as_str())
+                        .timeout(0)
+                        .build();
+                    let sender = sender.clone();
+                    toast.connect_button_clicked(move |_| {
+                        sender.input(AppInput::SignInAgain(service));
+                    });
+                    widgets.toasts.add_toast(toast);
+                }
+            }
+            AppInput::SignInAgain(service) => {
+                self.preferences.widget().present();
+                self.preferences
+                    .sender()
+                    .send(PreferencesComponentInput::MicrosoftRelogin(service))
+                    .unwrap_or_default();
+            }
+            AppInput::ReloadSidebar(service) => {
+                self.login_prompts.retain(|prompted| *prompted != service);
+                self.preferences
+                    .sender()
+                    .send(PreferencesComponentInput::RefreshAccounts)
+                    .unwrap_or_default();
+                self.task_list_sidebar_controller
+                    .sender()
+                    .send(ListSidebarInput::ReloadSidebar(service))
+                    .unwrap_or_default();
+            }
         }
         self.update_view(widgets, sender)
     }
 }
+
+/// Refreshes tokens ahead of their expiry for as long as the app runs, and
+/// asks the user to sign in again to accounts whose sign-in was revoked.
+fn refresh_tokens_periodically(sender: AsyncComponentSender<Done>) {
+    relm4::spawn(async move {
+        loop {
+            for service in Service::refresh_tokens().await {
+                sender.input(AppInput::LoginRequired(service));
+            }
+            relm4::tokio::time::sleep(TOKEN_REFRESH_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use done_core::models::priority::Priority;
+use done_core::models::status::Status;
+use done_core::models::task::Task;
+
+use crate::fl;
+
+use super::sorting::{ListGrouping, SortOrder};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewMode {
+	#[default]
+	List,
+	Board,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoardGrouping {
+	#[default]
+	Status,
+	Priority,
+	Tag,
+}
+
+impl BoardGrouping {
+	pub const ALL: [BoardGrouping; 3] = [
+		BoardGrouping::Status,
+		BoardGrouping::Priority,
+		BoardGrouping::Tag,
+	];
+
+	pub fn name(&self) -> String {
+		match self {
+			BoardGrouping::Status => fl!("status").to_string(),
+			BoardGrouping::Priority => fl!("importance").to_string(),
+			BoardGrouping::Tag => fl!("tags").to_string(),
+		}
+	}
+
+	/// Builds the columns of the board, tag columns are taken from `tasks`.
+	pub fn columns(&self, tasks: &[Task]) -> Vec<BoardColumn> {
+		match self {
+			BoardGrouping::Status => {
+				Status::ALL.into_iter().map(BoardColumn::Status).collect()
+			},
+			BoardGrouping::Priority => [Priority::High, Priority::Normal, Priority::Low]
+				.into_iter()
+				.map(BoardColumn::Priority)
+				.collect(),
+			BoardGrouping::Tag => {
+				let mut tags: Vec<String> =
+					tasks.iter().flat_map(|task| task.tags.clone()).collect();
+				tags.sort();
+				tags.dedup();
+				let mut columns = vec![BoardColumn::Tag(None)];
+				columns.extend(tags.into_iter().map(|tag| BoardColumn::Tag(Some(tag))));
+				columns
+			},
+		}
+	}
+}
+
+/// The view settings remembered for a sidebar list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListView {
+	pub mode: ViewMode,
+	pub grouping: BoardGrouping,
+	#[serde(default)]
+	pub sort: SortOrder,
+	/// Sections of the list view, the board has its own [`BoardGrouping`].
+	#[serde(default)]
+	pub group: ListGrouping,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardColumn {
+	Status(Status),
+	Priority(Priority),
+	/// `None` holds the tasks without any tag.
+	Tag(Option<String>),
+}
+
+impl BoardColumn {
+	/// Stable identifier used to remember the WIP limit of the column.
+	pub fn key(&self) -> String {
+		match self {
+			BoardColumn::Status(status) => format!("status:{}", status.as_str_name()),
+			BoardColumn::Priority(priority) => {
+				format!("priority:{}", priority.as_str_name())
+			},
+			BoardColumn::Tag(Some(tag)) => format!("tag:{tag}"),
+			BoardColumn::Tag(None) => "tag:".to_string(),
+		}
+	}
+
+	pub fn name(&self) -> String {
+		match self {
+			BoardColumn::Status(status) => status_name(*status),
+			BoardColumn::Priority(Priority::Low) => fl!("low").to_string(),
+			BoardColumn::Priority(Priority::Normal) => fl!("medium").to_string(),
+			BoardColumn::Priority(Priority::High) => fl!("high").to_string(),
+			BoardColumn::Tag(Some(tag)) => tag.clone(),
+			BoardColumn::Tag(None) => fl!("untagged").to_string(),
+		}
+	}
+
+	pub fn contains(&self, task: &Task) -> bool {
+		match self {
+			BoardColumn::Status(status) => task.status == *status,
+			BoardColumn::Priority(priority) => task.priority == *priority,
+			BoardColumn::Tag(Some(tag)) => task.tags.contains(tag),
+			BoardColumn::Tag(None) => task.tags.is_empty(),
+		}
+	}
+
+	/// Updates `task` so it moves from the `from` column into this one.
+	pub fn apply(&self, task: &mut Task, from: &BoardColumn) {
+		match self {
+			BoardColumn::Status(status) => task.set_status(*status),
+			BoardColumn::Priority(priority) => task.priority = *priority,
+			BoardColumn::Tag(tag) => {
+				if let BoardColumn::Tag(Some(previous)) = from {
+					task.tags.retain(|candidate| candidate != previous);
+				}
+				// Tasks only land in the untagged column once their last tag
+				// is gone, the other tags are kept.
+				if let Some(tag) = tag.as_ref().filter(|tag| !task.tags.contains(tag)) {
+					task.tags.push(tag.clone());
+				}
+			},
+		}
+	}
+}
+
+pub fn status_name(status: Status) -> String {
+	match status {
+		Status::NotStarted => fl!("not-started").to_string(),
+		Status::InProgress => fl!("in-progress").to_string(),
+		Status::Waiting => fl!("waiting").to_string(),
+		Status::Deferred => fl!("deferred").to_string(),
+		Status::Completed => fl!("completed").to_string(),
+		Status::Cancelled => fl!("cancelled").to_string(),
+	}
+}
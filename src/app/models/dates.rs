@@ -0,0 +1,18 @@
+use chrono::{DateTime, Datelike, Utc};
+use relm4::gtk::glib;
+
+/// Formats the calendar day of a date the way the user's locale writes dates.
+pub fn format_date(date: DateTime<Utc>) -> String {
+	let day = date.date_naive();
+	glib::DateTime::from_local(
+		day.year(),
+		day.month() as i32,
+		day.day() as i32,
+		0,
+		0,
+		0.0,
+	)
+	.and_then(|date| date.format("%x"))
+	.map(|formatted| formatted.to_string())
+	.unwrap_or_else(|_| day.to_string())
+}
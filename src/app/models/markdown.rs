@@ -0,0 +1,127 @@
+use pulldown_cmark::{Event, HeadingLevel, Tag, TagEnd};
+use relm4::gtk::glib;
+
+use done_core::models::notes::parse_markdown;
+
+/// Renders Markdown notes as Pango markup for a [`gtk::Label`](relm4::gtk::Label).
+/// Links, including bare URLs, are kept as `<a>` tags so they can be clicked.
+pub fn markdown_to_pango(markdown: &str) -> String {
+	let mut markup = String::new();
+	// The next number of every open list, `None` for bullet lists.
+	let mut lists: Vec<Option<u64>> = vec![];
+	let mut in_link = false;
+	let mut in_code = false;
+	for event in parse_markdown(markdown) {
+		match event {
+			Event::Start(tag) => match tag {
+				Tag::Heading { level, .. } => markup.push_str(&format!(
+					"<span weight=\"bold\" size=\"{}\">",
+					heading_size(level)
+				)),
+				Tag::Emphasis => markup.push_str("<i>"),
+				Tag::Strong => markup.push_str("<b>"),
+				Tag::Strikethrough => markup.push_str("<s>"),
+				Tag::BlockQuote(_) => markup.push_str("<i>"),
+				Tag::CodeBlock(_) => {
+					in_code = true;
+					markup.push_str("<tt>");
+				},
+				Tag::Link { dest_url, .. } => {
+					in_link = true;
+					markup.push_str(&format!(
+						"<a href=\"{}\">",
+						glib::markup_escape_text(&dest_url)
+					));
+				},
+				Tag::List(start) => {
+					if !lists.is_empty() && !markup.ends_with('\n') {
+						markup.push('\n');
+					}
+					lists.push(start);
+				},
+				Tag::Item => {
+					markup.push_str(&"    ".repeat(lists.len().saturating_sub(1)));
+					match lists.last_mut() {
+						Some(Some(number)) => {
+							markup.push_str(&format!("{number}. "));
+							*number += 1;
+						},
+						_ => markup.push_str("• "),
+					}
+				},
+				_ => {},
+			},
+			Event::End(tag) => match tag {
+				TagEnd::Heading(_) => markup.push_str("</span>\n"),
+				TagEnd::Paragraph => markup.push_str("\n\n"),
+				TagEnd::Emphasis => markup.push_str("</i>"),
+				TagEnd::Strong => markup.push_str("</b>"),
+				TagEnd::Strikethrough => markup.push_str("</s>"),
+				TagEnd::BlockQuote(_) => markup.push_str("</i>\n"),
+				TagEnd::CodeBlock => {
+					in_code = false;
+					markup.push_str("</tt>\n");
+				},
+				TagEnd::Link => {
+					in_link = false;
+					markup.push_str("</a>");
+				},
+				TagEnd::Item => {
+					if !markup.ends_with('\n') {
+						markup.push('\n');
+					}
+				},
+				TagEnd::List(_) => {
+					lists.pop();
+					if lists.is_empty() {
+						markup.push('\n');
+					}
+				},
+				_ => {},
+			},
+			Event::Text(text) if in_link || in_code => {
+				markup.push_str(&glib::markup_escape_text(&text))
+			},
+			Event::Text(text) => markup.push_str(&link_urls(&text)),
+			Event::Code(code) => markup.push_str(&format!(
+				"<tt>{}</tt>",
+				glib::markup_escape_text(&code)
+			)),
+			Event::Html(html) | Event::InlineHtml(html) => {
+				markup.push_str(&glib::markup_escape_text(&html))
+			},
+			Event::SoftBreak | Event::HardBreak => markup.push('\n'),
+			Event::Rule => markup.push_str("―――――\n\n"),
+			Event::TaskListMarker(checked) => {
+				markup.push_str(if checked { "☑ " } else { "☐ " })
+			},
+			_ => {},
+		}
+	}
+	markup.trim_end().to_string()
+}
+
+fn heading_size(level: HeadingLevel) -> &'static str {
+	match level {
+		HeadingLevel::H1 => "xx-large",
+		HeadingLevel::H2 => "x-large",
+		HeadingLevel::H3 => "large",
+		_ => "medium",
+	}
+}
+
+/// Escapes `text` and turns the bare URLs in it into links.
+fn link_urls(text: &str) -> String {
+	text
+		.split_inclusive(char::is_whitespace)
+		.map(|word| {
+			let url = word.trim_end();
+			if url.starts_with("https://") || url.starts_with("http://") {
+				let url = glib::markup_escape_text(url);
+				format!("<a href=\"{url}\">{url}</a>{}", &word[word.trim_end().len()..])
+			} else {
+				glib::markup_escape_text(word).to_string()
+			}
+		})
+		.collect()
+}
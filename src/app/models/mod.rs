@@ -1 +1,5 @@
+pub mod board;
+pub mod dates;
+pub mod markdown;
 pub mod sidebar_list;
+pub mod sorting;
@@ -12,7 +12,10 @@ pub enum SidebarList {
 	Today,
 	Starred,
 	Next7Days,
+	/// Tasks hidden until a start date that has not come yet.
+	Upcoming,
 	Done,
+	Agenda,
 	Custom(List),
 }
 
@@ -34,13 +37,17 @@ impl SidebarList {
 		let today: &String = fl!("today");
 		let starred: &String = fl!("starred");
 		let next_7_days: &String = fl!("next-7-days");
+		let upcoming: &String = fl!("upcoming");
 		let completed_list: &String = fl!("completed-list");
+		let agenda: &String = fl!("agenda");
 		match self {
 			SidebarList::All => all.clone(),
 			SidebarList::Today => today.clone(),
 			SidebarList::Starred => starred.clone(),
 			SidebarList::Next7Days => next_7_days.clone(),
+			SidebarList::Upcoming => upcoming.clone(),
 			SidebarList::Done => completed_list.clone(),
+			SidebarList::Agenda => agenda.clone(),
 			SidebarList::Custom(list) => list.name.clone(),
 		}
 	}
@@ -50,13 +57,17 @@ impl SidebarList {
 		let today_desc: &String = fl!("today-desc");
 		let starred_desc: &String = fl!("starred-desc");
 		let next_7_days_desc: &String = fl!("next-7-days-desc");
+		let upcoming_desc: &String = fl!("upcoming-desc");
 		let completed_list_desc: &String = fl!("completed-list-desc");
+		let agenda_desc: &String = fl!("agenda-desc");
 		match self {
 			SidebarList::All => all_desc.clone(),
 			SidebarList::Today => today_desc.clone(),
 			SidebarList::Starred => starred_desc.clone(),
 			SidebarList::Next7Days => next_7_days_desc.clone(),
+			SidebarList::Upcoming => upcoming_desc.clone(),
 			SidebarList::Done => completed_list_desc.clone(),
+			SidebarList::Agenda => agenda_desc.clone(),
 			SidebarList::Custom(list) => list.description.clone(),
 		}
 	}
@@ -67,12 +78,45 @@ impl SidebarList {
 			SidebarList::Today => Some(icon_name::IMAGE_ADJUST_BRIGHTNESS),
 			SidebarList::Starred => Some(icon_name::STAR_FILLED_ROUNDED),
 			SidebarList::Next7Days => Some(icon_name::WORK_WEEK),
+			SidebarList::Upcoming => Some(icon_name::CALENDAR_CLOCK_REGULAR),
 			SidebarList::Done => Some(icon_name::CHECK_ROUND_OUTLINE_WHOLE),
+			SidebarList::Agenda => Some(icon_name::CALENDAR_REGULAR),
 			SidebarList::Custom(list) => list.icon.as_deref(),
 		}
 	}
 
+	/// Identifier used to remember per-list settings.
+	pub fn key(&self) -> String {
+		match self {
+			SidebarList::All => "all".to_string(),
+			SidebarList::Today => "today".to_string(),
+			SidebarList::Starred => "starred".to_string(),
+			SidebarList::Next7Days => "next-7-days".to_string(),
+			SidebarList::Upcoming => "upcoming".to_string(),
+			SidebarList::Done => "done".to_string(),
+			SidebarList::Agenda => "agenda".to_string(),
+			SidebarList::Custom(list) => list.id.clone(),
+		}
+	}
+
 	pub fn smart(&self) -> bool {
 		!matches!(self, SidebarList::Custom(_))
 	}
+
+	pub fn shared(&self) -> bool {
+		matches!(self, SidebarList::Custom(list) if list.shared)
+	}
+
+	pub fn can_rename(&self) -> bool {
+		matches!(self, SidebarList::Custom(list) if list.can_rename())
+	}
+
+	pub fn can_delete(&self) -> bool {
+		matches!(self, SidebarList::Custom(list) if list.can_delete())
+	}
+
+	/// Whether tasks can't be added to the list.
+	pub fn read_only(&self) -> bool {
+		matches!(self, SidebarList::Custom(list) if list.read_only())
+	}
 }
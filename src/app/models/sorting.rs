@@ -0,0 +1,175 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use done_core::models::dates::local_today;
+use done_core::models::due::DueBucket;
+use done_core::models::list::List;
+use done_core::models::priority::Priority;
+use done_core::models::task::Task;
+
+use crate::fl;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+	/// The order the service returns the tasks in.
+	#[default]
+	Manual,
+	DueDate,
+	StartDate,
+	Priority,
+	Created,
+	Modified,
+	Title,
+}
+
+impl SortOrder {
+	pub const ALL: [SortOrder; 7] = [
+		SortOrder::Manual,
+		SortOrder::DueDate,
+		SortOrder::StartDate,
+		SortOrder::Priority,
+		SortOrder::Created,
+		SortOrder::Modified,
+		SortOrder::Title,
+	];
+
+	pub fn name(&self) -> String {
+		match self {
+			SortOrder::Manual => fl!("manual").to_string(),
+			SortOrder::DueDate => fl!("due-date").to_string(),
+			SortOrder::StartDate => fl!("start-date").to_string(),
+			SortOrder::Priority => fl!("importance").to_string(),
+			SortOrder::Created => fl!("created").to_string(),
+			SortOrder::Modified => fl!("modified").to_string(),
+			SortOrder::Title => fl!("title").to_string(),
+		}
+	}
+
+	/// Tasks without a due or start date go last, most recent dates come
+	/// first for creation and modification.
+	pub fn compare(&self, a: &Task, b: &Task) -> Ordering {
+		match self {
+			SortOrder::Manual => Ordering::Equal,
+			SortOrder::DueDate => dates_first(a.due_date, b.due_date),
+			SortOrder::StartDate => dates_first(a.start_date, b.start_date),
+			SortOrder::Priority => priority_rank(b.priority).cmp(&priority_rank(a.priority)),
+			SortOrder::Created => b.created_date_time.cmp(&a.created_date_time),
+			SortOrder::Modified => {
+				b.last_modified_date_time.cmp(&a.last_modified_date_time)
+			},
+			SortOrder::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ListGrouping {
+	#[default]
+	None,
+	DueBucket,
+	Priority,
+	List,
+	Tag,
+}
+
+impl ListGrouping {
+	pub const ALL: [ListGrouping; 5] = [
+		ListGrouping::None,
+		ListGrouping::DueBucket,
+		ListGrouping::Priority,
+		ListGrouping::List,
+		ListGrouping::Tag,
+	];
+
+	pub fn name(&self) -> String {
+		match self {
+			ListGrouping::None => fl!("no-grouping").to_string(),
+			ListGrouping::DueBucket => fl!("due-date").to_string(),
+			ListGrouping::Priority => fl!("importance").to_string(),
+			ListGrouping::List => fl!("list").to_string(),
+			ListGrouping::Tag => fl!("tags").to_string(),
+		}
+	}
+
+	/// The group `task` of `list` falls in, `None` when not grouping. Tasks
+	/// with several tags are grouped under the first one alphabetically.
+	pub fn group(&self, task: &Task, list: &List) -> Option<TaskGroup> {
+		let group = match self {
+			ListGrouping::None => return None,
+			ListGrouping::DueBucket => {
+				let bucket = DueBucket::of(task, local_today());
+				TaskGroup {
+					rank: bucket as u32,
+					name: due_bucket_name(bucket),
+					warning: bucket == DueBucket::Overdue,
+				}
+			},
+			ListGrouping::Priority => TaskGroup {
+				rank: 2 - priority_rank(task.priority),
+				name: match task.priority {
+					Priority::Low => fl!("low").to_string(),
+					Priority::Normal => fl!("medium").to_string(),
+					Priority::High => fl!("high").to_string(),
+				},
+				warning: false,
+			},
+			ListGrouping::List => TaskGroup {
+				rank: 0,
+				name: list.name.clone(),
+				warning: false,
+			},
+			ListGrouping::Tag => match task.tags.iter().min() {
+				Some(tag) => TaskGroup {
+					rank: 0,
+					name: tag.clone(),
+					warning: false,
+				},
+				None => TaskGroup {
+					rank: 1,
+					name: fl!("untagged").to_string(),
+					warning: false,
+				},
+			},
+		};
+		Some(group)
+	}
+}
+
+/// A section of a grouped list, sections are ordered by rank then name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TaskGroup {
+	pub rank: u32,
+	pub name: String,
+	/// Shown with a warning style, like the overdue section.
+	pub warning: bool,
+}
+
+fn dates_first(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> Ordering {
+	match (a, b) {
+		(Some(a), Some(b)) => a.cmp(&b),
+		(Some(_), None) => Ordering::Less,
+		(None, Some(_)) => Ordering::Greater,
+		(None, None) => Ordering::Equal,
+	}
+}
+
+fn priority_rank(priority: Priority) -> u32 {
+	match priority {
+		Priority::Low => 0,
+		Priority::Normal => 1,
+		Priority::High => 2,
+	}
+}
+
+pub fn due_bucket_name(bucket: DueBucket) -> String {
+	match bucket {
+		DueBucket::Overdue => fl!("overdue").to_string(),
+		DueBucket::Today => fl!("today").to_string(),
+		DueBucket::Tomorrow => fl!("tomorrow").to_string(),
+		DueBucket::ThisWeek => fl!("this-week").to_string(),
+		DueBucket::Later => fl!("later").to_string(),
+		DueBucket::NoDate => fl!("no-date").to_string(),
+	}
+}
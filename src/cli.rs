@@ -0,0 +1,67 @@
+use anyhow::Result;
+use done_core::models::login::Login;
+use done_core::service::Service;
+use done_core::services::local::database::Database;
+use done_core::services::microsoft::oauth::{
+    oauth_settings, set_oauth_settings, OAuthSettings, SignInFlow,
+};
+
+use crate::app::config::{info::APP_ID, settings};
+use crate::fl;
+
+const CHECK_DATABASE: &str = "--check-database";
+const REPAIR_DATABASE: &str = "--repair-database";
+/// Adds a Microsoft account with a device code, `=loopback` uses the browser.
+const SIGN_IN: &str = "--sign-in";
+
+/// Handles maintenance flags that run without starting the user interface.
+/// Returns `true` when a command was executed and the app should exit.
+pub fn run() -> Result<bool> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(arg) = args.iter().find(|arg| arg.starts_with(SIGN_IN)) {
+        let flow = match arg.strip_prefix(SIGN_IN) {
+            Some("=loopback") => SignInFlow::Loopback,
+            _ => SignInFlow::DeviceCode,
+        };
+        sign_in(flow)?;
+        return Ok(true);
+    }
+    let repair = args.iter().any(|arg| arg == REPAIR_DATABASE);
+    if !repair && !args.iter().any(|arg| arg == CHECK_DATABASE) {
+        return Ok(false);
+    }
+
+    let mut database = Database::new(APP_ID.to_string());
+    let report = database.check_integrity()?;
+    println!("{report}");
+
+    if repair && report.repairable() {
+        let summary = database.repair(
+            &report,
+            fl!("recovered-list"),
+            fl!("recovered-list-description"),
+        )?;
+        println!("{summary}");
+    } else if !report.is_clean() {
+        println!("Run with {REPAIR_DATABASE} to fix these issues.");
+    }
+    Ok(true)
+}
+
+fn sign_in(flow: SignInFlow) -> Result<()> {
+    settings::init()?;
+    set_oauth_settings(Some(OAuthSettings {
+        flow,
+        ..oauth_settings()
+    }));
+    let service = Service::new_microsoft_account();
+    let login = relm4::tokio::runtime::Runtime::new()?.block_on(
+        service
+            .get_service()
+            .login(Box::new(|prompt| println!("{prompt}"))),
+    )?;
+    if login == Login::Completed {
+        println!("Signed in as {}", service.label().replace('\n', " — "));
+    }
+    Ok(())
+}
@@ -1,10 +1,14 @@
 mod app;
+mod cli;
 use anyhow::Result;
 use app::App;
 // use app::Done;
 use app::config::info::APP_ID;
 use relm4::RelmApp;
 fn main() -> Result<()> {
+    if cli::run()? {
+        return Ok(());
+    }
     let app = RelmApp::new(APP_ID);
     // setup::init()?;
     app.run::<App>(());